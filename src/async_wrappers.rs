@@ -0,0 +1,52 @@
+//! Shared definitions of the async wrappers around the blocking operations.
+
+// Defines async versions of the blocking operations, running them with an
+// `asyncify` function that must be in scope at the call site.
+macro_rules! async_wrappers {
+    () => {
+        /// Creates a junction point from the specified directory to the specified target directory.
+        ///
+        /// This is an async version of [`crate::create`].
+        pub async fn create<P, Q>(target: P, junction: Q) -> std::io::Result<()>
+        where
+            P: AsRef<std::path::Path>,
+            Q: AsRef<std::path::Path>,
+        {
+            let target = target.as_ref().to_owned();
+            let junction = junction.as_ref().to_owned();
+            asyncify(move || crate::create(target, junction)).await
+        }
+
+        /// Deletes a `junction` reparse point from the specified file or directory.
+        ///
+        /// This is an async version of [`crate::delete`].
+        pub async fn delete<P: AsRef<std::path::Path>>(junction: P) -> std::io::Result<()> {
+            let junction = junction.as_ref().to_owned();
+            asyncify(move || crate::delete(junction)).await
+        }
+
+        /// Determines whether the specified path exists and refers to a junction point.
+        ///
+        /// This is an async version of [`crate::exists`].
+        pub async fn exists<P: AsRef<std::path::Path>>(junction: P) -> std::io::Result<bool> {
+            let junction = junction.as_ref().to_owned();
+            asyncify(move || crate::exists(junction)).await
+        }
+
+        /// Gets the target of the specified junction point.
+        ///
+        /// This is an async version of [`crate::get_target`].
+        pub async fn get_target<P: AsRef<std::path::Path>>(junction: P) -> std::io::Result<std::path::PathBuf> {
+            let junction = junction.as_ref().to_owned();
+            asyncify(move || crate::get_target(junction)).await
+        }
+
+        /// Gets the kind of the specified junction point.
+        ///
+        /// This is an async version of [`crate::get_kind`].
+        pub async fn get_kind<P: AsRef<std::path::Path>>(junction: P) -> std::io::Result<crate::JunctionKind> {
+            let junction = junction.as_ref().to_owned();
+            asyncify(move || crate::get_kind(junction)).await
+        }
+    };
+}
@@ -0,0 +1,137 @@
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Receives a record of every junction point change made through [`Options`](crate::Options).
+///
+/// Set with [`Options::audit_hook`](crate::Options::audit_hook). The hook is
+/// called on the calling thread once the change succeeded or failed, so that
+/// failed attempts are audited too. It is implemented for closures taking an
+/// [`OperationRecord`].
+///
+/// The methods reporting changes are `create`, `create_checked`, `create_resolved`,
+/// `create_with_base`, `create_idempotent` when it creates a junction point,
+/// `copy_junction`, `move_junction`, `set_target`, `convert_to_symlink`,
+/// `convert_to_junction`, the `delete` methods, and
+/// [`apply_batch`](crate::Options::apply_batch) for each of its operations and
+/// of their rollbacks, as well as [`JunctionOps`](crate::JunctionOps). The
+/// functions of the crate root use default options, and report to no hook.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::io;
+/// use junction::{OperationRecord, Options};
+///
+/// fn main() -> io::Result<()> {
+///     let mut opts = Options::new();
+///     opts.audit_hook(|record: &OperationRecord<'_>| {
+///         eprintln!(
+///             "{:?} pid={} {:?} {} ok={}",
+///             record.time(),
+///             record.process_id(),
+///             record.kind(),
+///             record.junction().display(),
+///             record.result().is_ok(),
+///         );
+///     });
+///     opts.create(r"C:\app\2.0", r"C:\app\current")
+/// }
+/// ```
+pub trait AuditHook: Send + Sync {
+    /// Called after a junction point was changed, or failed to be.
+    fn on_operation(&self, record: &OperationRecord<'_>);
+}
+
+impl<F: Fn(&OperationRecord<'_>) + Send + Sync> AuditHook for F {
+    fn on_operation(&self, record: &OperationRecord<'_>) {
+        self(record)
+    }
+}
+
+// The hook of `Options`, which must be `Debug` and `Clone` like the other options.
+#[derive(Clone)]
+pub(crate) struct Hook(pub(crate) Arc<dyn AuditHook>);
+
+impl fmt::Debug for Hook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AuditHook")
+    }
+}
+
+/// The kind of change reported by an [`OperationRecord`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum OperationKind {
+    /// A junction point was created.
+    Create,
+    /// The target of an existing junction point was changed.
+    Retarget,
+    /// A junction point was deleted.
+    Delete,
+    /// A junction point was moved from [`source`](OperationRecord::source) to
+    /// [`junction`](OperationRecord::junction).
+    Move,
+    /// A junction point was turned into a directory symbolic link.
+    ConvertToSymlink,
+    /// A directory symbolic link was turned into a junction point.
+    ConvertToJunction,
+}
+
+/// A change of a junction point, as reported to an [`AuditHook`].
+///
+/// Paths are the ones passed to [`Options`](crate::Options), before normalization.
+/// The exceptions are targets read from existing reparse data, and the junction
+/// point of [`delete_at`](crate::Options::delete_at), which is named after the
+/// `\\?\` path of its directory.
+#[derive(Debug)]
+pub struct OperationRecord<'a> {
+    pub(crate) kind: OperationKind,
+    pub(crate) junction: &'a Path,
+    pub(crate) source: Option<&'a Path>,
+    pub(crate) target: Option<&'a Path>,
+    pub(crate) time: SystemTime,
+    pub(crate) result: Result<(), &'a io::Error>,
+}
+
+impl OperationRecord<'_> {
+    /// Returns what was changed.
+    pub fn kind(&self) -> OperationKind {
+        self.kind
+    }
+
+    /// Returns the path of the junction point.
+    pub fn junction(&self) -> &Path {
+        self.junction
+    }
+
+    /// Returns the junction point that was copied or moved to
+    /// [`junction`](Self::junction), if any.
+    pub fn source(&self) -> Option<&Path> {
+        self.source
+    }
+
+    /// Returns the target of the junction point, or of the symbolic link it was
+    /// turned into. It is `None` for deletions, and for copies, moves and
+    /// conversions that failed before the target was read.
+    pub fn target(&self) -> Option<&Path> {
+        self.target
+    }
+
+    /// Returns when the operation completed.
+    pub fn time(&self) -> SystemTime {
+        self.time
+    }
+
+    /// Returns the ID of the process that made the change.
+    pub fn process_id(&self) -> u32 {
+        std::process::id()
+    }
+
+    /// Returns the error of the operation, if it failed.
+    pub fn result(&self) -> Result<(), &io::Error> {
+        self.result
+    }
+}
@@ -0,0 +1,52 @@
+//! Junction reparse data transported through the backup stream APIs.
+//!
+//! Backup software reads and restores reparse points with `BackupRead` and
+//! `BackupWrite` rather than with `FSCTL_GET_REPARSE_POINT` and `FSCTL_SET_REPARSE_POINT`.
+//! The reparse data is then one of the streams of the directory, which these
+//! functions read and write on their own. Together with the `SeBackupPrivilege`
+//! and `SeRestorePrivilege` privileges, enabled as configured by the
+//! [`PrivilegePolicy`](crate::PrivilegePolicy), this reaches directories that
+//! could not be opened otherwise.
+//!
+//! The data is the whole reparse data buffer, header included, in the layout
+//! that [`parse_reparse_data`](crate::parse_reparse_data) reads.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::io;
+//!
+//! fn main() -> io::Result<()> {
+//!     let data = junction::backup::read(r"C:\source\junction")?;
+//!     junction::backup::restore(r"D:\mirror\junction", &data)
+//! }
+//! ```
+
+use std::io;
+use std::path::Path;
+
+use crate::{internals, Options};
+
+/// Reads the reparse data of the junction point `junction` with `BackupRead`.
+///
+/// # Error
+///
+/// This function errors if `junction` does not exist, or with a
+/// [`TagMismatch`](crate::TagMismatch) if it is a reparse point other than a junction point.
+pub fn read<P: AsRef<Path>>(junction: P) -> io::Result<Vec<u8>> {
+    internals::backup_read(junction.as_ref(), &Options::new())
+}
+
+/// Restores the junction point `junction` from reparse data with `BackupWrite`.
+///
+/// `junction` is created if it does not exist, and may otherwise be an empty
+/// directory or a junction point, whose target is then replaced. The reparse
+/// data is written as is, without interpreting the target.
+///
+/// # Error
+///
+/// This function errors with a [`TagMismatch`](crate::TagMismatch) if `data` is
+/// not the reparse data of a junction point.
+pub fn restore<P: AsRef<Path>>(junction: P, data: &[u8]) -> io::Result<()> {
+    internals::backup_restore(junction.as_ref(), data, &Options::new())
+}
@@ -0,0 +1,184 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{Options, RollbackFailed};
+
+/// A sequence of junction operations applied all or nothing.
+///
+/// [`Options::apply_batch`] applies the operations in order. If one fails, the
+/// ones already applied are undone in reverse order: created junction points
+/// are deleted, along with their directory if it was created too, deleted ones
+/// are recreated with their previous target, and retargeted ones get their
+/// previous target back. Then the error of the failed operation is returned.
+///
+/// This is not a file system transaction. Other processes see the intermediate
+/// states, parent directories created by [`Options::create_parents`] are kept,
+/// and a crash in the middle leaves the operations applied so far in place.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::io;
+/// use junction::{Batch, Options};
+///
+/// fn main() -> io::Result<()> {
+///     let mut batch = Batch::new();
+///     batch
+///         .set_target(r"C:\app\current", r"C:\app\2.0")
+///         .create(r"C:\app\2.0\data", r"C:\app\data")
+///         .delete(r"C:\app\legacy");
+///     Options::new().apply_batch(&batch)
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Batch {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone)]
+enum Step {
+    Create { target: PathBuf, junction: PathBuf },
+    SetTarget { junction: PathBuf, target: PathBuf },
+    Delete { junction: PathBuf },
+}
+
+// How to undo an applied step.
+enum Undo {
+    // The junction point was created along with its directory.
+    Remove(PathBuf),
+    // The junction point was created in an existing empty directory.
+    Delete(PathBuf),
+    // The junction point pointed to `target` before.
+    Retarget { junction: PathBuf, target: PathBuf },
+    // The junction point to `target` was deleted, leaving its directory behind.
+    Recreate { junction: PathBuf, target: PathBuf },
+}
+
+impl Batch {
+    /// Creates an empty batch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the creation of a junction point, like [`crate::create`].
+    pub fn create<P: Into<PathBuf>, Q: Into<PathBuf>>(&mut self, target: P, junction: Q) -> &mut Self {
+        self.steps.push(Step::Create {
+            target: target.into(),
+            junction: junction.into(),
+        });
+        self
+    }
+
+    /// Adds a change of target of an existing junction point, like [`crate::set_target`].
+    pub fn set_target<P: Into<PathBuf>, Q: Into<PathBuf>>(&mut self, junction: P, target: Q) -> &mut Self {
+        self.steps.push(Step::SetTarget {
+            junction: junction.into(),
+            target: target.into(),
+        });
+        self
+    }
+
+    /// Adds the deletion of a junction point, like [`crate::delete`].
+    ///
+    /// Deleting a missing junction point does nothing, so there is nothing to undo.
+    pub fn delete<P: Into<PathBuf>>(&mut self, junction: P) -> &mut Self {
+        self.steps.push(Step::Delete {
+            junction: junction.into(),
+        });
+        self
+    }
+
+    /// Returns the number of operations in the batch.
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Returns `true` if the batch has no operations.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub(crate) fn apply(&self, opts: &Options) -> io::Result<()> {
+        let mut applied = Vec::with_capacity(self.steps.len());
+        for step in &self.steps {
+            match apply_step(step, opts) {
+                Ok(undo) => applied.extend(undo),
+                Err(error) => {
+                    return match rollback(applied, opts) {
+                        Ok(()) => Err(error),
+                        Err((junction, rollback)) => Err(io::Error::new(
+                            error.kind(),
+                            RollbackFailed::new(error, junction, rollback),
+                        )),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Applies `step`, and returns how to undo it, if it changed anything.
+fn apply_step(step: &Step, opts: &Options) -> io::Result<Option<Undo>> {
+    match step {
+        Step::Create { target, junction } => {
+            let undo = match fs::symlink_metadata(junction) {
+                Ok(metadata) if metadata.file_type().is_symlink() => Undo::Retarget {
+                    junction: junction.clone(),
+                    target: opts.get_target(junction)?,
+                },
+                Ok(_) => Undo::Delete(junction.clone()),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Undo::Remove(junction.clone()),
+                Err(e) => return Err(e),
+            };
+            opts.create(target, junction)?;
+            Ok(Some(undo))
+        }
+        Step::SetTarget { junction, target } => {
+            let previous = opts.get_target(junction)?;
+            opts.set_target(junction, target)?;
+            Ok(Some(Undo::Retarget {
+                junction: junction.clone(),
+                target: previous,
+            }))
+        }
+        Step::Delete { junction } => {
+            let previous = match opts.get_target(junction) {
+                Ok(previous) => previous,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+                Err(e) => return Err(e),
+            };
+            opts.delete(junction)?;
+            Ok(Some(Undo::Recreate {
+                junction: junction.clone(),
+                target: previous,
+            }))
+        }
+    }
+}
+
+// Undoes the `applied` steps in reverse order. Every step is attempted, and the
+// first failure is returned along with the path of its junction point.
+fn rollback(applied: Vec<Undo>, opts: &Options) -> Result<(), (PathBuf, io::Error)> {
+    let mut recreate_opts = opts.clone();
+    recreate_opts
+        .adopt_existing(true)
+        .overwrite(false)
+        .create_parents(false);
+    let mut result = Ok(());
+    for undo in applied.into_iter().rev() {
+        let (junction, undone): (&Path, _) = match &undo {
+            Undo::Remove(junction) => (junction, opts.delete_and_remove_dir(junction)),
+            Undo::Delete(junction) => (junction, opts.delete(junction)),
+            Undo::Retarget { junction, target } => (junction, opts.set_target(junction, target)),
+            Undo::Recreate { junction, target } => (junction, recreate_opts.create(target, junction)),
+        };
+        if let Err(e) = undone {
+            if result.is_ok() {
+                result = Err((junction.to_path_buf(), e));
+            }
+        }
+    }
+    result
+}
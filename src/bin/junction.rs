@@ -0,0 +1,334 @@
+//! Command-line tool for working with NTFS junctions.
+
+use std::ffi::OsString;
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process;
+
+use junction::manifest::Manifest;
+use junction::{EntryKind, JunctionKind};
+
+const USAGE: &str = "\
+Usage: junction <COMMAND> <ARGS>
+
+Commands:
+  create <JUNCTION> <TARGET>  Create a junction pointing to TARGET
+  delete <JUNCTION>           Remove the reparse point, leaving an empty directory
+  target [--json] <JUNCTION>  Print the target of a junction
+  list [--json] [ROOT]        List the junctions below ROOT (default: .)
+  resolve [--json] <PATH>     Follow the junctions and symbolic links in PATH,
+                              printing each hop and the final path
+
+With --json, each junction is printed as a JSON object on its own line, with
+the fields \"path\", \"target\", \"kind\" (\"directory\", \"volume\" or \"device\")
+and \"dangling\" (whether the target is missing). `resolve --json` prints one
+object with the fields \"hops\" (objects with \"link\", \"target\" and \"kind\",
+either \"junction\" or \"symlink\"), \"path\", \"dangling\" and \"cycle\".
+
+Sysinternals-compatible forms:
+  junction [-s] [-q] <PATH>   Report whether PATH is a junction, or with -s,
+                              every junction below it; -q hides errors
+  junction -d <JUNCTION>      Delete a junction and its directory
+  junction <JUNCTION> <TARGET>
+                              Create a junction pointing to TARGET
+
+  -accepteula and -nobanner are accepted and ignored. The commands above
+  take precedence over junctions named like them.
+";
+
+fn main() {
+    let mut args = std::env::args_os().skip(1);
+    let first = args.next();
+    let mut args: Vec<OsString> = args.collect();
+    let command = first.as_ref().and_then(|c| c.to_str());
+    let mut json = false;
+    if let Some("target" | "list" | "resolve") = command {
+        let len = args.len();
+        args.retain(|arg| arg != "--json");
+        json = args.len() < len;
+    }
+    let mut quiet = false;
+    let result = match (command, args.as_slice()) {
+        (Some("create"), [junction, target]) => junction::create(target, junction),
+        (Some("delete"), [junction]) => junction::delete(junction),
+        (Some("target"), [junction]) => target(Path::new(junction), json),
+        (Some("list"), []) => list(PathBuf::from("."), json),
+        (Some("list"), [root]) => list(PathBuf::from(root), json),
+        (Some("resolve"), [path]) => resolve(Path::new(path), json),
+        (Some("help" | "-h" | "--help"), _) => {
+            print!("{}", USAGE);
+            return;
+        }
+        _ => match Sysinternals::parse(first.clone().into_iter().chain(args)) {
+            Some(invocation) => {
+                quiet = invocation.quiet;
+                invocation.run()
+            }
+            None => {
+                eprint!("{}", USAGE);
+                process::exit(2);
+            }
+        },
+    };
+    if let Err(e) = result {
+        if !quiet {
+            eprintln!("junction: {}", e);
+        }
+        process::exit(1);
+    }
+}
+
+fn target(junction: &Path, json: bool) -> io::Result<()> {
+    let target = junction::get_target(junction)?;
+    if json {
+        print_record(&mut io::stdout(), junction, &target, junction::get_kind(junction)?)
+    } else {
+        println!("{}", target.display());
+        Ok(())
+    }
+}
+
+fn list(root: PathBuf, json: bool) -> io::Result<()> {
+    let manifest = Manifest::snapshot(&root)?;
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    for entry in manifest.entries() {
+        let path = root.join(entry.path());
+        if json {
+            print_record(&mut stdout, &path, entry.target(), entry.kind())?;
+        } else {
+            writeln!(stdout, "{} -> {}", path.display(), entry.target().display())?;
+        }
+    }
+    Ok(())
+}
+
+// Links followed by `resolve` before it reports a cycle, like the limit of 63
+// reparse points Windows follows when opening a path.
+const MAX_HOPS: usize = 63;
+
+fn resolve(path: &Path, json: bool) -> io::Result<()> {
+    let mut hops: Vec<(PathBuf, PathBuf, &str)> = Vec::new();
+    let mut current = std::env::current_dir()?.join(path);
+    let mut cycle = false;
+    'restart: loop {
+        let mut prefix = PathBuf::new();
+        let mut components = current.components();
+        while let Some(component) = components.next() {
+            // Every prefix so far has been followed, so `..` can be applied lexically.
+            match component {
+                Component::CurDir => continue,
+                Component::ParentDir => {
+                    prefix.pop();
+                    continue;
+                }
+                _ => prefix.push(component),
+            }
+            if let Some((target, kind)) = read_hop(&prefix)? {
+                cycle = hops.len() == MAX_HOPS || hops.iter().any(|(link, ..)| *link == prefix);
+                let rest = components.as_path();
+                current = if rest.as_os_str().is_empty() {
+                    target.clone()
+                } else {
+                    target.join(rest)
+                };
+                hops.push((prefix, target, kind));
+                if cycle {
+                    break 'restart;
+                }
+                continue 'restart;
+            }
+        }
+        current = prefix;
+        break;
+    }
+    let dangling = !cycle && std::fs::symlink_metadata(&current).is_err();
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    if json {
+        write!(stdout, "{{\"hops\":[")?;
+        for (i, (link, target, kind)) in hops.iter().enumerate() {
+            let separator = if i == 0 { "" } else { "," };
+            write!(
+                stdout,
+                "{}{{\"link\":{},\"target\":{},\"kind\":\"{}\"}}",
+                separator,
+                json_string(link),
+                json_string(target),
+                kind
+            )?;
+        }
+        writeln!(
+            stdout,
+            "],\"path\":{},\"dangling\":{},\"cycle\":{}}}",
+            json_string(&current),
+            dangling,
+            cycle
+        )
+    } else {
+        for (link, target, kind) in &hops {
+            writeln!(stdout, "{} -> {} ({})", link.display(), target.display(), kind)?;
+        }
+        if cycle {
+            writeln!(stdout, "cycle: {} is followed again", hops[hops.len() - 1].0.display())
+        } else if dangling {
+            writeln!(stdout, "{} (dangling: does not exist)", current.display())
+        } else {
+            writeln!(stdout, "{}", current.display())
+        }
+    }
+}
+
+// Returns the target of `path` and whether it is a junction point or a
+// symbolic link, or `None` for anything else, including missing paths.
+fn read_hop(path: &Path) -> io::Result<Option<(PathBuf, &'static str)>> {
+    let file_type = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata.file_type(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    if junction::metadata(path).map_or(false, |metadata| metadata.is_junction()) {
+        return junction::get_target(path).map(|target| Some((target, "junction")));
+    }
+    if !file_type.is_symlink() {
+        return Ok(None);
+    }
+    // A relative symbolic link is resolved against the directory holding it.
+    let target = std::fs::read_link(path)?;
+    let parent = path.parent().unwrap_or(path);
+    Ok(Some((parent.join(target), "symlink")))
+}
+
+// Prints the junction point `path` as a JSON object on its own line.
+fn print_record(out: &mut impl Write, path: &Path, target: &Path, kind: JunctionKind) -> io::Result<()> {
+    let kind = match kind {
+        JunctionKind::VolumeMountPoint => "volume",
+        JunctionKind::DevicePath => "device",
+        _ => "directory",
+    };
+    writeln!(
+        out,
+        "{{\"path\":{},\"target\":{},\"kind\":\"{}\",\"dangling\":{}}}",
+        json_string(path),
+        json_string(target),
+        kind,
+        !target.exists()
+    )
+}
+
+// Quotes `path` as a JSON string. Names that are not valid Unicode are
+// printed with replacement characters.
+fn json_string(path: &Path) -> String {
+    let mut quoted = String::from('"');
+    for c in path.to_string_lossy().chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c < ' ' => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// The command line of the Sysinternals `junction` tool, for scripts written against it.
+struct Sysinternals {
+    delete: bool,
+    recurse: bool,
+    quiet: bool,
+    paths: Vec<PathBuf>,
+}
+
+impl Sysinternals {
+    fn parse<I: IntoIterator<Item = OsString>>(args: I) -> Option<Self> {
+        let mut invocation = Sysinternals {
+            delete: false,
+            recurse: false,
+            quiet: false,
+            paths: Vec::new(),
+        };
+        for arg in args {
+            match arg.to_str().map(str::to_ascii_lowercase).as_deref() {
+                Some("-d") => invocation.delete = true,
+                Some("-s") => invocation.recurse = true,
+                Some("-q") => invocation.quiet = true,
+                Some("-accepteula" | "-nobanner") => {}
+                Some(flag) if flag.starts_with('-') => return None,
+                _ => invocation.paths.push(PathBuf::from(arg)),
+            }
+        }
+        let valid = match invocation.paths.len() {
+            1 => !(invocation.delete && invocation.recurse),
+            2 => !invocation.delete && !invocation.recurse,
+            _ => false,
+        };
+        if valid {
+            Some(invocation)
+        } else {
+            None
+        }
+    }
+
+    fn run(&self) -> io::Result<()> {
+        match self.paths.as_slice() {
+            [junction, target] => junction::create(target, junction),
+            [junction] if self.delete => {
+                junction::delete_and_remove_dir(junction)?;
+                println!("Deleting {}... Deleted.", junction.display());
+                Ok(())
+            }
+            [path] => self.report(path),
+            _ => unreachable!("checked by `parse`"),
+        }
+    }
+
+    // Prints `path` if it is a junction point, or with `recurse`, every
+    // junction point below it, in the format of the Sysinternals tool.
+    fn report(&self, path: &Path) -> io::Result<()> {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        let mut found = 0;
+        if let Some(target) = junction::metadata(path)?.target() {
+            print_junction(&mut stdout, path, target)?;
+            found += 1;
+        }
+        if self.recurse {
+            for entry in junction::walk(path) {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        if !self.quiet {
+                            eprintln!("junction: {}", e);
+                        }
+                        continue;
+                    }
+                };
+                if let EntryKind::Junction { target } = entry.kind() {
+                    print_junction(&mut stdout, entry.path(), target)?;
+                    found += 1;
+                }
+            }
+        }
+        if found == 0 {
+            writeln!(stdout, "No reparse points found.")?;
+        }
+        Ok(())
+    }
+}
+
+fn print_junction(out: &mut impl Write, path: &Path, target: &Path) -> io::Result<()> {
+    let kind = match junction::get_kind(path) {
+        Ok(JunctionKind::VolumeMountPoint) => "MOUNT POINT",
+        _ => "JUNCTION",
+    };
+    let print_name = junction::Options::new()
+        .open_file(path, false)
+        .and_then(|file| file.print_name())
+        .unwrap_or_default();
+    writeln!(out, "{}: {}", path.display(), kind)?;
+    writeln!(out, "   Print Name     : {}", print_name.display())?;
+    writeln!(out, "   Substitute Name: {}", target.display())?;
+    writeln!(out)
+}
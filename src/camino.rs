@@ -0,0 +1,72 @@
+//! Junction operations on [`camino`] UTF-8 paths.
+//!
+//! The functions in the crate root already accept [`Utf8Path`] as input. This
+//! module adds variants that return [`Utf8PathBuf`], so callers that use UTF-8
+//! paths throughout do not have to convert the results themselves.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::io;
+//! use camino::Utf8Path;
+//!
+//! fn main() -> io::Result<()> {
+//!     let target = junction::camino::get_target(Utf8Path::new(r"C:\junction"))?;
+//!     println!("{}", target);
+//!     Ok(())
+//! }
+//! ```
+//!
+//! [`camino`]: ::camino
+
+use std::io;
+use std::path::PathBuf;
+
+use ::camino::{Utf8Path, Utf8PathBuf};
+
+use crate::JunctionKind;
+
+/// Creates a junction point from the specified directory to the specified target directory.
+///
+/// See [`crate::create`].
+pub fn create<P: AsRef<Utf8Path>, Q: AsRef<Utf8Path>>(target: P, junction: Q) -> io::Result<()> {
+    crate::create(target.as_ref(), junction.as_ref())
+}
+
+/// Deletes a `junction` reparse point from the specified file or directory.
+///
+/// See [`crate::delete`].
+pub fn delete<P: AsRef<Utf8Path>>(junction: P) -> io::Result<()> {
+    crate::delete(junction.as_ref())
+}
+
+/// Determines whether the specified path exists and refers to a junction point.
+///
+/// See [`crate::exists`].
+pub fn exists<P: AsRef<Utf8Path>>(junction: P) -> io::Result<bool> {
+    crate::exists(junction.as_ref())
+}
+
+/// Gets the target of the specified junction point.
+///
+/// See [`crate::get_target`].
+///
+/// # Error
+///
+/// Returns an error of kind [`io::ErrorKind::InvalidData`] if the target is
+/// not valid UTF-8.
+pub fn get_target<P: AsRef<Utf8Path>>(junction: P) -> io::Result<Utf8PathBuf> {
+    crate::get_target(junction.as_ref()).and_then(to_utf8)
+}
+
+/// Gets the kind of the specified junction point.
+///
+/// See [`crate::get_kind`].
+pub fn get_kind<P: AsRef<Utf8Path>>(junction: P) -> io::Result<JunctionKind> {
+    crate::get_kind(junction.as_ref())
+}
+
+fn to_utf8(path: PathBuf) -> io::Result<Utf8PathBuf> {
+    Utf8PathBuf::from_path_buf(path)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "junction target is not valid UTF-8"))
+}
@@ -0,0 +1,63 @@
+//! Junction operations relative to a [`cap_std`] directory handle.
+//!
+//! Each function takes a [`Dir`] and a path relative to it, and opens the
+//! junction through that handle instead of through ambient path authority.
+//! Paths that escape the directory, for example through `..` or an absolute
+//! path, are rejected by `cap-std`.
+//!
+//! Unlike the functions in the crate root, these never adjust the privileges
+//! of the process when access is denied.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::io;
+//! use cap_std::ambient_authority;
+//! use cap_std::fs::Dir;
+//!
+//! fn main() -> io::Result<()> {
+//!     let dir = Dir::open_ambient_dir(r"C:\sandbox", ambient_authority())?;
+//!     junction::cap_std::create(&dir, r"C:\target", "junction")?;
+//!     assert_eq!(junction::cap_std::get_target(&dir, "junction")?.to_str(), Some(r"C:\target"));
+//!     junction::cap_std::delete(&dir, "junction")
+//! }
+//! ```
+//!
+//! [`cap_std`]: ::cap_std
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use ::cap_std::fs::Dir;
+
+use crate::internals;
+
+/// Creates a junction point at `junction`, relative to `dir`, pointing to `target`.
+///
+/// The target is only stored in the junction, so it is not resolved relative
+/// to `dir` and must be an absolute path.
+///
+/// # Error
+///
+/// This function may error if the `junction` path already exists, or with
+/// [`io::ErrorKind::InvalidInput`] if `target` is not absolute. Like
+/// [`crate::create`], it fails with an [`UnsupportedFilesystem`](crate::UnsupportedFilesystem)
+/// on file systems without reparse points, and removes the directory it
+/// created if that could not be turned into a junction.
+pub fn create<P: AsRef<Path>, Q: AsRef<Path>>(dir: &Dir, target: P, junction: Q) -> io::Result<()> {
+    internals::create_at(dir, target.as_ref(), junction.as_ref())
+}
+
+/// Deletes the `junction` reparse point, relative to `dir`.
+///
+/// See [`crate::delete`].
+pub fn delete<P: AsRef<Path>>(dir: &Dir, junction: P) -> io::Result<()> {
+    internals::delete_at(dir, junction.as_ref())
+}
+
+/// Gets the target of the `junction` point, relative to `dir`.
+///
+/// See [`crate::get_target`].
+pub fn get_target<P: AsRef<Path>>(dir: &Dir, junction: P) -> io::Result<PathBuf> {
+    internals::get_target_at(dir, junction.as_ref())
+}
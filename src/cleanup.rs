@@ -0,0 +1,89 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::reparse::{self, parse_reparse_data, ReparseData};
+use crate::JunctionKind;
+
+/// Deletes every junction point below `root`, leaving everything else alone.
+///
+/// N.B. Only works on NTFS.
+///
+/// The tree is walked without following any reparse points, so the targets of
+/// the junctions and their content are never touched. Volume mount points are
+/// skipped, as they must be removed with [`unmount_volume`](crate::unmount_volume).
+///
+/// Returns the path of every junction found, together with the result of deleting it.
+/// The directories left behind are kept; see [`delete_all_and_remove_dirs`].
+///
+/// # Error
+///
+/// This function errors if a directory of the tree cannot be read. Reparse
+/// points that cannot be read or deleted are reported in the returned list instead.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::io;
+/// use junction::delete_all;
+///
+/// fn main() -> io::Result<()> {
+///     for (junction, result) in delete_all(r"C:\build\node_modules")? {
+///         if let Err(e) = result {
+///             eprintln!("{}: {}", junction.display(), e);
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn delete_all<P: AsRef<Path>>(root: P) -> io::Result<Vec<(PathBuf, io::Result<()>)>> {
+    let mut results = Vec::new();
+    walk(root.as_ref(), false, &mut results)?;
+    Ok(results)
+}
+
+/// Deletes every junction point below `root` like [`delete_all`], and removes
+/// the directories left behind.
+///
+/// Directories are only removed if they are empty, so content that appears
+/// while the tree is cleaned up is kept, and its removal is reported as failed.
+pub fn delete_all_and_remove_dirs<P: AsRef<Path>>(root: P) -> io::Result<Vec<(PathBuf, io::Result<()>)>> {
+    let mut results = Vec::new();
+    walk(root.as_ref(), true, &mut results)?;
+    Ok(results)
+}
+
+fn walk(dir: &Path, remove_dirs: bool, results: &mut Vec<(PathBuf, io::Result<()>)>) -> io::Result<()> {
+    let mut children = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    children.sort_by_key(|child| child.file_name());
+    for child in children {
+        let path = child.path();
+        let file_type = child.file_type()?;
+        // Junctions are name surrogates, which `std` reports as symlinks.
+        if file_type.is_symlink() {
+            let result = match junction_kind(&path) {
+                Ok(Some(JunctionKind::Directory | JunctionKind::DevicePath)) if remove_dirs => {
+                    crate::delete_and_remove_dir(&path)
+                }
+                Ok(Some(JunctionKind::Directory | JunctionKind::DevicePath)) => crate::delete(&path),
+                Ok(_) => continue,
+                Err(e) => Err(e),
+            };
+            results.push((path, result));
+        } else if file_type.is_dir() {
+            walk(&path, remove_dirs, results)?;
+        }
+    }
+    Ok(())
+}
+
+// Gets the kind of the junction point at `path` without following it, so that
+// junction points whose target no longer exists are found too.
+fn junction_kind(path: &Path) -> io::Result<Option<JunctionKind>> {
+    let (tag, guid, data) = crate::read_reparse_raw(path)?;
+    let bytes = reparse::build_reparse_data(tag, guid, &data)?;
+    match parse_reparse_data(&bytes)? {
+        ReparseData::MountPoint(data) => Ok(Some(JunctionKind::of_substitute_name(data.substitute_name()))),
+        _ => Ok(None),
+    }
+}
@@ -0,0 +1,47 @@
+//! Values of the Windows reparse point API, for use with other Windows crates.
+//!
+//! These are the values used by this crate, defined for every platform so that
+//! code preparing or inspecting reparse point data does not need a Windows
+//! binding crate.
+
+/// Reparse tag of directory junctions and volume mount points.
+pub const IO_REPARSE_TAG_MOUNT_POINT: u32 = 0xA000_0003;
+/// Reparse tag of symbolic links.
+pub const IO_REPARSE_TAG_SYMLINK: u32 = 0xA000_000C;
+/// Reparse tag of app execution aliases.
+pub const IO_REPARSE_TAG_APPEXECLINK: u32 = 0x8000_001B;
+/// Reparse tag of symbolic links created by WSL.
+pub const IO_REPARSE_TAG_LX_SYMLINK: u32 = 0xA000_001D;
+/// Reparse tag of files deduplicated by Data Deduplication.
+pub const IO_REPARSE_TAG_DEDUP: u32 = 0x8000_0013;
+/// Reparse tag of Cloud Files placeholders.
+pub const IO_REPARSE_TAG_CLOUD: u32 = 0x9000_001A;
+/// Bits set in the variants `IO_REPARSE_TAG_CLOUD_1` to `IO_REPARSE_TAG_CLOUD_F`.
+pub const IO_REPARSE_TAG_CLOUD_MASK: u32 = 0x0000_F000;
+/// Raw OS error of operations on entries that are not reparse points.
+pub const ERROR_NOT_A_REPARSE_POINT: i32 = 4390;
+/// The symlink substitute name is relative to the directory containing the link.
+pub const SYMLINK_FLAG_RELATIVE: u32 = 0x0000_0001;
+
+/// Maximum size of reparse point data, including its header.
+pub const MAXIMUM_REPARSE_DATA_BUFFER_SIZE: usize = 16 * 1024;
+/// Size of the `REPARSE_DATA_BUFFER` header: tag, data length and reserved.
+pub const REPARSE_DATA_BUFFER_HEADER_SIZE: usize = 8;
+/// Size of the `REPARSE_GUID_DATA_BUFFER` header, which also includes the GUID.
+pub const REPARSE_GUID_DATA_BUFFER_HEADER_SIZE: usize = 24;
+/// Size of the name offsets and lengths in `MountPointReparseBuffer`.
+pub const MOUNT_POINT_REPARSE_BUFFER_HEADER_SIZE: usize = 8;
+/// Size of the name offsets, lengths and flags in `SymbolicLinkReparseBuffer`.
+pub const SYMLINK_REPARSE_BUFFER_HEADER_SIZE: usize = 12;
+
+/// Maximum length of the target of a junction point, in UTF-16 code units.
+///
+/// This is the length of the target as stored, after the `\??\` prefix, when
+/// the print name is empty as with the junction points created by this crate.
+pub const MAXIMUM_JUNCTION_TARGET_LEN: usize = (MAXIMUM_REPARSE_DATA_BUFFER_SIZE
+    - REPARSE_DATA_BUFFER_HEADER_SIZE
+    - MOUNT_POINT_REPARSE_BUFFER_HEADER_SIZE)
+    / 2
+    // The `\??\` prefix and the null characters ending both names.
+    - 4
+    - 2;
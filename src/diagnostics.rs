@@ -0,0 +1,227 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{internals, Options, ReparseDump, ReparseTag};
+
+/// What the file system and the process say about a path, as collected by [`explain`].
+///
+/// Each query is made independently, so one failing does not hide the others,
+/// and its result is kept as is. The [`Display`](fmt::Display) implementation
+/// prints one `name: value` line per query, for bug reports and logs.
+#[derive(Debug)]
+pub struct Diagnostics {
+    pub(crate) path: PathBuf,
+    pub(crate) full_path: io::Result<PathBuf>,
+    pub(crate) nt_path: io::Result<PathBuf>,
+    pub(crate) normalized: io::Result<PathBuf>,
+    pub(crate) volume: io::Result<Volume>,
+    pub(crate) attributes: io::Result<u32>,
+    pub(crate) reparse_tag: io::Result<Option<u32>>,
+    pub(crate) target: Option<io::Result<PathBuf>>,
+    pub(crate) privileges: io::Result<Vec<Privilege>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct Volume {
+    pub(crate) root: PathBuf,
+    pub(crate) filesystem: String,
+    pub(crate) flags: u32,
+}
+
+impl Volume {
+    fn supports_reparse_points(&self) -> bool {
+        const FILE_SUPPORTS_REPARSE_POINTS: u32 = 0x80;
+        self.flags & FILE_SUPPORTS_REPARSE_POINTS != 0
+    }
+}
+
+/// A privilege held by the process, as reported by [`Diagnostics::privileges`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Privilege {
+    pub(crate) name: String,
+    pub(crate) enabled: bool,
+}
+
+impl Privilege {
+    /// Returns the name of the privilege, such as `SeBackupPrivilege`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns `true` if the privilege is enabled, rather than only held.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+impl Diagnostics {
+    /// Returns the path that was explained, as given.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the path made absolute by `GetFullPathNameW`.
+    pub fn full_path(&self) -> Result<&Path, &io::Error> {
+        self.full_path.as_deref()
+    }
+
+    /// Returns the NT path of the path, such as `\??\C:\dir`.
+    pub fn nt_path(&self) -> Result<&Path, &io::Error> {
+        self.nt_path.as_deref()
+    }
+
+    /// Returns the path as [`create`](crate::create) would store it as the
+    /// target of a junction point, without the `\??\` prefix.
+    pub fn normalized(&self) -> Result<&Path, &io::Error> {
+        self.normalized.as_deref()
+    }
+
+    /// Returns the root of the volume holding the path.
+    pub fn volume(&self) -> Result<&Path, &io::Error> {
+        self.volume.as_ref().map(|volume| volume.root.as_path())
+    }
+
+    /// Returns the name of the file system of the volume, such as `NTFS`.
+    pub fn filesystem(&self) -> Result<&str, &io::Error> {
+        self.volume.as_ref().map(|volume| volume.filesystem.as_str())
+    }
+
+    /// Returns `true` if the file system of the volume supports reparse points.
+    pub fn supports_reparse_points(&self) -> Result<bool, &io::Error> {
+        self.volume.as_ref().map(Volume::supports_reparse_points)
+    }
+
+    /// Returns the raw file attributes of the path itself, without following it.
+    pub fn attributes(&self) -> Result<u32, &io::Error> {
+        self.attributes.as_ref().map(|&attributes| attributes)
+    }
+
+    /// Returns the reparse tag, or `None` if the path is not a reparse point.
+    pub fn reparse_tag(&self) -> Result<Option<u32>, &io::Error> {
+        self.reparse_tag.as_ref().map(|&tag| tag)
+    }
+
+    /// Returns the target, if the path is a junction point.
+    pub fn target(&self) -> Option<Result<&Path, &io::Error>> {
+        self.target.as_ref().map(|target| target.as_deref())
+    }
+
+    /// Returns the privileges held by the process token.
+    pub fn privileges(&self) -> Result<&[Privilege], &io::Error> {
+        self.privileges.as_deref()
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn line<T>(
+            f: &mut fmt::Formatter<'_>,
+            name: &str,
+            value: Result<T, &io::Error>,
+            show: impl FnOnce(&mut fmt::Formatter<'_>, T) -> fmt::Result,
+        ) -> fmt::Result {
+            write!(f, "\n{}: ", name)?;
+            match value {
+                Ok(value) => show(f, value),
+                Err(e) => write!(f, "error: {}", e),
+            }
+        }
+
+        write!(f, "path: {}", self.path.display())?;
+        line(f, "full path", self.full_path(), |f, path| {
+            write!(f, "{}", path.display())
+        })?;
+        line(f, "NT path", self.nt_path(), |f, path| write!(f, "{}", path.display()))?;
+        line(f, "normalized", self.normalized(), |f, path| {
+            write!(f, "{}", path.display())
+        })?;
+        line(f, "volume", self.volume.as_ref(), |f, volume| {
+            let support = if volume.supports_reparse_points() {
+                "supported"
+            } else {
+                "not supported"
+            };
+            write!(
+                f,
+                "{} ({}, reparse points {})",
+                volume.root.display(),
+                volume.filesystem,
+                support
+            )
+        })?;
+        line(f, "attributes", self.attributes(), |f, attributes| {
+            write!(f, "{:#010x}", attributes)
+        })?;
+        line(f, "reparse tag", self.reparse_tag(), |f, tag| match tag {
+            Some(tag) => match ReparseTag::from(tag) {
+                ReparseTag::Unknown(_) => write!(f, "{:#010x}", tag),
+                known => write!(f, "{:#010x} ({})", tag, known),
+            },
+            None => f.write_str("none"),
+        })?;
+        if let Some(target) = self.target() {
+            line(f, "target", target, |f, path| write!(f, "{}", path.display()))?;
+        }
+        line(f, "privileges", self.privileges(), |f, privileges| {
+            if privileges.is_empty() {
+                return f.write_str("none");
+            }
+            for (i, privilege) in privileges.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                f.write_str(&privilege.name)?;
+                if privilege.enabled {
+                    f.write_str(" (enabled)")?;
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Collects what is known about `path`, for diagnosing why junction operations on it fail.
+///
+/// The report covers the file system of the volume and whether it supports
+/// reparse points, the attributes and raw reparse tag of `path` itself, the
+/// target if it is a junction point, the privileges held by the process, and
+/// the results of the path conversions made by this crate. This function never
+/// fails; every query that fails is reported with its error instead.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use junction::explain;
+///
+/// println!("{}", explain(r"C:\workspace\link"));
+/// ```
+pub fn explain<P: AsRef<Path>>(path: P) -> Diagnostics {
+    internals::explain(path.as_ref())
+}
+
+/// Reads the reparse data of `path` for display, whatever its reparse tag.
+///
+/// The returned [`ReparseDump`] prints the header fields, the substitute name
+/// and print name of junction points and symbolic links, and a hex dump of the
+/// whole buffer. Malformed data is read all the same, so that it can be
+/// attached to bug reports.
+///
+/// # Error
+///
+/// This function errors if `path` is not a reparse point or cannot be opened.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::io;
+/// use junction::debug_dump;
+///
+/// fn main() -> io::Result<()> {
+///     println!("{}", debug_dump(r"C:\workspace\link")?);
+///     Ok(())
+/// }
+/// ```
+pub fn debug_dump<P: AsRef<Path>>(path: P) -> io::Result<ReparseDump> {
+    internals::debug_dump(path.as_ref(), &Options::new())
+}
@@ -0,0 +1,273 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The file system of a volume does not support reparse points.
+///
+/// Returned as the payload of an [`io::Error`](std::io::Error) of kind
+/// [`Unsupported`](std::io::ErrorKind::Unsupported), for example when creating a
+/// junction on a FAT32 or exFAT drive. It can be retrieved with
+/// [`get_ref`](std::io::Error::get_ref) and [`downcast_ref`](Error#method.downcast_ref):
+///
+/// ```rust,no_run
+/// use junction::UnsupportedFilesystem;
+///
+/// if let Err(e) = junction::create(r"E:\target", r"E:\junction") {
+///     if let Some(fs) = e.get_ref().and_then(|e| e.downcast_ref::<UnsupportedFilesystem>()) {
+///         eprintln!("{} is formatted as {}", fs.volume().display(), fs.filesystem());
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedFilesystem {
+    volume: PathBuf,
+    filesystem: String,
+}
+
+impl UnsupportedFilesystem {
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub(crate) fn new(volume: PathBuf, filesystem: String) -> Self {
+        UnsupportedFilesystem { volume, filesystem }
+    }
+
+    /// Returns the root of the volume, such as `E:\`.
+    pub fn volume(&self) -> &Path {
+        &self.volume
+    }
+
+    /// Returns the name of the file system, such as `FAT32` or `exFAT`.
+    pub fn filesystem(&self) -> &str {
+        &self.filesystem
+    }
+}
+
+impl fmt::Display for UnsupportedFilesystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the {} file system of volume {} does not support reparse points",
+            self.filesystem,
+            self.volume.display()
+        )
+    }
+}
+
+impl Error for UnsupportedFilesystem {}
+
+/// A reparse point is not a junction point.
+///
+/// Returned as the payload of an [`io::Error`](std::io::Error) of kind
+/// [`Other`](std::io::ErrorKind::Other) by operations that modify a junction
+/// point, such as [`delete`](crate::delete), when the path carries another
+/// kind of reparse point, such as a symbolic link. The tag is checked on the
+/// same handle that is modified, so the reparse point cannot be swapped in between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TagMismatch {
+    tag: u32,
+}
+
+impl TagMismatch {
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub(crate) fn new(tag: u32) -> Self {
+        TagMismatch { tag }
+    }
+
+    /// Returns the reparse tag that was found instead of `IO_REPARSE_TAG_MOUNT_POINT`.
+    pub fn tag(&self) -> u32 {
+        self.tag
+    }
+}
+
+impl fmt::Display for TagMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "not a reparse tag mount point, but reparse tag {:#010x}", self.tag)
+    }
+}
+
+impl Error for TagMismatch {}
+
+/// A junction point does not point to the expected target.
+///
+/// Returned as the payload of an [`io::Error`](std::io::Error) of kind
+/// [`Other`](std::io::ErrorKind::Other) by [`delete_if_target`](crate::delete_if_target).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetMismatch {
+    target: PathBuf,
+}
+
+impl TargetMismatch {
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub(crate) fn new(target: PathBuf) -> Self {
+        TargetMismatch { target }
+    }
+
+    /// Returns the actual target of the junction point.
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+}
+
+impl fmt::Display for TargetMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the junction point points to {} instead", self.target.display())
+    }
+}
+
+impl Error for TargetMismatch {}
+
+/// A junction point cannot target the given path.
+///
+/// Returned as the payload of an [`io::Error`](std::io::Error) of kind
+/// [`InvalidInput`](std::io::ErrorKind::InvalidInput) when creating a junction
+/// to a target that would be written successfully, but cause trouble whenever
+/// the junction is used. See [`reason`](UnsupportedTarget::reason) for the cases.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedTarget {
+    target: PathBuf,
+    reason: UnsupportedTargetReason,
+}
+
+/// Why a target was rejected, as returned by [`UnsupportedTarget::reason`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum UnsupportedTargetReason {
+    /// The target is a network path such as `\\server\share`. Windows only
+    /// follows junctions to local volumes.
+    ///
+    /// See [`Options::allow_unc_targets`](crate::Options::allow_unc_targets).
+    NetworkPath,
+    /// A component of the target ends with a dot or a space, such as `C:\dir.`.
+    /// Win32 functions strip those from names, so many tools cannot open or
+    /// delete such a directory.
+    ///
+    /// See [`Options::allow_trailing_dots_and_spaces`](crate::Options::allow_trailing_dots_and_spaces).
+    TrailingDotOrSpace,
+}
+
+impl UnsupportedTarget {
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub(crate) fn new(target: PathBuf, reason: UnsupportedTargetReason) -> Self {
+        UnsupportedTarget { target, reason }
+    }
+
+    /// Returns the rejected target.
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+
+    /// Returns why the target was rejected.
+    pub fn reason(&self) -> UnsupportedTargetReason {
+        self.reason
+    }
+}
+
+impl fmt::Display for UnsupportedTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self.reason {
+            UnsupportedTargetReason::NetworkPath => "it is a network path",
+            UnsupportedTargetReason::TrailingDotOrSpace => "a name ends with a dot or a space",
+        };
+        write!(f, "junction points cannot target {}: {}", self.target.display(), reason)
+    }
+}
+
+impl Error for UnsupportedTarget {}
+
+/// A condition checked by a [`Policy`](crate::Policy) of [`Options`](crate::Options).
+///
+/// Passed to the hook of [`Options::on_policy_warning`](crate::Options::on_policy_warning)
+/// under [`Policy::Warn`](crate::Policy::Warn), and returned as the payload of
+/// an [`io::Error`] of kind [`InvalidInput`](io::ErrorKind::InvalidInput)
+/// under [`Policy::Deny`](crate::Policy::Deny).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PolicyViolation {
+    /// `path`, the junction point or its target, is on a network share or a
+    /// mapped network drive.
+    ///
+    /// See [`Options::remote_filesystems`](crate::Options::remote_filesystems).
+    RemoteFilesystem {
+        /// The path on the remote file system.
+        path: PathBuf,
+    },
+    /// The target is on another volume than the directory holding the junction point.
+    ///
+    /// See [`Options::cross_volume`](crate::Options::cross_volume).
+    CrossVolume {
+        /// The junction point.
+        junction: PathBuf,
+        /// The target, as it is written into the junction point.
+        target: PathBuf,
+    },
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyViolation::RemoteFilesystem { path } => {
+                write!(f, "{} is on a remote file system", path.display())
+            }
+            PolicyViolation::CrossVolume { junction, target } => write!(
+                f,
+                "{} is on another volume than junction point {}",
+                target.display(),
+                junction.display()
+            ),
+        }
+    }
+}
+
+impl Error for PolicyViolation {}
+
+/// A [`Batch`](crate::Batch) failed, and so did undoing one of its operations.
+///
+/// Returned as the payload of an [`io::Error`] by
+/// [`Options::apply_batch`](crate::Options::apply_batch), with the kind of the
+/// error of the failed operation. The junction point at [`path`](RollbackFailed::path)
+/// is left as the batch changed it, and the other operations are undone.
+#[derive(Debug)]
+pub struct RollbackFailed {
+    error: io::Error,
+    path: PathBuf,
+    rollback: io::Error,
+}
+
+impl RollbackFailed {
+    pub(crate) fn new(error: io::Error, path: PathBuf, rollback: io::Error) -> Self {
+        RollbackFailed { error, path, rollback }
+    }
+
+    /// Returns the error of the operation that failed.
+    pub fn error(&self) -> &io::Error {
+        &self.error
+    }
+
+    /// Returns the path of the junction point whose change could not be undone.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the error of undoing the change to [`path`](RollbackFailed::path).
+    pub fn rollback_error(&self) -> &io::Error {
+        &self.rollback
+    }
+}
+
+impl fmt::Display for RollbackFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}, and undoing the change to {} failed: {}",
+            self.error,
+            self.path.display(),
+            self.rollback
+        )
+    }
+}
+
+impl Error for RollbackFailed {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
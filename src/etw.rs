@@ -0,0 +1,42 @@
+//! Events of the `Junction` ETW provider, for tracing junction changes with Windows tools.
+//!
+//! Every junction point created, retargeted, moved, converted or deleted by this
+//! crate is reported as a string event of the provider [`PROVIDER_ID`],
+//! registered on first use. Successful changes are logged at the information
+//! level (4), and failures at the error level (2). Events are only formatted
+//! while a trace session listens.
+//!
+//! The messages read like `create C:\app\current -> C:\app\2.0`,
+//! `retarget C:\app\current -> C:\app\2.1` and `delete C:\app\current`, with
+//! the error appended after a colon when the change failed. Targets are the
+//! ones written to the reparse point, after normalization. The other operations are:
+//!
+//! - `move C:\old -> C:\new`, for a junction renamed within a volume by
+//!   `move_junction`. Across volumes, it is reported as `create` and `delete`.
+//! - `convert C:\app\current -> C:\app\2.0`, for a junction replaced by a
+//!   directory symbolic link or the other way around.
+//! - `write C:\app\current -> C:\app\2.0`, for mount point data written with
+//!   `write_reparse_raw` or the `raw` module.
+//!
+//! Changes made through a handle, such as those of the `raw` and `cap_std`
+//! modules, are reported with the final path of the handle, like
+//! `\\?\C:\app\current`.
+//!
+//! # Example
+//!
+//! To record the events of all processes next to kernel events, start a trace
+//! session before running the program, and open `junction.etl` in Windows
+//! Performance Analyzer afterwards:
+//!
+//! ```text
+//! logman start junction -p {DFD6C6BA-4341-49DF-B580-FD7A83C07868} -o junction.etl -ets
+//! logman stop junction -ets
+//! ```
+
+use crate::Guid;
+
+/// The name of the provider.
+pub const PROVIDER_NAME: &str = "Junction";
+
+/// The GUID of the provider, `{DFD6C6BA-4341-49DF-B580-FD7A83C07868}`.
+pub const PROVIDER_ID: Guid = Guid::from_u128(0xdfd6c6ba_4341_49df_b580_fd7a83c07868);
@@ -0,0 +1,121 @@
+#[cfg(windows)]
+use std::fs::File;
+use std::io;
+#[cfg(windows)]
+use std::os::windows::io::{FromRawHandle, RawHandle};
+use std::path::{Path, PathBuf};
+
+use crate::{internals, Options};
+
+/// An open junction point, for several queries through the same handle.
+///
+/// Each function of the crate root opens the junction point anew, so calling
+/// [`exists`](crate::exists) and then [`get_target`](crate::get_target) opens it
+/// twice and reads its reparse data twice. A `JunctionFile` opens it once, and
+/// caches the reparse data read by the first query until [`refresh`](JunctionFile::refresh)
+/// is called. While it is open, the junction point can only be opened again as
+/// allowed by the [share mode](Options::share_mode), which is exclusive by default.
+///
+/// A handle opened elsewhere, such as by another Windows crate, can be used
+/// without opening the path again through `From<File>` or `FromRawHandle`. It
+/// must have been opened with `FILE_FLAG_OPEN_REPARSE_POINT`, so that it refers
+/// to the reparse point rather than to its target.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use std::fs;
+/// # use junction::{create, JunctionFile};
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     let tmpdir = tempfile::tempdir()?;
+///     let target = tmpdir.path().join("target");
+///     let junction = tmpdir.path().join("junction");
+///     # fs::create_dir_all(&target)?;
+///     create(&target, &junction)?;
+///     let file = JunctionFile::open_writable(&junction)?;
+///     if file.tag()? == 0xA000_0003 && file.target()? == target {
+///         file.delete()?;
+///     }
+///     Ok(())
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub struct JunctionFile {
+    inner: internals::JunctionFile,
+}
+
+impl JunctionFile {
+    /// Opens the reparse point at `path` for reading, without following it.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Options::new().open_file(path, false)
+    }
+
+    /// Opens the reparse point at `path` for reading and writing, as required
+    /// by [`delete`](JunctionFile::delete).
+    pub fn open_writable<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Options::new().open_file(path, true)
+    }
+
+    pub(crate) fn from_inner(inner: internals::JunctionFile) -> Self {
+        Self { inner }
+    }
+
+    /// Returns the reparse tag, such as `0xA000_0003` for junction points.
+    ///
+    /// # Error
+    ///
+    /// This function errors if the opened path is not a reparse point.
+    pub fn tag(&self) -> io::Result<u32> {
+        self.inner.tag()
+    }
+
+    /// Returns the target of the junction point, like [`get_target`](crate::get_target).
+    pub fn target(&self) -> io::Result<PathBuf> {
+        self.inner.target()
+    }
+
+    /// Returns the print name of the junction point, as stored.
+    ///
+    /// The print name is meant for display. Junction points created by this
+    /// crate have an empty one, while those created by `mklink /J` repeat the target.
+    pub fn print_name(&self) -> io::Result<PathBuf> {
+        self.inner.print_name()
+    }
+
+    /// Forgets the cached reparse data, so that the next query reads it again.
+    pub fn refresh(&self) {
+        self.inner.refresh();
+    }
+
+    /// Deletes the junction point, leaving an empty directory behind, like [`delete`](crate::delete).
+    ///
+    /// # Error
+    ///
+    /// This function errors if the file was not opened with [`open_writable`](JunctionFile::open_writable),
+    /// or with a [`TagMismatch`](crate::TagMismatch) if it is not a junction point.
+    pub fn delete(self) -> io::Result<()> {
+        self.inner.delete()
+    }
+}
+
+#[cfg(windows)]
+impl From<File> for JunctionFile {
+    fn from(file: File) -> Self {
+        Self::from_inner(internals::JunctionFile::from_file(file))
+    }
+}
+
+#[cfg(windows)]
+impl FromRawHandle for JunctionFile {
+    /// Takes ownership of `handle`, which is closed when the `JunctionFile` is dropped.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be an open file handle owned by the caller, like for [`File::from_raw_handle`].
+    unsafe fn from_raw_handle(handle: RawHandle) -> Self {
+        File::from_raw_handle(handle).into()
+    }
+}
@@ -0,0 +1,69 @@
+//! Junction points under the names of [`std::fs`] and `std::os::unix::fs`.
+//!
+//! Code written against Unix directory symbolic links can use junction points
+//! on Windows by swapping the imports:
+//!
+//! ```rust,no_run
+//! #[cfg(unix)]
+//! use std::{fs::read_link, os::unix::fs::symlink as symlink_dir};
+//! #[cfg(windows)]
+//! use junction::fs::{read_link, symlink_dir};
+//!
+//! fn main() -> std::io::Result<()> {
+//!     symlink_dir("../shared", "app/data")?;
+//!     println!("{}", read_link("app/data")?.display());
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Junction points only approximate symbolic links. Their targets are always
+//! stored as absolute paths, so [`read_link`] returns the resolved target
+//! rather than the string given to [`symlink_dir`], and they can only target
+//! directories on local volumes.
+
+use std::fs::Metadata;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{internals, Options};
+
+/// Creates a junction point at `link` to the directory `original`, like
+/// `std::os::unix::fs::symlink`.
+///
+/// As with symbolic links, a relative `original` is resolved against the
+/// parent directory of `link`, not against the current directory, and
+/// `original` does not need to exist.
+///
+/// # Error
+///
+/// This function errors with [`io::ErrorKind::AlreadyExists`] if `link`
+/// already exists, and like [`create`](crate::create) otherwise.
+pub fn symlink_dir<P: AsRef<Path>, Q: AsRef<Path>>(original: P, link: Q) -> io::Result<()> {
+    Options::new().relative_to_junction(true).create(original, link)
+}
+
+/// Returns the target of the junction point at `path`, like [`std::fs::read_link`].
+///
+/// # Error
+///
+/// This function errors with [`io::ErrorKind::NotFound`] if `path` does not
+/// exist, and with [`io::ErrorKind::InvalidInput`] if it is not a junction
+/// point, as `readlink` fails with `EINVAL` for paths that are not symbolic links.
+pub fn read_link<P: AsRef<Path>>(path: P) -> io::Result<PathBuf> {
+    let metadata = crate::metadata(path)?;
+    if !metadata.exists() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "`path` does not exist"));
+    }
+    metadata
+        .target
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "`path` is not a junction point"))
+}
+
+/// Queries the metadata of `path` without following it, like [`std::fs::symlink_metadata`].
+///
+/// Junction points are reported as symbolic links by
+/// [`FileType::is_symlink`](std::fs::FileType::is_symlink), as `lstat` does
+/// for symbolic links, whether or not their target exists.
+pub fn symlink_metadata<P: AsRef<Path>>(path: P) -> io::Result<Metadata> {
+    internals::symlink_metadata(path.as_ref())
+}
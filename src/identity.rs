@@ -0,0 +1,69 @@
+use std::io;
+use std::path::Path;
+
+use crate::internals;
+
+/// The identity of a file or directory on a volume.
+///
+/// Two paths refer to the same directory if and only if their `FileId`s are
+/// equal, regardless of how the paths are spelled or which junctions they pass
+/// through. The identity is stable for as long as the directory exists, but may
+/// be reused once it is deleted.
+///
+/// Returned by [`file_id`] and [`target_file_id`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FileId {
+    pub(crate) volume_serial_number: u64,
+    pub(crate) file_id: u128,
+}
+
+impl FileId {
+    /// Returns the serial number of the volume containing the file.
+    pub fn volume_serial_number(&self) -> u64 {
+        self.volume_serial_number
+    }
+
+    /// Returns the identifier of the file within its volume.
+    ///
+    /// ReFS uses all 128 bits, while NTFS and FAT identifiers fit into 64 bits.
+    pub fn file_id(&self) -> u128 {
+        self.file_id
+    }
+}
+
+/// Gets the identity of `path` itself, without following it if it is a junction point.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use std::fs;
+/// # use junction::{create, file_id, target_file_id};
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     let tmpdir = tempfile::tempdir()?;
+///     let target = tmpdir.path().join("target");
+///     let junction = tmpdir.path().join("junction");
+///     # fs::create_dir_all(&target)?;
+///     create(&target, &junction)?;
+///     assert_ne!(file_id(&junction)?, file_id(&target)?);
+///     assert_eq!(target_file_id(&junction)?, file_id(&target)?);
+///     Ok(())
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub fn file_id<P: AsRef<Path>>(path: P) -> io::Result<FileId> {
+    internals::file_id(path.as_ref(), false)
+}
+
+/// Gets the identity of the directory that `path` finally resolves to,
+/// following junction points and other reparse points on the way.
+///
+/// # Error
+///
+/// This function errors if the target does not exist, or if the junction
+/// points, directly or indirectly, back to itself.
+pub fn target_file_id<P: AsRef<Path>>(path: P) -> io::Result<FileId> {
+    internals::file_id(path.as_ref(), true)
+}
@@ -1,128 +1,1567 @@
+#[macro_use]
+mod trace;
 mod c;
 mod cast;
+#[cfg(feature = "etw")]
+mod etw;
 mod helpers;
 
-use std::ffi::OsString;
-use std::mem::size_of;
-use std::os::windows::ffi::OsStringExt;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::ffi::{OsStr, OsString};
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
+use std::os::windows::fs::MetadataExt;
 use std::os::windows::io::AsRawHandle;
-use std::path::{Path, PathBuf};
-use std::ptr::{addr_of_mut, copy_nonoverlapping};
-use std::{cmp, fs, io, slice};
+use std::path::{Component, Path, PathBuf};
+use std::{fs, io};
 
-use cast::BytesAsReparseDataBuffer;
+use cast::{BytesAsReparseDataBuffer, SmallReparseDataBuffer, StackReparseDataBuffer};
+
+use crate::diagnostics::{Privilege, Volume};
+use crate::reparse::{self, parse_reparse_data, MountPointData, ReparseData, SymlinkData};
+use crate::{
+    AppExecLink, DeleteOutcome, Diagnostics, DirEntry, DriveType, FileId, FilesystemCapabilities, Guid, JunctionKind,
+    JunctionMetadata, Options, Policy, PolicyViolation, ReparseDump, ReparseKind, ResolvedJunction, TagMismatch,
+    TargetMismatch, TargetNormalization, UnsupportedTarget, UnsupportedTargetReason, Verification,
+};
 
 /// This prefix indicates to NTFS that the path is to be treated as a non-interpreted
 /// path in the virtual file system.
 const NON_INTERPRETED_PATH_PREFIX: [u16; 4] = helpers::utf16s(br"\??\");
+/// Win32 paths with this prefix are passed to the file system without normalization.
+const VERBATIM_PATH_PREFIX: [u16; 4] = helpers::utf16s(br"\\?\");
+/// Win32 paths with this prefix refer to the device namespace.
+const DEVICE_PATH_PREFIX: [u16; 4] = helpers::utf16s(br"\\.\");
+/// Substitute names of volume mount points start with this prefix.
+const VOLUME_GUID_PREFIX: [u16; 11] = helpers::utf16s(br"\??\Volume{");
 
-const WCHAR_SIZE: u16 = size_of::<u16>() as _;
-
-pub fn create(target: &Path, junction: &Path) -> io::Result<()> {
-    const UNICODE_NULL_SIZE: u16 = WCHAR_SIZE;
-    const MAX_AVAILABLE_PATH_BUFFER: u16 = c::MAXIMUM_REPARSE_DATA_BUFFER_SIZE as u16
-        - c::REPARSE_DATA_BUFFER_HEADER_SIZE
-        - c::MOUNT_POINT_REPARSE_BUFFER_HEADER_SIZE
-        - 2 * UNICODE_NULL_SIZE;
-
+pub fn create(target: &Path, junction: &Path, opts: &Options) -> io::Result<()> {
     // We're using low-level APIs to create the junction, and these are more picky about paths.
     // For example, forward slashes cannot be used as a path separator, so we should try to
     // canonicalize the path first.
-    let target = helpers::get_full_path(target)?;
-    fs::create_dir(junction)?;
-    let file = helpers::open_reparse_point(junction, true)?;
-    let target_len_in_bytes = {
-        // "\??\" + target
-        let len = NON_INTERPRETED_PATH_PREFIX.len().saturating_add(target.len());
-        let min_len = cmp::min(len, u16::MAX as usize) as u16;
-        // Len without `UNICODE_NULL` at the end
-        let target_len_in_bytes = min_len.saturating_mul(WCHAR_SIZE);
-        // Check for buffer overflow.
-        if target_len_in_bytes > MAX_AVAILABLE_PATH_BUFFER {
-            return Err(io::Error::new(io::ErrorKind::InvalidInput, "`target` is too long"));
-        }
-        target_len_in_bytes
+    let target = normalize_target(&resolve_target(target, junction, opts), opts)?;
+    create_mount_point(&target, junction, opts)
+}
+
+pub fn create_checked(target: &Path, junction: &Path, opts: &Options) -> io::Result<()> {
+    // The target is not followed, so a junction point to a directory passes.
+    let metadata = match fs::symlink_metadata(resolve_target(target, junction, opts)) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "`target` does not exist"))
+        }
+        result => result?,
     };
+    if metadata.file_attributes() & c::FILE_ATTRIBUTE_DIRECTORY == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "`target` is not a directory",
+        ));
+    }
+    create(target, junction, opts)
+}
 
-    // Redefine the above char array into a ReparseDataBuffer we can work with
-    let mut data = BytesAsReparseDataBuffer::new();
-    let rdb = data.as_mut_ptr();
-    let in_buffer_size: u16 = unsafe {
-        // Set the type of reparse point we are creating
-        addr_of_mut!((*rdb).ReparseTag).write(c::IO_REPARSE_TAG_MOUNT_POINT);
-        addr_of_mut!((*rdb).Reserved).write(0);
-
-        // We write target at offset 0 of PathBuffer
-        addr_of_mut!((*rdb).ReparseBuffer.SubstituteNameOffset).write(0);
-        addr_of_mut!((*rdb).ReparseBuffer.SubstituteNameLength).write(target_len_in_bytes);
-
-        // We do not use PrintName. However let's set its offset correctly right after SubstituteName
-        addr_of_mut!((*rdb).ReparseBuffer.PrintNameOffset).write(target_len_in_bytes + UNICODE_NULL_SIZE);
-        addr_of_mut!((*rdb).ReparseBuffer.PrintNameLength).write(0);
-
-        let mut path_buffer_ptr: *mut u16 = addr_of_mut!((*rdb).ReparseBuffer.PathBuffer).cast();
-        // Safe because we checked `MAX_AVAILABLE_PATH_BUFFER`
-        copy_nonoverlapping(
-            NON_INTERPRETED_PATH_PREFIX.as_ptr(),
-            path_buffer_ptr,
-            NON_INTERPRETED_PATH_PREFIX.len(),
-        );
-        // TODO: Do we need to write the NULL-terminator byte?
-        // It looks like libuv does that.
-        path_buffer_ptr = path_buffer_ptr.add(NON_INTERPRETED_PATH_PREFIX.len());
-        copy_nonoverlapping(target.as_ptr(), path_buffer_ptr, target.len());
-
-        // Set the total size of the data buffer
-        let size = target_len_in_bytes.wrapping_add(c::MOUNT_POINT_REPARSE_BUFFER_HEADER_SIZE + 2 * UNICODE_NULL_SIZE);
-        addr_of_mut!((*rdb).ReparseDataLength).write(size);
-        size.wrapping_add(c::REPARSE_DATA_BUFFER_HEADER_SIZE)
+pub fn create_resolved(target: &Path, junction: &Path, opts: &Options) -> io::Result<ResolvedJunction> {
+    let target = normalize_target(&resolve_target(target, junction, opts), opts)?;
+    let full_junction = helpers::get_full_path(junction)?;
+    create_mount_point(&target, junction, opts)?;
+    let substitute_name = [substitute_name_prefix(&target), &target].concat();
+    Ok(ResolvedJunction {
+        junction: PathBuf::from(OsString::from_wide(&full_junction)),
+        substitute_name: OsString::from_wide(&substitute_name),
+        target: target_from_substitute_name(substitute_name)?,
+    })
+}
+
+// Joins a relative `target` to the parent directory of `junction` if requested
+// by `opts`. Otherwise, relative targets are resolved against the current directory.
+fn resolve_target<'a>(target: &'a Path, junction: &Path, opts: &Options) -> Cow<'a, Path> {
+    match junction.parent() {
+        Some(parent) if opts.relative_to_junction && target.is_relative() => Cow::Owned(parent.join(target)),
+        _ => Cow::Borrowed(target),
+    }
+}
+
+pub fn create_with_base(base: &Path, target: &Path, junction: &Path, opts: &Options) -> io::Result<()> {
+    let target = helpers::join_lexically(base, target)?;
+    let junction = helpers::join_lexically(base, junction)?;
+    // `GetFullPathNameW` does not consult the current directory for the fully
+    // qualified paths that `join_lexically` returns.
+    create_mount_point(&normalize_target(&target, opts)?, &junction, opts)
+}
+
+pub fn join_lexically(base: &Path, path: &Path) -> io::Result<PathBuf> {
+    helpers::join_lexically(base, path)
+}
+
+pub fn create_to_volume(volume: &OsStr, junction: &Path, opts: &Options) -> io::Result<()> {
+    let target = volume_guid_target(volume)?;
+    create_mount_point(&target, junction, opts)
+}
+
+// Normalizes the accepted spellings of a volume GUID to `Volume{GUID}\`,
+// which is written verbatim after the `\??\` prefix.
+fn volume_guid_target(volume: &OsStr) -> io::Result<Vec<u16>> {
+    const GUID_LEN: usize = 36;
+    let invalid = || io::Error::new(io::ErrorKind::InvalidInput, "`volume` is not a volume GUID");
+    let volume = volume.to_str().ok_or_else(invalid)?;
+    let guid = volume
+        .strip_prefix(r"\\?\")
+        .or_else(|| volume.strip_prefix(r"\??\"))
+        .unwrap_or(volume);
+    let guid = guid.strip_prefix("Volume").unwrap_or(guid);
+    let guid = guid.strip_suffix('\\').unwrap_or(guid);
+    let guid = guid.strip_prefix('{').and_then(|g| g.strip_suffix('}')).unwrap_or(guid);
+    let is_valid = guid.len() == GUID_LEN
+        && guid.bytes().enumerate().all(|(i, b)| match i {
+            8 | 13 | 18 | 23 => b == b'-',
+            _ => b.is_ascii_hexdigit(),
+        });
+    if !is_valid {
+        return Err(invalid());
+    }
+    Ok(format!("Volume{{{}}}\\", guid).encode_utf16().collect())
+}
+
+pub fn mount_point_data(target: &Path) -> io::Result<Vec<u8>> {
+    let target = normalize_target(target, &Options::new())?;
+    Ok(mount_point_buffer(&target, &[])?.as_bytes().to_vec())
+}
+
+// Fails with `UnsupportedTarget` if `target` is a network path or has names
+// ending in a dot or a space, unless allowed by `opts` or written verbatim. Such targets are
+// written fine, but cause trouble when the junction is used.
+fn check_target(target: &[u16], opts: &Options) -> io::Result<()> {
+    const UNC_PREFIX: [u16; 2] = helpers::utf16s(br"\\");
+    const UNC_COMPONENT: [u16; 4] = helpers::utf16s(br"UNC\");
+
+    if opts.normalization == TargetNormalization::Verbatim {
+        return Ok(());
+    }
+    let rest = target
+        .strip_prefix(&VERBATIM_PATH_PREFIX)
+        .or_else(|| target.strip_prefix(&NON_INTERPRETED_PATH_PREFIX))
+        .unwrap_or(target);
+    // `\\server\share` and `\\?\UNC\server\share`, the latter without its prefix
+    // as returned by `normalize_target`.
+    let is_unc = (rest.starts_with(&UNC_PREFIX)
+        && !rest.starts_with(&VERBATIM_PATH_PREFIX)
+        && !rest.starts_with(&DEVICE_PATH_PREFIX))
+        || rest
+            .get(..UNC_COMPONENT.len())
+            .map_or(false, |start| helpers::paths_equal(start, &UNC_COMPONENT));
+    let reason = if is_unc && !opts.allow_unc_targets {
+        UnsupportedTargetReason::NetworkPath
+    } else if has_trailing_dot_or_space(rest) && !opts.allow_trailing_dots_and_spaces {
+        UnsupportedTargetReason::TrailingDotOrSpace
+    } else {
+        return Ok(());
     };
+    let target = PathBuf::from(OsString::from_wide(target));
+    Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        UnsupportedTarget::new(target, reason),
+    ))
+}
+
+// Applies the policies of `opts` to a junction point about to be created or
+// retargeted at `junction`, with the substitute name "\??\" + `target`.
+fn check_policies(target: &[u16], junction: &Path, opts: &Options) -> io::Result<()> {
+    if opts.remote_filesystems == Policy::Allow && opts.cross_volume == Policy::Allow {
+        return Ok(());
+    }
+    let target = target_from_substitute_name([substitute_name_prefix(target), target].concat())?;
+    // Paths whose volume cannot be queried are left to the operation itself.
+    if opts.remote_filesystems != Policy::Allow {
+        for path in [junction, target.as_path()] {
+            if helpers::drive_type(path).ok() == Some(c::DRIVE_REMOTE) {
+                let violation = PolicyViolation::RemoteFilesystem {
+                    path: path.to_path_buf(),
+                };
+                opts.check_policy(opts.remote_filesystems, violation)?;
+            }
+        }
+    }
+    if opts.cross_volume != Policy::Allow {
+        // The junction point itself may not exist yet, but its parent must.
+        let parent = match junction.parent() {
+            Some(parent) if parent.as_os_str().is_empty() => Path::new("."),
+            Some(parent) => parent,
+            None => junction,
+        };
+        if let (Ok((parent_volume, _)), Ok((target_volume, _))) =
+            (helpers::file_id(parent, true), helpers::file_id(&target, true))
+        {
+            if parent_volume != target_volume {
+                let violation = PolicyViolation::CrossVolume {
+                    junction: junction.to_path_buf(),
+                    target,
+                };
+                opts.check_policy(opts.cross_volume, violation)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Whether a name in `path` ends with a dot or a space, apart from the `.` and
+// `..` names. `GetFullPathNameW` strips those, so they only get here through
+// verbatim paths.
+fn has_trailing_dot_or_space(path: &[u16]) -> bool {
+    const DOT: u16 = b'.' as u16;
+    const SPACE: u16 = b' ' as u16;
 
-    helpers::set_reparse_point(file.as_raw_handle() as isize, rdb, u32::from(in_buffer_size))
+    path.split(|&c| c == u16::from(b'\\')).any(|name| match name {
+        [] | [DOT] | [DOT, DOT] => false,
+        [.., last] => *last == DOT || *last == SPACE,
+    })
 }
 
-pub fn delete(junction: &Path) -> io::Result<()> {
-    let file = helpers::open_reparse_point(junction, true)?;
-    helpers::delete_reparse_point(file.as_raw_handle() as isize)
+// Returns the NT path `\Device\...` of a `\\?\GLOBALROOT\Device\...` path,
+// such as a path into a Volume Shadow Copy snapshot.
+fn device_path(target: &Path) -> Option<Vec<u16>> {
+    const GLOBALROOT: [u16; 10] = helpers::utf16s(b"GLOBALROOT");
+
+    let wide: Vec<u16> = target.as_os_str().encode_wide().collect();
+    let rest = wide
+        .strip_prefix(&VERBATIM_PATH_PREFIX)
+        .or_else(|| wide.strip_prefix(&DEVICE_PATH_PREFIX))
+        .or_else(|| wide.strip_prefix(&NON_INTERPRETED_PATH_PREFIX))?;
+    if !helpers::paths_equal(rest.get(..GLOBALROOT.len())?, &GLOBALROOT) {
+        return None;
+    }
+    let path = &rest[GLOBALROOT.len()..];
+    if path.first() == Some(&u16::from(b'\\')) {
+        Some(path.to_vec())
+    } else {
+        None
+    }
 }
 
-pub fn exists(junction: &Path) -> io::Result<bool> {
-    if !junction.exists() {
+// Returns what precedes `target` in the substitute name: "\??\", or nothing
+// for NT paths such as `\Device\HarddiskVolume1\dir`.
+fn substitute_name_prefix(target: &[u16]) -> &'static [u16] {
+    if JunctionKind::of_substitute_name(target) == JunctionKind::DevicePath {
+        &[]
+    } else {
+        &NON_INTERPRETED_PATH_PREFIX
+    }
+}
+
+// Builds the reparse data of a mount point whose substitute name is "\??\" + `target`,
+// or `target` itself if it is an NT path such as `\Device\HarddiskVolume1\dir`.
+// The layout is the one of `ReparseData::to_bytes`, but the data is built on the stack.
+fn mount_point_buffer(target: &[u16], print_name: &[u16]) -> io::Result<StackReparseDataBuffer> {
+    const UNICODE_NULL: [u8; 2] = [0; 2];
+
+    let prefix = substitute_name_prefix(target);
+    let substitute_name_len = (prefix.len() + target.len()) * 2;
+    let print_name_len = print_name.len() * 2;
+    let data_len =
+        reparse::MOUNT_POINT_REPARSE_BUFFER_HEADER_SIZE + substitute_name_len + print_name_len + 2 * UNICODE_NULL.len();
+    if reparse::REPARSE_DATA_BUFFER_HEADER_SIZE + data_len > reparse::MAXIMUM_REPARSE_DATA_BUFFER_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "`target` is too long"));
+    }
+    let mut buf = StackReparseDataBuffer::new();
+    buf.push(&c::IO_REPARSE_TAG_MOUNT_POINT.to_le_bytes());
+    buf.push(&(data_len as u16).to_le_bytes());
+    // Reserved
+    buf.push(&0u16.to_le_bytes());
+    // Offsets and lengths of the substitute name and the print name.
+    buf.push(&0u16.to_le_bytes());
+    buf.push(&(substitute_name_len as u16).to_le_bytes());
+    buf.push(&(substitute_name_len as u16 + 2).to_le_bytes());
+    buf.push(&(print_name_len as u16).to_le_bytes());
+    buf.push_wide(prefix);
+    buf.push_wide(target);
+    buf.push(&UNICODE_NULL);
+    buf.push_wide(print_name);
+    buf.push(&UNICODE_NULL);
+    Ok(buf)
+}
+
+// Returns the print name to write along with `target`. It is empty, as Windows
+// ignores it, unless `opts` asks for the layout of `mklink /J`, which repeats
+// targets such as `C:\dir` there without the `\??\` prefix.
+fn print_name<'a>(target: &'a [u16], opts: &Options) -> &'a [u16] {
+    if opts.mklink_compatible && target.get(1) == Some(&u16::from(b':')) {
+        target
+    } else {
+        &[]
+    }
+}
+
+// Creates the `junction` directory and turns it into a mount point
+// whose substitute name is "\??\" + `target`.
+fn create_mount_point(target: &[u16], junction: &Path, opts: &Options) -> io::Result<()> {
+    check_target(target, opts)?;
+    check_policies(target, junction, opts)?;
+    create_mount_point_with(
+        junction,
+        &mut mount_point_buffer(target, print_name(target, opts))?,
+        opts,
+    )
+}
+
+// Returns the target of the copied junction point, for the audit hook.
+pub fn copy_junction(src: &Path, dst: &Path, opts: &Options) -> io::Result<PathBuf> {
+    let (bytes, security) = helpers::retry(opts, || {
+        let file = helpers::open_reparse_point(src, false, opts)?;
+        Ok((read_mount_point_file(&file)?, read_security(&file, opts)?))
+    })?;
+    let target = mount_point_target(&bytes)?;
+    // The data is copied as is, without interpreting the names.
+    let mut data = StackReparseDataBuffer::new();
+    data.push(&bytes);
+    create_mount_point_with(dst, &mut data, opts)?;
+    if let Some(mut descriptor) = security {
+        apply_security(dst, &mut descriptor, opts)?;
+    }
+    Ok(target)
+}
+
+// Returns the target of the moved junction point, for the audit hook.
+pub fn move_junction(src: &Path, dst: &Path, opts: &Options) -> io::Result<PathBuf> {
+    let target = helpers::retry(opts, || {
+        let file = helpers::open_reparse_point(src, false, opts)?;
+        mount_point_target(&read_mount_point_file(&file)?)
+    })?;
+    // Within a volume, renaming moves the junction point itself.
+    match fs::rename(src, dst) {
+        Err(e) if e.raw_os_error() == Some(c::ERROR_NOT_SAME_DEVICE as i32) => {}
+        result => {
+            etw_event!("move", src, Some(dst), result.as_ref().map(drop));
+            return result.map(|()| target);
+        }
+    }
+
+    let file = helpers::retry(opts, || helpers::open_reparse_point(src, true, opts))?;
+    let bytes = read_mount_point_file(&file)?;
+    let mut info = helpers::basic_info(file.as_raw_handle() as isize)?;
+    let security = read_security(&file, opts)?;
+    let mut data = StackReparseDataBuffer::new();
+    data.push(&bytes);
+    create_mount_point_with(dst, &mut data, opts)?;
+    // These attributes come with the directory and the mount point, and cannot be set.
+    info.FileAttributes &= !(c::FILE_ATTRIBUTE_DIRECTORY | c::FILE_ATTRIBUTE_REPARSE_POINT);
+    // The change time is maintained by the file system.
+    info.ChangeTime = 0;
+    let copied = helpers::open_reparse_point(dst, true, opts)
+        .and_then(|copy| helpers::set_basic_info(copy.as_raw_handle() as isize, &info))
+        .and_then(|()| match security {
+            Some(mut descriptor) => apply_security(dst, &mut descriptor, opts),
+            None => Ok(()),
+        });
+    if let Err(e) = copied {
+        // Don't leave behind a partial copy.
+        let _ = delete(dst, opts).and_then(|()| fs::remove_dir(dst));
+        return Err(e);
+    }
+    let result = helpers::delete_reparse_point(file.as_raw_handle() as isize);
+    etw_event!("delete", src, None, result.as_ref().map(drop));
+    drop(file);
+    let moved = result.and_then(|()| {
+        fs::remove_dir(src).map_err(|e| {
+            // Put the reparse data back on the empty directory left at `src`.
+            let mut data = StackReparseDataBuffer::new();
+            data.push(&bytes);
+            let _ = helpers::open_reparse_point(src, true, opts).and_then(|file| {
+                helpers::set_reparse_point(file.as_raw_handle() as isize, data.as_mut_ptr(), data.len() as u32)
+            });
+            e
+        })
+    });
+    if let Err(e) = moved {
+        // Don't leave the junction point in both places.
+        let _ = delete(dst, opts).and_then(|()| fs::remove_dir(dst));
+        return Err(e);
+    }
+    Ok(target)
+}
+
+// Reads the owner and DACL of `file` if requested by `opts`.
+fn read_security(file: &fs::File, opts: &Options) -> io::Result<Option<Vec<u8>>> {
+    if !opts.preserve_security {
+        return Ok(None);
+    }
+    helpers::security_descriptor(file.as_raw_handle() as isize).map(Some)
+}
+
+fn apply_security(junction: &Path, descriptor: &mut [u8], opts: &Options) -> io::Result<()> {
+    let file = helpers::open_reparse_point_for_security(junction, opts)?;
+    helpers::set_security_descriptor(file.as_raw_handle() as isize, descriptor)
+}
+
+// Creates the directory of a new junction point, with the security descriptor
+// of `opts` if there is one.
+fn create_dir(junction: &Path, opts: &Options) -> io::Result<()> {
+    match &opts.security_descriptor {
+        Some(sddl) => helpers::create_dir_with_security(junction, sddl),
+        None => fs::create_dir(junction),
+    }
+}
+
+// Creates the `junction` directory and sets the mount point `data` on it.
+fn create_mount_point_with(junction: &Path, data: &mut StackReparseDataBuffer, opts: &Options) -> io::Result<()> {
+    let result = write_new_mount_point(junction, data, opts);
+    etw_event!(
+        "create",
+        junction,
+        data_target(data.as_bytes()).as_deref(),
+        result.as_ref().map(drop)
+    );
+    result
+}
+
+fn write_new_mount_point(junction: &Path, data: &mut StackReparseDataBuffer, opts: &Options) -> io::Result<()> {
+    helpers::check_reparse_point_support(junction)?;
+    if opts.create_parents {
+        if let Some(parent) = junction.parent() {
+            // `create_dir_all` tolerates other processes creating the same directories.
+            fs::create_dir_all(parent)?;
+        }
+    }
+    let created = match create_dir(junction, opts) {
+        Ok(()) => true,
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists && opts.overwrite && is_reparse_point(junction)? => {
+            return retarget_mount_point(junction, data, opts);
+        }
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists && opts.adopt_existing => {
+            if !is_empty_dir(junction)? {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    "`junction` already exists and is not an empty directory",
+                ));
+            }
+            false
+        }
+        Err(e) => return Err(e),
+    };
+    let result = helpers::retry(opts, || {
+        let file = helpers::open_reparse_point(junction, true, opts)?;
+        helpers::set_reparse_point(file.as_raw_handle() as isize, data.as_mut_ptr(), data.len() as u32)
+    })
+    .and_then(|()| configure_new_junction(junction, opts));
+    if result.is_err() && created {
+        // Don't leave behind the empty directory that we just created.
+        let _ = fs::remove_dir(junction);
+    }
+    result
+}
+
+// Sets the attributes and DACL requested by `opts` on a new junction point.
+// With `os_compatibility`, it is configured like `C:\Documents and Settings`:
+// hidden, system, and denying everyone to list it, so that it redirects old
+// paths without showing up twice in listings and backups.
+fn configure_new_junction(junction: &Path, opts: &Options) -> io::Result<()> {
+    const COMPATIBILITY_DACL: &str = "D:PAI(D;;0x100001;;;WD)(A;;0x1200a9;;;WD)(A;;FA;;;SY)(A;;FA;;;BA)";
+
+    let mut attributes = 0;
+    if opts.os_compatibility {
+        attributes |= c::FILE_ATTRIBUTE_HIDDEN | c::FILE_ATTRIBUTE_SYSTEM;
+    }
+    if opts.not_content_indexed {
+        attributes |= c::FILE_ATTRIBUTE_NOT_CONTENT_INDEXED;
+    }
+    if attributes == 0 {
+        return Ok(());
+    }
+    let file = helpers::open_reparse_point_for_attributes(junction, opts.os_compatibility, opts)?;
+    let handle = file.as_raw_handle() as isize;
+    let mut info = helpers::basic_info(handle)?;
+    // Zero timestamps are left unchanged.
+    info.CreationTime = 0;
+    info.LastAccessTime = 0;
+    info.LastWriteTime = 0;
+    info.ChangeTime = 0;
+    // These attributes come with the directory and the mount point, and cannot be set.
+    info.FileAttributes =
+        (info.FileAttributes | attributes) & !(c::FILE_ATTRIBUTE_DIRECTORY | c::FILE_ATTRIBUTE_REPARSE_POINT);
+    helpers::set_basic_info(handle, &info)?;
+    if opts.os_compatibility {
+        // Last, as the DACL denies the access needed to modify the junction afterwards.
+        helpers::set_dacl(handle, COMPATIBILITY_DACL)?;
+    }
+    Ok(())
+}
+
+pub fn verify(junction: &Path, expected: &Path, opts: &Options) -> io::Result<Verification> {
+    match fs::symlink_metadata(junction) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Verification::Missing),
+        Err(e) => return Err(e),
+        Ok(metadata) if !metadata.file_type().is_symlink() => return Ok(Verification::NotAJunction),
+        Ok(_) => {}
+    }
+    let bytes = helpers::retry(opts, || {
+        let file = helpers::open_reparse_point(junction, false, opts)?;
+        read_reparse_file(&file)
+    })?;
+    let name = match parse_reparse_data(&bytes)? {
+        ReparseData::MountPoint(data) => data.substitute_name().to_vec(),
+        _ => return Ok(Verification::NotAJunction),
+    };
+    if helpers::paths_equal(
+        strip_non_interpreted_prefix(&name),
+        &normalize_target(&resolve_target(expected, junction, opts), opts)?,
+    ) {
+        Ok(Verification::Match)
+    } else {
+        Ok(Verification::DifferentTarget(target_from_substitute_name(name)?))
+    }
+}
+
+pub fn reparse_tag(path: &Path) -> io::Result<Option<u32>> {
+    let name = match path.file_name() {
+        Some(name) => name,
+        // Root directories have no directory entry, and cannot be reparse points.
+        None => {
+            fs::metadata(path)?;
+            return Ok(None);
+        }
+    };
+    // `FindFirstFileExW` would match other entries against these wildcards.
+    if name.encode_wide().any(|c| c == u16::from(b'*') || c == u16::from(b'?')) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "`path` must not contain wildcards",
+        ));
+    }
+    helpers::find_reparse_tag(path)
+}
+
+pub fn explain(path: &Path) -> Diagnostics {
+    let to_path = |wide: Vec<u16>| PathBuf::from(OsString::from_wide(&wide));
+    let reparse_tag = reparse_tag(path);
+    let target = match reparse_tag {
+        Ok(Some(reparse::IO_REPARSE_TAG_MOUNT_POINT)) => Some(get_target(path, &Options::new())),
+        _ => None,
+    };
+    Diagnostics {
+        path: path.to_path_buf(),
+        full_path: helpers::get_full_path(path).map(to_path),
+        nt_path: helpers::nt_path(path).map(to_path),
+        normalized: normalize_target(path, &Options::new()).map(to_path),
+        volume: helpers::volume_information(path).map(|(root, filesystem, flags)| Volume {
+            root,
+            filesystem,
+            flags,
+        }),
+        attributes: fs::symlink_metadata(path).map(|metadata| metadata.file_attributes()),
+        reparse_tag,
+        target,
+        privileges: helpers::token_privileges().map(|privileges| {
+            privileges
+                .into_iter()
+                .map(|(name, enabled)| Privilege { name, enabled })
+                .collect()
+        }),
+    }
+}
+
+pub fn filesystem_capabilities(path: &Path) -> io::Result<FilesystemCapabilities> {
+    let (volume, filesystem, flags) = helpers::volume_information(path)?;
+    let (sectors_per_cluster, bytes_per_sector) = helpers::cluster_geometry(&volume)?;
+    Ok(FilesystemCapabilities {
+        // Windows versions without Dev Drives cannot query them.
+        dev_drive: helpers::is_dev_drive(&volume).unwrap_or(false),
+        case_sensitive_dir: helpers::is_case_sensitive_dir(path).ok(),
+        volume,
+        filesystem,
+        flags,
+        sectors_per_cluster,
+        bytes_per_sector,
+    })
+}
+
+pub fn symlink_metadata(path: &Path) -> io::Result<fs::Metadata> {
+    // `std` reports name surrogates, which include mount points, as symlinks.
+    fs::symlink_metadata(path)
+}
+
+pub fn read_dir_no_follow(path: &Path) -> io::Result<Vec<DirEntry>> {
+    let mut entries = Vec::new();
+    helpers::find_entries(path, |data| {
+        let reparse_tag = if data.dwFileAttributes & c::FILE_ATTRIBUTE_REPARSE_POINT != 0 {
+            // `dwReserved0` holds the reparse tag only for reparse points.
+            Some(data.dwReserved0)
+        } else {
+            None
+        };
+        entries.push(DirEntry {
+            path: path.join(helpers::utf16_until_nul(&data.cFileName)),
+            attributes: data.dwFileAttributes,
+            reparse_tag,
+        });
+    })?;
+    Ok(entries)
+}
+
+pub fn metadata(path: &Path, opts: &Options) -> io::Result<JunctionMetadata> {
+    let mut metadata = JunctionMetadata {
+        exists: false,
+        tag: None,
+        target: None,
+        kind: None,
+        target_drive_type: None,
+    };
+    let bytes = match read_reparse_point(path, opts) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(metadata),
+        Err(e) if e.raw_os_error() == Some(c::ERROR_NOT_A_REPARSE_POINT) => {
+            metadata.exists = true;
+            return Ok(metadata);
+        }
+        Err(e) => return Err(e),
+    };
+    metadata.exists = true;
+    metadata.tag = Some(reparse::split_reparse_data(&bytes)?.0);
+    if let ReparseData::MountPoint(data) = parse_reparse_data(&bytes)? {
+        let name = data.substitute_name();
+        metadata.kind = Some(JunctionKind::of_substitute_name(name));
+        let target = target_from_substitute_name(name.to_vec())?;
+        metadata.target_drive_type = helpers::drive_type(&target).ok().and_then(DriveType::from_raw);
+        metadata.target = Some(target);
+    }
+    Ok(metadata)
+}
+
+// Turns `target` into a substitute name without its `\??\` prefix, as
+// configured by `opts`. Paths that are already verbatim, such as
+// `\\?\Volume{GUID}\`, are kept as is. Device paths are kept as NT paths,
+// which are written without the `\??\` prefix.
+fn normalize_target(target: &Path, opts: &Options) -> io::Result<Vec<u16>> {
+    let wide: Vec<u16> = target.as_os_str().encode_wide().collect();
+    if opts.normalization == TargetNormalization::Verbatim {
+        return Ok(strip_non_interpreted_prefix(&wide).to_vec());
+    }
+    if let Some(path) = device_path(target) {
+        return Ok(path);
+    }
+    if let Some(rest) = wide
+        .strip_prefix(&VERBATIM_PATH_PREFIX)
+        .or_else(|| wide.strip_prefix(&NON_INTERPRETED_PATH_PREFIX))
+    {
+        return Ok(rest.to_vec());
+    }
+    if opts.normalization == TargetNormalization::Full {
+        if opts.nt_paths {
+            let path = helpers::nt_path(target)?;
+            return Ok(strip_non_interpreted_prefix(&path).to_vec());
+        }
+        return helpers::get_full_path(target);
+    }
+    let wide: Vec<u16> = wide
+        .into_iter()
+        .map(|c| if c == u16::from(b'/') { u16::from(b'\\') } else { c })
+        .collect();
+    if !Path::new(&OsString::from_wide(&wide)).is_absolute() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "`target` must be an absolute path",
+        ));
+    }
+    Ok(wide)
+}
+
+pub fn create_idempotent(target: &Path, junction: &Path, opts: &Options) -> io::Result<bool> {
+    if is_reparse_point(junction).unwrap_or(false) {
+        if let Ok(name) = read_substitute_name(junction, opts) {
+            if helpers::paths_equal(
+                strip_non_interpreted_prefix(&name),
+                &normalize_target(&resolve_target(target, junction, opts), opts)?,
+            ) {
+                return Ok(false);
+            }
+        }
+    }
+    create(target, junction, opts)?;
+    Ok(true)
+}
+
+pub fn same_target(a: &Path, b: &Path, opts: &Options) -> io::Result<bool> {
+    let a_name = read_substitute_name(a, opts)?;
+    let b_name = read_substitute_name(b, opts)?;
+    if helpers::paths_equal(
+        strip_non_interpreted_prefix(&a_name),
+        strip_non_interpreted_prefix(&b_name),
+    ) {
+        return Ok(true);
+    }
+    if !opts.compare_file_ids {
         return Ok(false);
     }
-    let file = helpers::open_reparse_point(junction, false)?;
-    // Allocate enough space to fit the maximum sized reparse data buffer
-    let mut data = BytesAsReparseDataBuffer::new();
-    // XXX: Could also use FindFirstFile to read the reparse point type
-    // Ref https://learn.microsoft.com/en-us/windows/win32/fileio/reparse-point-tags
-    helpers::get_reparse_data_point(file.as_raw_handle() as isize, data.as_mut_ptr())?;
-    // SATETY: rdb should be initialized now
-    let rdb = unsafe { data.assume_init() };
+    // Opening the junctions without `FILE_FLAG_OPEN_REPARSE_POINT` resolves
+    // them, including further junctions and volume GUID paths on the way.
+    Ok(file_id(a, true)? == file_id(b, true)?)
+}
+
+pub fn file_id(path: &Path, follow: bool) -> io::Result<FileId> {
+    let (volume_serial_number, file_id) = helpers::file_id(path, follow)?;
+    Ok(FileId {
+        volume_serial_number,
+        file_id,
+    })
+}
+
+fn strip_non_interpreted_prefix(wide: &[u16]) -> &[u16] {
+    wide.strip_prefix(&NON_INTERPRETED_PATH_PREFIX).unwrap_or(wide)
+}
+
+pub fn set_target(junction: &Path, target: &Path, opts: &Options) -> io::Result<()> {
+    let result = normalize_target(&resolve_target(target, junction, opts), opts).and_then(|target| {
+        check_target(&target, opts)?;
+        check_policies(&target, junction, opts)?;
+        let mut data = mount_point_buffer(&target, print_name(&target, opts))?;
+        retarget_mount_point(junction, &mut data, opts)
+    });
+    etw_event!("retarget", junction, Some(target), result.as_ref().map(drop));
+    result
+}
+
+// Replaces the data of the existing mount point `junction` with `data`. The
+// file system swaps the data in one step, so the junction never disappears.
+fn retarget_mount_point(junction: &Path, data: &mut StackReparseDataBuffer, opts: &Options) -> io::Result<()> {
+    helpers::retry(opts, || {
+        let file = helpers::open_reparse_point(junction, true, opts)?;
+        read_mount_point_file(&file)?;
+        let handle = file.as_raw_handle() as isize;
+        let times = if opts.preserve_timestamps {
+            Some(helpers::basic_info(handle)?)
+        } else {
+            None
+        };
+        helpers::set_reparse_point(handle, data.as_mut_ptr(), data.len() as u32)?;
+        if let Some(mut times) = times {
+            // Zero fields are left unchanged, so only the timestamps are restored.
+            times.ChangeTime = 0;
+            times.FileAttributes = 0;
+            helpers::set_basic_info(handle, &times)?;
+        }
+        Ok(())
+    })
+}
+
+// Returns the target of the symbolic link, for the audit hook.
+pub fn convert_to_symlink(junction: &Path, opts: &Options) -> io::Result<PathBuf> {
+    let result = helpers::retry(opts, || {
+        let file = helpers::open_reparse_point(junction, true, opts)?;
+        let bytes = read_mount_point_file(&file)?;
+        let name = mount_point_of(&bytes)?.substitute_name().to_vec();
+        let (prefix, skip) = win32_form(&name);
+        let print_name: Vec<u16> = prefix.iter().chain(&name[skip..]).copied().collect();
+        let target = PathBuf::from(OsString::from_wide(&print_name));
+        let symlink = ReparseData::Symlink(SymlinkData::new(name, print_name, 0)).to_bytes()?;
+        let mut data = StackReparseDataBuffer::new();
+        data.push(&symlink);
+        replace_reparse_point(&file, &bytes, &mut data).map(|()| target)
+    });
+    etw_event!(
+        "convert",
+        junction,
+        result.as_ref().ok().map(PathBuf::as_path),
+        result.as_ref().map(drop)
+    );
+    result
+}
+
+// Returns the target of the junction point, for the audit hook.
+pub fn convert_to_junction(symlink: &Path, opts: &Options) -> io::Result<PathBuf> {
+    let result = helpers::retry(opts, || {
+        let file = helpers::open_reparse_point(symlink, true, opts)?;
+        let bytes = read_reparse_file(&file)?;
+        let data = match parse_reparse_data(&bytes)? {
+            ReparseData::Symlink(data) => data,
+            other => return Err(io::Error::new(io::ErrorKind::Other, TagMismatch::new(other.tag()))),
+        };
+        if file.metadata()?.file_attributes() & c::FILE_ATTRIBUTE_DIRECTORY == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "`symlink` is a file symbolic link, which cannot become a junction",
+            ));
+        }
+        let name = PathBuf::from(OsString::from_wide(data.substitute_name()));
+        // Relative symbolic links are relative to the directory containing them.
+        let target = match symlink.parent() {
+            Some(parent) if data.is_relative() => parent.join(name),
+            _ => name,
+        };
+        let target = normalize_target(&target, opts)?;
+        check_target(&target, opts)?;
+        let mut data = mount_point_buffer(&target, print_name(&target, opts))?;
+        replace_reparse_point(&file, &bytes, &mut data)?;
+        mount_point_target(data.as_bytes())
+    });
+    etw_event!(
+        "convert",
+        symlink,
+        result.as_ref().ok().map(PathBuf::as_path),
+        result.as_ref().map(drop)
+    );
+    result
+}
+
+// Replaces the reparse point `original` of `file` with `data`, which may have
+// another tag. The tag of a reparse point cannot be changed, so `original` is
+// removed first; the directory itself, with its attributes, stays.
+fn replace_reparse_point(file: &fs::File, original: &[u8], data: &mut StackReparseDataBuffer) -> io::Result<()> {
+    let mut buf = StackReparseDataBuffer::new();
+    buf.push(original);
+    let handle = file.as_raw_handle() as isize;
+    helpers::delete_reparse_point(handle)?;
+    helpers::set_reparse_point(handle, data.as_mut_ptr(), data.len() as u32).map_err(|e| {
+        // Put `original` back rather than leave an empty directory behind.
+        let _ = helpers::set_reparse_point(handle, buf.as_mut_ptr(), buf.len() as u32);
+        e
+    })
+}
+
+fn is_reparse_point(path: &Path) -> io::Result<bool> {
+    // `std` reports name surrogates, which include mount points, as symlinks.
+    Ok(fs::symlink_metadata(path)?.file_type().is_symlink())
+}
+
+// Whether `path` is a plain directory without entries. Reparse points, such
+// as existing junctions, are not plain directories.
+fn is_empty_dir(path: &Path) -> io::Result<bool> {
+    let file_type = fs::symlink_metadata(path)?.file_type();
+    Ok(file_type.is_dir() && !file_type.is_symlink() && fs::read_dir(path)?.next().is_none())
+}
+
+pub fn delete(junction: &Path, opts: &Options) -> io::Result<()> {
+    let result = delete_mount_point(junction, None, opts).map(drop);
+    etw_event!("delete", junction, None, result.as_ref().map(drop));
+    result
+}
+
+// Returns the path of the open directory `dir`, in its `\\?\` form.
+pub fn dir_path(dir: &fs::File) -> io::Result<PathBuf> {
+    helpers::final_path(dir.as_raw_handle() as isize)
+}
+
+pub fn delete_in(dir: &fs::File, name: &Path, opts: &Options) -> io::Result<()> {
+    let mut components = name.components();
+    let name = match (components.next(), components.next()) {
+        (Some(Component::Normal(name)), None) => name,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "`name` must be a single file name",
+            ))
+        }
+    };
+    let result = helpers::retry(opts, || {
+        let file = helpers::nt_open_reparse_point_at(dir.as_raw_handle() as isize, name, true, opts.share_mode)?;
+        read_mount_point_file(&file)?;
+        helpers::delete_reparse_point(file.as_raw_handle() as isize)
+    });
+    etw_event!(
+        "delete",
+        &etw::handle_path(dir.as_raw_handle() as isize).join(name),
+        None,
+        result.as_ref().map(drop)
+    );
+    result
+}
+
+pub fn delete_if_target(junction: &Path, expected: &Path, opts: &Options) -> io::Result<()> {
+    delete_if_target_is(junction, expected, opts).and_then(|outcome| match outcome {
+        DeleteOutcome::Deleted => Ok(()),
+        DeleteOutcome::DifferentTarget(target) => {
+            Err(io::Error::new(io::ErrorKind::Other, TargetMismatch::new(target)))
+        }
+    })
+}
+
+pub fn delete_if_target_is(junction: &Path, expected: &Path, opts: &Options) -> io::Result<DeleteOutcome> {
+    let result = delete_mount_point(junction, Some(expected), opts);
+    // A junction point left in place is no change to report.
+    if !matches!(result, Ok(DeleteOutcome::DifferentTarget(_))) {
+        etw_event!("delete", junction, None, result.as_ref().map(drop));
+    }
+    result
+}
+
+fn delete_mount_point(junction: &Path, expected: Option<&Path>, opts: &Options) -> io::Result<DeleteOutcome> {
+    let expected = match expected {
+        Some(expected) => Some(normalize_target(&resolve_target(expected, junction, opts), opts)?),
+        None => None,
+    };
+    helpers::retry(opts, || {
+        let file = helpers::open_reparse_point(junction, true, opts)?;
+        let bytes = read_mount_point_file(&file)?;
+        if let (Some(expected), ReparseData::MountPoint(data)) = (&expected, parse_reparse_data(&bytes)?) {
+            let name = data.substitute_name();
+            if !helpers::paths_equal(strip_non_interpreted_prefix(name), expected) {
+                return Ok(DeleteOutcome::DifferentTarget(target_from_substitute_name(
+                    name.to_vec(),
+                )?));
+            }
+        }
+        helpers::delete_reparse_point(file.as_raw_handle() as isize).map(|()| DeleteOutcome::Deleted)
+    })
+}
+
+// An open reparse point, with the reparse data of the last query.
+pub struct JunctionFile {
+    file: helpers::ReparsePoint,
+    data: RefCell<Option<Vec<u8>>>,
+}
+
+impl JunctionFile {
+    pub fn open(path: &Path, write: bool, opts: &Options) -> io::Result<Self> {
+        let file = helpers::retry(opts, || helpers::open_reparse_point(path, write, opts))?;
+        Ok(Self {
+            file,
+            data: RefCell::new(None),
+        })
+    }
+
+    pub fn from_file(file: fs::File) -> Self {
+        Self {
+            file: file.into(),
+            data: RefCell::new(None),
+        }
+    }
+
+    // Runs `f` on the cached reparse data, reading it first if needed.
+    fn with_data<T, F: FnOnce(&[u8]) -> io::Result<T>>(&self, f: F) -> io::Result<T> {
+        let mut data = self.data.borrow_mut();
+        let bytes = match &mut *data {
+            Some(bytes) => bytes,
+            empty => empty.insert(read_reparse_file(&self.file)?),
+        };
+        f(bytes)
+    }
+
+    pub fn refresh(&self) {
+        self.data.borrow_mut().take();
+    }
+
+    pub fn tag(&self) -> io::Result<u32> {
+        self.with_data(|bytes| Ok(reparse::split_reparse_data(bytes)?.0))
+    }
+
+    pub fn target(&self) -> io::Result<PathBuf> {
+        self.with_data(|bytes| target_from_substitute_name(mount_point_of(bytes)?.substitute_name().to_vec()))
+    }
+
+    pub fn print_name(&self) -> io::Result<PathBuf> {
+        self.with_data(|bytes| Ok(PathBuf::from(OsString::from_wide(mount_point_of(bytes)?.print_name()))))
+    }
+
+    pub fn delete(self) -> io::Result<()> {
+        let handle = self.file.as_raw_handle() as isize;
+        // Read the data again, as the reparse point may have changed since it was cached.
+        let result = read_mount_point_file(&self.file).and_then(|_| helpers::delete_reparse_point(handle));
+        etw_event!("delete", &etw::handle_path(handle), None, result.as_ref().map(drop));
+        result
+    }
+}
+
+fn mount_point_of(bytes: &[u8]) -> io::Result<MountPointData> {
+    match parse_reparse_data(bytes)? {
+        ReparseData::MountPoint(data) => Ok(data),
+        _ => Err(io::Error::new(io::ErrorKind::Other, "not a reparse tag mount point")),
+    }
+}
+
+#[cfg(feature = "backup")]
+pub fn backup_read(junction: &Path, opts: &Options) -> io::Result<Vec<u8>> {
+    let bytes = helpers::retry(opts, || {
+        let file = helpers::open_reparse_point(junction, false, opts)?;
+        helpers::backup_read_stream(file.as_raw_handle() as isize, c::BACKUP_REPARSE_DATA)
+    })?;
+    let bytes = bytes.ok_or_else(|| io::Error::from_raw_os_error(c::ERROR_NOT_A_REPARSE_POINT))?;
+    check_mount_point_tag(&bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(feature = "backup")]
+pub fn backup_restore(junction: &Path, data: &[u8], opts: &Options) -> io::Result<()> {
+    check_mount_point_tag(data)?;
+    let created = match create_dir(junction, opts) {
+        Ok(()) => true,
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => false,
+        Err(e) => return Err(e),
+    };
+    let result = helpers::retry(opts, || {
+        let file = helpers::open_reparse_point(junction, true, opts)?;
+        helpers::backup_write_stream(file.as_raw_handle() as isize, c::BACKUP_REPARSE_DATA, data)
+    });
+    if result.is_err() && created {
+        // Don't leave behind the empty directory that we just created.
+        let _ = fs::remove_dir(junction);
+    }
+    etw_event!(
+        "create",
+        junction,
+        data_target(data).as_deref(),
+        result.as_ref().map(drop)
+    );
+    result
+}
+
+#[cfg(feature = "cap-std")]
+pub fn create_at(dir: &::cap_std::fs::Dir, target: &Path, junction: &Path) -> io::Result<()> {
+    if !target.is_absolute() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "`target` must be an absolute path",
+        ));
+    }
+    let opts = Options::new();
+    let target = normalize_target(target, &opts)?;
+    check_target(&target, &opts)?;
+    let mut data = mount_point_buffer(&target, &[])?;
+    // Like `check_reparse_point_support` itself, let through directories whose
+    // path cannot be queried.
+    if let Ok(path) = helpers::final_path(dir.as_raw_handle() as isize) {
+        helpers::check_reparse_point_support(&path)?;
+    }
+    dir.create_dir(junction)?;
+    let result = helpers::open_reparse_point_at(dir, junction, true, opts.share_mode).and_then(|file| {
+        let handle = file.as_raw_handle() as isize;
+        let result = helpers::set_reparse_point(handle, data.as_mut_ptr(), data.len() as u32);
+        etw_event!(
+            "create",
+            &etw::handle_path(handle),
+            data_target(data.as_bytes()).as_deref(),
+            result.as_ref().map(drop)
+        );
+        result
+    });
+    if result.is_err() {
+        // Don't leave behind the empty directory that we just created.
+        let _ = dir.remove_dir(junction);
+    }
+    result
+}
+
+#[cfg(feature = "cap-std")]
+pub fn delete_at(dir: &::cap_std::fs::Dir, junction: &Path) -> io::Result<()> {
+    let file = helpers::open_reparse_point_at(dir, junction, true, Options::new().share_mode)?;
+    let handle = file.as_raw_handle() as isize;
+    let result = read_mount_point_file(&file).and_then(|_| helpers::delete_reparse_point(handle));
+    etw_event!("delete", &etw::handle_path(handle), None, result.as_ref().map(drop));
+    result
+}
+
+#[cfg(feature = "cap-std")]
+pub fn get_target_at(dir: &::cap_std::fs::Dir, junction: &Path) -> io::Result<PathBuf> {
+    let file = helpers::open_reparse_point_at(dir, junction, false, Options::new().share_mode)?;
+    target_from_substitute_name(substitute_name_of(&file)?)
+}
+
+pub fn exists(junction: &Path, opts: &Options) -> io::Result<bool> {
+    if !path_exists(junction)? {
+        return Ok(false);
+    }
+    // Only the tag is needed, which is written even if the rest of the data does not fit.
+    let mut data = SmallReparseDataBuffer::new();
+    let result = helpers::retry(opts, || {
+        let file = helpers::open_reparse_point(junction, false, opts)?;
+        // XXX: Could also use FindFirstFile to read the reparse point type
+        // Ref https://learn.microsoft.com/en-us/windows/win32/fileio/reparse-point-tags
+        match data.read(file.as_raw_handle() as isize) {
+            Err(e) if e.raw_os_error() == Some(c::ERROR_MORE_DATA as i32) => Ok(()),
+            result => result.map(drop),
+        }
+    });
+    match result {
+        Err(e) if opts.lenient_exists && e.raw_os_error() == Some(c::ERROR_NOT_A_REPARSE_POINT) => return Ok(false),
+        result => result?,
+    };
     // The reparse tag indicates if this is a junction or not
-    Ok(rdb.ReparseTag == c::IO_REPARSE_TAG_MOUNT_POINT)
+    match data.tag() {
+        Some(tag) => Ok(tag == c::IO_REPARSE_TAG_MOUNT_POINT),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "reparse data shorter than its header",
+        )),
+    }
+}
+
+pub fn get_target(junction: &Path, opts: &Options) -> io::Result<PathBuf> {
+    target_from_substitute_name(read_substitute_name(junction, opts)?)
+}
+
+// Returns the target of the mount point reparse data `bytes`, for reports.
+#[cfg(feature = "etw")]
+fn data_target(bytes: &[u8]) -> Option<PathBuf> {
+    mount_point_target(bytes).ok()
+}
+
+// Returns the target of the mount point reparse data `bytes`.
+fn mount_point_target(bytes: &[u8]) -> io::Result<PathBuf> {
+    target_from_substitute_name(mount_point_of(bytes)?.substitute_name().to_vec())
 }
 
-pub fn get_target(junction: &Path) -> io::Result<PathBuf> {
-    // MSRV(1.63): use Path::try_exists instead
-    if !junction.exists() {
+fn target_from_substitute_name(wide: Vec<u16>) -> io::Result<PathBuf> {
+    let (prefix, skip) = win32_form(&wide);
+    let mut path = OsString::from_wide(prefix);
+    path.push(OsString::from_wide(&wide[skip..]));
+    Ok(PathBuf::from(path))
+}
+
+// Returns the prefix that turns a substitute name into its Win32 form, once the
+// returned number of leading characters is removed.
+fn win32_form(wide: &[u16]) -> (&'static [u16], usize) {
+    const GLOBALROOT_PREFIX: [u16; 14] = helpers::utf16s(br"\\?\GLOBALROOT");
+
+    if JunctionKind::of_substitute_name(wide) == JunctionKind::DevicePath {
+        // In case of "\Device\HarddiskVolume1\foo", use the Win32 form
+        // "\\?\GLOBALROOT\Device\HarddiskVolume1\foo"
+        return (&GLOBALROOT_PREFIX, 0);
+    }
+    if wide.starts_with(&VOLUME_GUID_PREFIX) {
+        // In case of "\??\Volume{GUID}\", use the Win32 form "\\?\Volume{GUID}\"
+        return (&VERBATIM_PATH_PREFIX, NON_INTERPRETED_PATH_PREFIX.len());
+    }
+    // In case of "\??\C:\foo\bar"
+    (&[], wide.len() - strip_non_interpreted_prefix(wide).len())
+}
+
+pub fn get_target_into<'a>(junction: &Path, buf: &'a mut Vec<u16>, opts: &Options) -> io::Result<&'a [u16]> {
+    if !path_exists(junction)? {
         return Err(io::Error::new(io::ErrorKind::NotFound, "`junction` does not exist"));
     }
-    let file = helpers::open_reparse_point(junction, false)?;
+    // Reuse the capacity of `buf` as the reparse data buffer, so that neither
+    // allocating nor zeroing is needed once it has grown to the maximum size.
+    buf.clear();
+    buf.reserve(reparse::MAXIMUM_REPARSE_DATA_BUFFER_SIZE / 2);
+    let len = helpers::retry(opts, || {
+        let file = helpers::open_reparse_point(junction, false, opts)?;
+        // FSCTL_GET_REPARSE_POINT is buffered, so the output buffer needs no particular alignment.
+        helpers::get_reparse_data_point(
+            file.as_raw_handle() as isize,
+            buf.as_mut_ptr().cast(),
+            reparse::MAXIMUM_REPARSE_DATA_BUFFER_SIZE,
+        )
+    })?;
+    let len = len as usize;
+    // SAFETY: FSCTL_GET_REPARSE_POINT initialized the first `len` bytes of the
+    // buffer, and `len` does not exceed its capacity in bytes.
+    let bytes = unsafe {
+        buf.set_len(len / 2);
+        std::slice::from_raw_parts(buf.as_ptr().cast::<u8>(), len)
+    };
+    let (tag, _, data) = reparse::split_reparse_data(bytes)?;
+    if tag != c::IO_REPARSE_TAG_MOUNT_POINT {
+        return Err(io::Error::new(io::ErrorKind::Other, "not a reparse tag mount point"));
+    }
+    let range = reparse::name_range(data, reparse::MOUNT_POINT_REPARSE_BUFFER_HEADER_SIZE, 0)?;
+    let start = reparse::REPARSE_DATA_BUFFER_HEADER_SIZE + range.start;
+    if start % 2 != 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "misaligned substitute name"));
+    }
+    let name = start / 2..start / 2 + range.len() / 2;
+    let (prefix, skip) = win32_form(&buf[name.clone()]);
+    let len = prefix.len() + name.len() - skip;
+    buf.copy_within(name.start + skip..name.end, prefix.len());
+    buf[..prefix.len()].copy_from_slice(prefix);
+    buf.truncate(len);
+    Ok(buf)
+}
+
+pub fn get_kind(junction: &Path, opts: &Options) -> io::Result<JunctionKind> {
+    Ok(JunctionKind::of_substitute_name(&read_substitute_name(junction, opts)?))
+}
+
+pub fn kind(path: &Path, opts: &Options) -> io::Result<Option<ReparseKind>> {
+    let tag = match reparse_tag(path)? {
+        Some(tag) => tag,
+        None => return Ok(None),
+    };
+    Ok(Some(match tag {
+        c::IO_REPARSE_TAG_MOUNT_POINT => ReparseKind::Junction(get_kind(path, opts)?),
+        reparse::IO_REPARSE_TAG_SYMLINK => {
+            if fs::symlink_metadata(path)?.file_attributes() & c::FILE_ATTRIBUTE_DIRECTORY != 0 {
+                ReparseKind::DirSymlink
+            } else {
+                ReparseKind::FileSymlink
+            }
+        }
+        tag => ReparseKind::Other(tag),
+    }))
+}
+
+// Reads the raw substitute name of a mount point, including the `\??\` prefix.
+fn read_substitute_name(junction: &Path, opts: &Options) -> io::Result<Vec<u16>> {
+    if !path_exists(junction)? {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "`junction` does not exist"));
+    }
+    helpers::retry(opts, || {
+        let file = helpers::open_reparse_point(junction, false, opts)?;
+        substitute_name_of(&file)
+    })
+}
+
+// Whether `path` exists, without following it. Unlike `Path::exists`, this
+// holds for junctions whose target is missing, as is common for junctions in
+// Volume Shadow Copy snapshots, whose targets refer to the live volume.
+fn path_exists(path: &Path) -> io::Result<bool> {
+    match fs::symlink_metadata(path) {
+        Ok(_) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+fn substitute_name_of(file: &fs::File) -> io::Result<Vec<u16>> {
+    match parse_reparse_data(&read_reparse_file(file)?)? {
+        ReparseData::MountPoint(data) => Ok(data.substitute_name().to_vec()),
+        _ => Err(io::Error::new(io::ErrorKind::Other, "not a reparse tag mount point")),
+    }
+}
+
+pub fn get_app_exec_link(path: &Path, opts: &Options) -> io::Result<AppExecLink> {
+    // Version of the AppExecLink reparse data that we know how to parse.
+    const APP_EXEC_LINK_VERSION: u32 = 3;
+
+    let (tag, _, data) = read_reparse_raw(path, opts)?;
+    if tag != c::IO_REPARSE_TAG_APPEXECLINK {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            "not a reparse tag app execution link",
+        ));
+    }
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed app execution link reparse data");
+    // The data is a version number followed by NUL-terminated UTF-16 strings:
+    // the package id, the app user model id, the target executable path and
+    // the application type.
+    if reparse::read_u32(&data, 0).ok() != Some(APP_EXEC_LINK_VERSION) {
+        return Err(invalid());
+    }
+    let wide = reparse::to_wide(&data[4..]);
+    let mut strings = wide.split(|&c| c == 0).map(OsString::from_wide);
+    let package_id = strings.next().ok_or_else(invalid)?;
+    let entry_point = strings.next().ok_or_else(invalid)?;
+    let target = strings.next().map(PathBuf::from).ok_or_else(invalid)?;
+    Ok(AppExecLink {
+        package_id,
+        entry_point,
+        target,
+    })
+}
+
+pub fn get_lx_symlink(path: &Path, opts: &Options) -> io::Result<String> {
+    // Version of the LX symlink reparse data that we know how to parse.
+    const LX_SYMLINK_VERSION: u32 = 2;
+
+    let (tag, _, data) = read_reparse_raw(path, opts)?;
+    if tag != c::IO_REPARSE_TAG_LX_SYMLINK {
+        return Err(io::Error::new(io::ErrorKind::Other, "not a reparse tag LX symlink"));
+    }
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed LX symlink reparse data");
+    // The data is a version number followed by the UTF-8 target, without a NUL terminator.
+    if reparse::read_u32(&data, 0).ok() != Some(LX_SYMLINK_VERSION) {
+        return Err(invalid());
+    }
+    String::from_utf8(data[4..].to_vec()).map_err(|_| invalid())
+}
+
+pub fn read_reparse_raw(path: &Path, opts: &Options) -> io::Result<(u32, Option<Guid>, Vec<u8>)> {
+    let bytes = read_reparse_point(path, opts)?;
+    let (tag, guid, data) = reparse::split_reparse_data(&bytes)?;
+    Ok((tag, guid, data.to_vec()))
+}
+
+pub fn debug_dump(path: &Path, opts: &Options) -> io::Result<ReparseDump> {
+    read_reparse_point(path, opts).map(ReparseDump::new)
+}
+
+// Reads the whole reparse data buffer, including its header.
+fn read_reparse_point(path: &Path, opts: &Options) -> io::Result<Vec<u8>> {
+    helpers::retry(opts, || {
+        let file = helpers::open_reparse_point(path, false, opts)?;
+        read_reparse_file(&file)
+    })
+}
+
+// Reads the reparse data of `file`, failing with `TagMismatch` unless it is a
+// mount point. Modifying the same handle afterwards ensures that the reparse
+// point cannot be swapped for another kind in between.
+fn read_mount_point_file(file: &fs::File) -> io::Result<Vec<u8>> {
+    let bytes = read_reparse_file(file)?;
+    check_mount_point_tag(&bytes)?;
+    Ok(bytes)
+}
+
+// Fails with `TagMismatch` unless the reparse data `bytes` is the one of a mount point.
+fn check_mount_point_tag(bytes: &[u8]) -> io::Result<()> {
+    let (tag, _, _) = reparse::split_reparse_data(bytes)?;
+    if tag != c::IO_REPARSE_TAG_MOUNT_POINT {
+        return Err(io::Error::new(io::ErrorKind::Other, TagMismatch::new(tag)));
+    }
+    Ok(())
+}
+
+fn read_reparse_file(file: &fs::File) -> io::Result<Vec<u8>> {
+    // Try a buffer on the stack first, and only allocate one of the maximum
+    // size for reparse data that does not fit.
+    let mut small = SmallReparseDataBuffer::new();
+    match small.read(file.as_raw_handle() as isize) {
+        Ok(bytes) => return Ok(bytes.to_vec()),
+        Err(e) if e.raw_os_error() == Some(c::ERROR_MORE_DATA as i32) => {}
+        Err(e) => return Err(e),
+    }
     let mut data = BytesAsReparseDataBuffer::new();
-    helpers::get_reparse_data_point(file.as_raw_handle() as isize, data.as_mut_ptr())?;
-    // SAFETY: rdb should be initialized now
-    let rdb = unsafe { data.assume_init() };
-    if rdb.ReparseTag == c::IO_REPARSE_TAG_MOUNT_POINT {
-        let offset = rdb.ReparseBuffer.SubstituteNameOffset / WCHAR_SIZE;
-        let len = rdb.ReparseBuffer.SubstituteNameLength / WCHAR_SIZE;
-        let wide = unsafe {
-            let buf = rdb.ReparseBuffer.PathBuffer.as_ptr().add(offset as usize);
-            slice::from_raw_parts(buf, len as usize)
+    Ok(data.read(file.as_raw_handle() as isize)?.to_vec())
+}
+
+pub fn write_reparse_raw(path: &Path, tag: u32, guid: Option<Guid>, data: &[u8], opts: &Options) -> io::Result<()> {
+    let mut data = reparse::build_reparse_data(tag, guid, data)?;
+    let result = helpers::retry(opts, || {
+        let file = helpers::open_reparse_point(path, true, opts)?;
+        helpers::set_reparse_point(
+            file.as_raw_handle() as isize,
+            data.as_mut_ptr().cast(),
+            data.len() as u32,
+        )
+    });
+    if tag == c::IO_REPARSE_TAG_MOUNT_POINT {
+        etw_event!("write", path, data_target(&data).as_deref(), result.as_ref().map(drop));
+    }
+    result
+}
+
+#[cfg(feature = "usn")]
+pub fn usn_open(path: &Path) -> io::Result<(fs::File, u64, i64)> {
+    let volume = helpers::open_volume(path)?;
+    let (journal_id, next_usn) = helpers::query_usn_journal(&volume)?;
+    Ok((volume, journal_id, next_usn))
+}
+
+#[cfg(feature = "usn")]
+pub fn usn_read(
+    volume: &fs::File,
+    journal_id: u64,
+    start: i64,
+    reasons: u32,
+    wait: bool,
+    buffer: &mut [u8],
+) -> io::Result<usize> {
+    helpers::read_usn_journal(volume, journal_id, start, reasons, wait, buffer)
+}
+
+// Returns the reparse tag of the file `file_id`, or `None` if it cannot be read,
+// for example because the file was deleted since.
+#[cfg(feature = "usn")]
+pub fn usn_reparse_tag(volume: &fs::File, file_id: u64) -> Option<u32> {
+    let file = helpers::open_file_by_id(volume, file_id).ok()?;
+    let bytes = read_reparse_file(&file).ok()?;
+    Some(u32::from_le_bytes(bytes.get(..4)?.try_into().ok()?))
+}
+
+#[cfg(feature = "raw")]
+pub fn raw_open(path: &Path, write: bool, opts: &Options) -> io::Result<fs::File> {
+    helpers::open_reparse_point(path, write, opts).map(helpers::ReparsePoint::into_file)
+}
+
+#[cfg(feature = "raw")]
+pub fn raw_get(file: &fs::File) -> io::Result<Vec<u8>> {
+    read_reparse_file(file)
+}
+
+#[cfg(feature = "raw")]
+pub fn raw_set(file: &fs::File, data: &[u8]) -> io::Result<()> {
+    let (tag, guid, tag_data) = reparse::split_reparse_data(data)?;
+    // Rebuilding the buffer checks the GUID against the tag, and the length.
+    let mut data = reparse::build_reparse_data(tag, guid, tag_data)?;
+    let handle = file.as_raw_handle() as isize;
+    let result = helpers::set_reparse_point(handle, data.as_mut_ptr().cast(), data.len() as u32);
+    if tag == c::IO_REPARSE_TAG_MOUNT_POINT {
+        etw_event!(
+            "write",
+            &etw::handle_path(handle),
+            data_target(&data).as_deref(),
+            result.as_ref().map(drop)
+        );
+    }
+    result
+}
+
+#[cfg(feature = "raw")]
+pub fn raw_delete(file: &fs::File) -> io::Result<()> {
+    let bytes = read_reparse_file(file)?;
+    let (tag, guid, _) = reparse::split_reparse_data(&bytes)?;
+    let mut header = reparse::build_reparse_data(tag, guid, &[])?;
+    let handle = file.as_raw_handle() as isize;
+    let result = helpers::delete_reparse_point_with_header(handle, &mut header);
+    if tag == c::IO_REPARSE_TAG_MOUNT_POINT {
+        etw_event!("delete", &etw::handle_path(handle), None, result.as_ref().map(drop));
+    }
+    result
+}
+
+pub fn clear_privilege_cache() {
+    helpers::clear_privilege_cache();
+}
+
+pub fn mount_volume(volume: &Path, mount_point: &Path) -> io::Result<()> {
+    let volume = helpers::dir_path_to_utf16(volume);
+    let mount_point = helpers::dir_path_to_utf16(mount_point);
+    if unsafe { c::SetVolumeMountPointW(mount_point.as_ptr(), volume.as_ptr()) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+pub fn unmount_volume(mount_point: &Path) -> io::Result<()> {
+    let mount_point = helpers::dir_path_to_utf16(mount_point);
+    if unsafe { c::DeleteVolumeMountPointW(mount_point.as_ptr()) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+// Mount point names are relative to the volume root, which may already be
+// a long path.
+const VOLUME_MOUNT_POINT_BUFFER_LEN: usize = 32 * 1024;
+// A volume GUID path is 49 characters including the terminating null character.
+const VOLUME_NAME_BUFFER_LEN: usize = 64;
+
+pub struct VolumeMountPoints {
+    handle: c::HANDLE,
+    root: PathBuf,
+    buf: Vec<u16>,
+    // `FindFirstVolumeMountPointW` already fills `buf` with the first entry.
+    first: bool,
+}
+
+pub fn list_volume_mount_points(volume_root: &Path) -> io::Result<VolumeMountPoints> {
+    let root = helpers::dir_path_to_utf16(volume_root);
+    let mut buf = vec![0u16; VOLUME_MOUNT_POINT_BUFFER_LEN];
+    let handle = unsafe { c::FindFirstVolumeMountPointW(root.as_ptr(), buf.as_mut_ptr(), buf.len() as u32) };
+    if handle == c::INVALID_HANDLE_VALUE {
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() != Some(c::ERROR_NO_MORE_FILES as i32) {
+            return Err(err);
+        }
+    }
+    Ok(VolumeMountPoints {
+        handle,
+        root: volume_root.to_path_buf(),
+        buf,
+        first: true,
+    })
+}
+
+impl VolumeMountPoints {
+    fn close(&mut self) {
+        if self.handle != c::INVALID_HANDLE_VALUE {
+            unsafe { c::FindVolumeMountPointClose(self.handle) };
+            self.handle = c::INVALID_HANDLE_VALUE;
+        }
+    }
+}
+
+impl Iterator for VolumeMountPoints {
+    /// The full path of the mount point folder and the volume GUID path mounted there.
+    type Item = io::Result<(PathBuf, PathBuf)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.handle == c::INVALID_HANDLE_VALUE {
+            return None;
+        }
+        if !self.first
+            && unsafe { c::FindNextVolumeMountPointW(self.handle, self.buf.as_mut_ptr(), self.buf.len() as u32) } == 0
+        {
+            let err = io::Error::last_os_error();
+            self.close();
+            if err.raw_os_error() == Some(c::ERROR_NO_MORE_FILES as i32) {
+                return None;
+            }
+            return Some(Err(err));
+        }
+        self.first = false;
+
+        // Mount point names are relative to the root and end with a backslash.
+        let name = PathBuf::from(helpers::utf16_until_nul(&self.buf));
+        let path = self.root.join(name);
+        let mut volume = [0u16; VOLUME_NAME_BUFFER_LEN];
+        let mount_point = helpers::dir_path_to_utf16(&path);
+        if unsafe {
+            c::GetVolumeNameForVolumeMountPointW(mount_point.as_ptr(), volume.as_mut_ptr(), volume.len() as u32)
+        } == 0
+        {
+            return Some(Err(io::Error::last_os_error()));
+        }
+        Some(Ok((path, PathBuf::from(helpers::utf16_until_nul(&volume)))))
+    }
+}
+
+impl Drop for VolumeMountPoints {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+// A watch on the direct entries of a directory, with a `ReadDirectoryChangesW`
+// read always pending so that no notification is missed between reads.
+#[cfg(feature = "watch")]
+pub struct DirWatch {
+    dir: fs::File,
+    // Boxed, since the system writes to both while a read is pending.
+    state: Box<(c::OVERLAPPED, [u32; WATCH_BUFFER_LEN])>,
+    pending: bool,
+}
+
+// The size of the notification buffer in `u32`, for 64 KiB, the limit for network shares.
+#[cfg(feature = "watch")]
+const WATCH_BUFFER_LEN: usize = 64 * 1024 / 4;
+
+#[cfg(feature = "watch")]
+impl DirWatch {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let dir = helpers::open_dir_for_watch(path)?;
+        // SAFETY: `OVERLAPPED` is plain data, for which zeroes are valid.
+        let overlapped = unsafe { std::mem::zeroed() };
+        let mut watch = DirWatch {
+            dir,
+            state: Box::new((overlapped, [0; WATCH_BUFFER_LEN])),
+            pending: false,
         };
-        // In case of "\??\C:\foo\bar"
-        let wide = wide.strip_prefix(&NON_INTERPRETED_PATH_PREFIX).unwrap_or(wide);
-        Ok(PathBuf::from(OsString::from_wide(wide)))
-    } else {
-        Err(io::Error::new(io::ErrorKind::Other, "not a reparse tag mount point"))
+        watch.start()?;
+        Ok(watch)
+    }
+
+    fn start(&mut self) -> io::Result<()> {
+        let (overlapped, buffer) = &mut *self.state;
+        helpers::read_directory_changes(self.dir.as_raw_handle() as isize, buffer, overlapped)?;
+        self.pending = true;
+        Ok(())
+    }
+
+    // Waits for the pending read, starts the next one, and returns the
+    // notifications, which are empty if they overflowed the buffer.
+    pub fn read(&mut self) -> io::Result<Vec<u8>> {
+        if !self.pending {
+            self.start()?;
+        }
+        let handle = self.dir.as_raw_handle() as isize;
+        let (overlapped, buffer) = &mut *self.state;
+        self.pending = false;
+        let len = helpers::wait_overlapped(handle, overlapped)? as usize;
+        let bytes = buffer.iter().flat_map(|word| word.to_le_bytes()).take(len).collect();
+        self.start()?;
+        Ok(bytes)
+    }
+}
+
+#[cfg(feature = "watch")]
+impl Drop for DirWatch {
+    fn drop(&mut self) {
+        if self.pending {
+            let handle = self.dir.as_raw_handle() as isize;
+            let overlapped = &mut self.state.0;
+            helpers::cancel_overlapped(handle, overlapped);
+        }
+    }
+}
+
+#[cfg(feature = "watch")]
+impl std::fmt::Debug for DirWatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DirWatch").field("dir", &self.dir).finish()
     }
 }
@@ -5,28 +5,103 @@
 mod nightly;
 
 use std::alloc::Layout;
+use std::ffi::c_void;
 use std::os::raw::{c_ulong, c_ushort};
 use std::os::windows::io::RawHandle;
 
-pub use windows_sys::Win32::Foundation::{
-    CloseHandle, GetLastError, SetLastError, ERROR_INSUFFICIENT_BUFFER, FALSE, GENERIC_READ, GENERIC_WRITE, HANDLE,
-    INVALID_HANDLE_VALUE,
-};
-pub use windows_sys::Win32::Security::{
-    AdjustTokenPrivileges, LookupPrivilegeValueW, SE_PRIVILEGE_ENABLED, TOKEN_ADJUST_PRIVILEGES, TOKEN_PRIVILEGES,
-};
-// See more in <https://learn.microsoft.com/en-us/windows/win32/secauthz/privilege-constants>.
-pub use windows_sys::Win32::Security::{SE_BACKUP_NAME, SE_CREATE_SYMBOLIC_LINK_NAME, SE_RESTORE_NAME};
-pub use windows_sys::Win32::Storage::FileSystem::{
-    GetFullPathNameW, FILE_FLAG_BACKUP_SEMANTICS, FILE_FLAG_OPEN_REPARSE_POINT, MAXIMUM_REPARSE_DATA_BUFFER_SIZE,
-    REPARSE_GUID_DATA_BUFFER,
-};
-pub use windows_sys::Win32::System::Ioctl::{
-    FSCTL_DELETE_REPARSE_POINT, FSCTL_GET_REPARSE_POINT, FSCTL_SET_REPARSE_POINT,
-};
-pub use windows_sys::Win32::System::SystemServices::IO_REPARSE_TAG_MOUNT_POINT;
-pub use windows_sys::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
-pub use windows_sys::Win32::System::IO::DeviceIoControl;
+// The bindings of the selected backend, re-exported under the names and raw
+// signatures of `windows-sys`. `no-bindings` takes precedence over `windows`,
+// which takes precedence over the default `windows-sys`.
+#[cfg(not(any(feature = "windows", feature = "no-bindings")))]
+mod sys;
+#[cfg(not(any(feature = "windows", feature = "no-bindings")))]
+pub use self::sys::*;
+#[cfg(all(feature = "windows", not(feature = "no-bindings")))]
+mod windows_rs;
+#[cfg(all(feature = "windows", not(feature = "no-bindings")))]
+pub use self::windows_rs::*;
+#[cfg(feature = "no-bindings")]
+mod no_bindings;
+#[cfg(feature = "no-bindings")]
+pub use self::no_bindings::*;
+
+// ETW is bound through `windows-sys` whatever the backend, as `etw` enables it there.
+#[cfg(feature = "etw")]
+pub use windows_sys::core::GUID;
+#[cfg(feature = "etw")]
+pub use windows_sys::Win32::System::Diagnostics::Etw::{EventProviderEnabled, EventRegister, EventWriteString};
+
+/// `NtCreateFile` disposition opening an existing file only.
+pub const FILE_OPEN: u32 = 1;
+/// `NtCreateFile` option like `FILE_FLAG_OPEN_REPARSE_POINT`.
+pub const FILE_OPEN_REPARSE_POINT: u32 = 0x0020_0000;
+/// `NtCreateFile` option like `FILE_FLAG_BACKUP_SEMANTICS`.
+pub const FILE_OPEN_FOR_BACKUP_INTENT: u32 = 0x0000_4000;
+/// `NtCreateFile` option making the handle usable for synchronous I/O.
+pub const FILE_SYNCHRONOUS_IO_NONALERT: u32 = 0x0000_0020;
+/// `GetDriveTypeW` result for network shares and mapped network drives.
+pub const DRIVE_REMOTE: u32 = 4;
+pub const OBJ_CASE_INSENSITIVE: u32 = 0x0000_0040;
+pub const SYNCHRONIZE: u32 = 0x0010_0000;
+
+/// `GetFileInformationByHandleEx` class of `FILE_CASE_SENSITIVE_INFO`, missing from `windows-sys`.
+pub const FILE_CASE_SENSITIVE_INFO_CLASS: FILE_INFO_BY_HANDLE_CLASS = 23;
+/// Flag of `FILE_CASE_SENSITIVE_INFO` set on case-sensitive directories.
+pub const FILE_CS_FLAG_CASE_SENSITIVE_DIR: u32 = 0x0000_0001;
+/// Queries `FILE_FS_PERSISTENT_VOLUME_INFORMATION`.
+pub const FSCTL_QUERY_PERSISTENT_VOLUME_STATE: u32 = 0x0009_023C;
+/// Flag of `FILE_FS_PERSISTENT_VOLUME_INFORMATION` set on Dev Drives.
+pub const PERSISTENT_VOLUME_STATE_DEV_VOLUME: u32 = 0x0000_2000;
+
+/// In- and output of `FSCTL_QUERY_PERSISTENT_VOLUME_STATE`, not exposed by `windows-sys` without the `Wdk` feature.
+#[repr(C)]
+pub struct FILE_FS_PERSISTENT_VOLUME_INFORMATION {
+    pub VolumeFlags: u32,
+    pub FlagMask: u32,
+    pub Version: u32,
+    pub Reserved: u32,
+}
+
+/// Object attributes of `NtCreateFile`, not exposed by `windows-sys` without the `Wdk` feature.
+#[repr(C)]
+pub struct OBJECT_ATTRIBUTES {
+    pub Length: u32,
+    pub RootDirectory: HANDLE,
+    pub ObjectName: *const UNICODE_STRING,
+    pub Attributes: u32,
+    pub SecurityDescriptor: *const c_void,
+    pub SecurityQualityOfService: *const c_void,
+}
+
+// Not exposed by `windows-sys` without the `Wdk` feature, or not at all.
+#[link(name = "ntdll")]
+extern "system" {
+    pub fn NtCreateFile(
+        FileHandle: *mut HANDLE,
+        DesiredAccess: u32,
+        ObjectAttributes: *const OBJECT_ATTRIBUTES,
+        IoStatusBlock: *mut IO_STATUS_BLOCK,
+        AllocationSize: *const i64,
+        FileAttributes: u32,
+        ShareAccess: u32,
+        CreateDisposition: u32,
+        CreateOptions: u32,
+        EaBuffer: *const c_void,
+        EaLength: u32,
+    ) -> NTSTATUS;
+    pub fn RtlDosPathNameToNtPathName_U_WithStatus(
+        DosFileName: *const u16,
+        NtFileName: *mut UNICODE_STRING,
+        FilePart: *mut *mut u16,
+        Reserved: *mut c_void,
+    ) -> NTSTATUS;
+    pub fn RtlFreeUnicodeString(UnicodeString: *mut UNICODE_STRING);
+}
+
+// Not exposed by `windows-sys` without the `Wdk` feature.
+pub use crate::consts::IO_REPARSE_TAG_LX_SYMLINK;
+// As `i32` to compare with `io::Error::raw_os_error`, unlike the `windows-sys` error codes.
+pub use crate::consts::ERROR_NOT_A_REPARSE_POINT;
 
 // Makes sure layout of RawHandle and windows-sys's HANDLE are the same
 // for pointer casts between them.
@@ -39,19 +114,20 @@ const _: () = {
 };
 
 // NOTE: to use `size_of` operator, below structs should be packed.
-/// Reparse Data Buffer header size
-pub const REPARSE_DATA_BUFFER_HEADER_SIZE: u16 = 8;
 /// Reparse GUID Data Buffer header size
 pub const REPARSE_GUID_DATA_BUFFER_HEADER_SIZE: u16 = 24;
-/// MountPointReparseBuffer header size
-pub const MOUNT_POINT_REPARSE_BUFFER_HEADER_SIZE: u16 = 8;
 
+// The reparse data (de)serializer hardcodes the header sizes.
 #[cfg(feature = "nightly")]
 #[allow(clippy::assertions_on_constants)]
 const _: () = {
-    assert!(REPARSE_DATA_BUFFER_HEADER_SIZE == nightly::REPARSE_DATA_BUFFER_HEADER_SIZE);
+    use crate::reparse;
+    assert!(reparse::REPARSE_DATA_BUFFER_HEADER_SIZE == nightly::REPARSE_DATA_BUFFER_HEADER_SIZE as usize);
     assert!(REPARSE_GUID_DATA_BUFFER_HEADER_SIZE == nightly::REPARSE_GUID_DATA_BUFFER_HEADER_SIZE);
-    assert!(MOUNT_POINT_REPARSE_BUFFER_HEADER_SIZE == nightly::MOUNT_POINT_REPARSE_BUFFER_HEADER_SIZE);
+    assert!(reparse::REPARSE_GUID_DATA_BUFFER_HEADER_SIZE == nightly::REPARSE_GUID_DATA_BUFFER_HEADER_SIZE as usize);
+    assert!(
+        reparse::MOUNT_POINT_REPARSE_BUFFER_HEADER_SIZE == nightly::MOUNT_POINT_REPARSE_BUFFER_HEADER_SIZE as usize
+    );
 };
 
 type VarLenArr<T> = [T; 1];
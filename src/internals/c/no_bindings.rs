@@ -0,0 +1,503 @@
+//! Win32 declarations written out by hand, selected by the `no-bindings` feature.
+//!
+//! Only the functions, structs and constants used by this crate are declared,
+//! with the names and types of `windows-sys` so that the rest of the crate
+//! does not depend on which backend is selected.
+#![allow(non_camel_case_types, non_upper_case_globals)]
+
+use std::ffi::c_void;
+
+pub type BOOL = i32;
+pub type HANDLE = isize;
+pub type NTSTATUS = i32;
+pub type PSECURITY_DESCRIPTOR = *mut c_void;
+pub type FILE_INFO_BY_HANDLE_CLASS = i32;
+#[cfg(feature = "watch")]
+pub type LPOVERLAPPED_COMPLETION_ROUTINE =
+    Option<unsafe extern "system" fn(dwerrorcode: u32, dwnumberofbytestransfered: u32, lpoverlapped: *mut OVERLAPPED)>;
+
+pub const FALSE: BOOL = 0;
+pub const TRUE: BOOL = 1;
+pub const INVALID_HANDLE_VALUE: HANDLE = -1;
+pub const MAX_PATH: u32 = 260;
+
+pub const ERROR_NOT_SAME_DEVICE: u32 = 17;
+pub const ERROR_NO_MORE_FILES: u32 = 18;
+pub const ERROR_INSUFFICIENT_BUFFER: u32 = 122;
+pub const ERROR_MORE_DATA: u32 = 234;
+
+pub const GENERIC_READ: u32 = 0x8000_0000;
+pub const GENERIC_WRITE: u32 = 0x4000_0000;
+pub const READ_CONTROL: u32 = 0x0002_0000;
+pub const WRITE_DAC: u32 = 0x0004_0000;
+pub const WRITE_OWNER: u32 = 0x0008_0000;
+pub const FILE_READ_ATTRIBUTES: u32 = 0x0080;
+pub const FILE_WRITE_ATTRIBUTES: u32 = 0x0100;
+#[cfg(feature = "watch")]
+pub const FILE_LIST_DIRECTORY: u32 = 0x0001;
+
+pub const FILE_SHARE_READ: u32 = 0x0001;
+pub const FILE_SHARE_WRITE: u32 = 0x0002;
+pub const FILE_SHARE_DELETE: u32 = 0x0004;
+
+pub const FILE_ATTRIBUTE_HIDDEN: u32 = 0x0002;
+pub const FILE_ATTRIBUTE_SYSTEM: u32 = 0x0004;
+pub const FILE_ATTRIBUTE_DIRECTORY: u32 = 0x0010;
+pub const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x0400;
+pub const FILE_ATTRIBUTE_NOT_CONTENT_INDEXED: u32 = 0x2000;
+pub const FILE_FLAG_OPEN_REPARSE_POINT: u32 = 0x0020_0000;
+pub const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+#[cfg(feature = "watch")]
+pub const FILE_FLAG_OVERLAPPED: u32 = 0x4000_0000;
+
+#[cfg(feature = "watch")]
+pub const FILE_NOTIFY_CHANGE_FILE_NAME: u32 = 0x0001;
+#[cfg(feature = "watch")]
+pub const FILE_NOTIFY_CHANGE_DIR_NAME: u32 = 0x0002;
+#[cfg(feature = "watch")]
+pub const FILE_NOTIFY_CHANGE_ATTRIBUTES: u32 = 0x0004;
+#[cfg(feature = "watch")]
+pub const FILE_NOTIFY_CHANGE_LAST_WRITE: u32 = 0x0010;
+
+pub const FileBasicInfo: FILE_INFO_BY_HANDLE_CLASS = 0;
+pub const FileIdInfo: FILE_INFO_BY_HANDLE_CLASS = 18;
+pub const FindExInfoBasic: i32 = 1;
+pub const FindExSearchNameMatch: i32 = 0;
+pub const FIND_FIRST_EX_LARGE_FETCH: u32 = 0x0002;
+#[cfg(feature = "usn")]
+pub const FileIdType: i32 = 0;
+#[cfg(feature = "backup")]
+pub const BACKUP_REPARSE_DATA: u32 = 8;
+
+pub const CSTR_EQUAL: i32 = 2;
+pub const SDDL_REVISION_1: u32 = 1;
+pub const TokenPrivileges: i32 = 3;
+pub const OWNER_SECURITY_INFORMATION: u32 = 0x0001;
+pub const DACL_SECURITY_INFORMATION: u32 = 0x0004;
+pub const SE_PRIVILEGE_ENABLED: u32 = 0x0002;
+pub const TOKEN_QUERY: u32 = 0x0008;
+pub const TOKEN_ADJUST_PRIVILEGES: u32 = 0x0020;
+
+pub const FSCTL_SET_REPARSE_POINT: u32 = 0x0009_00A4;
+pub const FSCTL_GET_REPARSE_POINT: u32 = 0x0009_00A8;
+pub const FSCTL_DELETE_REPARSE_POINT: u32 = 0x0009_00AC;
+#[cfg(feature = "usn")]
+pub const FSCTL_READ_USN_JOURNAL: u32 = 0x0009_00BB;
+#[cfg(feature = "usn")]
+pub const FSCTL_QUERY_USN_JOURNAL: u32 = 0x0009_00F4;
+pub const MAXIMUM_REPARSE_DATA_BUFFER_SIZE: u32 = 16 * 1024;
+pub const FILE_SUPPORTS_REPARSE_POINTS: u32 = 0x0080;
+pub const IO_REPARSE_TAG_MOUNT_POINT: u32 = 0xA000_0003;
+pub const IO_REPARSE_TAG_APPEXECLINK: u32 = 0x8000_001B;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct GUID {
+    pub data1: u32,
+    pub data2: u16,
+    pub data3: u16,
+    pub data4: [u8; 8],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct FILETIME {
+    pub dwLowDateTime: u32,
+    pub dwHighDateTime: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct LUID {
+    pub LowPart: u32,
+    pub HighPart: i32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct LUID_AND_ATTRIBUTES {
+    pub Luid: LUID,
+    pub Attributes: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct TOKEN_PRIVILEGES {
+    pub PrivilegeCount: u32,
+    pub Privileges: [LUID_AND_ATTRIBUTES; 1],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct SECURITY_ATTRIBUTES {
+    pub nLength: u32,
+    pub lpSecurityDescriptor: *mut c_void,
+    pub bInheritHandle: BOOL,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct UNICODE_STRING {
+    pub Length: u16,
+    pub MaximumLength: u16,
+    pub Buffer: *mut u16,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct IO_STATUS_BLOCK {
+    pub Anonymous: IO_STATUS_BLOCK_0,
+    pub Information: usize,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub union IO_STATUS_BLOCK_0 {
+    pub Status: NTSTATUS,
+    pub Pointer: *mut c_void,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct OVERLAPPED {
+    pub Internal: usize,
+    pub InternalHigh: usize,
+    pub Anonymous: OVERLAPPED_0,
+    pub hEvent: HANDLE,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub union OVERLAPPED_0 {
+    pub Anonymous: OVERLAPPED_0_0,
+    pub Pointer: *mut c_void,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct OVERLAPPED_0_0 {
+    pub Offset: u32,
+    pub OffsetHigh: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct BY_HANDLE_FILE_INFORMATION {
+    pub dwFileAttributes: u32,
+    pub ftCreationTime: FILETIME,
+    pub ftLastAccessTime: FILETIME,
+    pub ftLastWriteTime: FILETIME,
+    pub dwVolumeSerialNumber: u32,
+    pub nFileSizeHigh: u32,
+    pub nFileSizeLow: u32,
+    pub nNumberOfLinks: u32,
+    pub nFileIndexHigh: u32,
+    pub nFileIndexLow: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct FILE_BASIC_INFO {
+    pub CreationTime: i64,
+    pub LastAccessTime: i64,
+    pub LastWriteTime: i64,
+    pub ChangeTime: i64,
+    pub FileAttributes: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct FILE_ID_128 {
+    pub Identifier: [u8; 16],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct FILE_ID_INFO {
+    pub VolumeSerialNumber: u64,
+    pub FileId: FILE_ID_128,
+}
+
+#[cfg(feature = "usn")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct FILE_ID_DESCRIPTOR {
+    pub dwSize: u32,
+    pub Type: i32,
+    pub Anonymous: FILE_ID_DESCRIPTOR_0,
+}
+
+#[cfg(feature = "usn")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub union FILE_ID_DESCRIPTOR_0 {
+    pub FileId: i64,
+    pub ObjectId: GUID,
+    pub ExtendedFileId: FILE_ID_128,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct WIN32_FIND_DATAW {
+    pub dwFileAttributes: u32,
+    pub ftCreationTime: FILETIME,
+    pub ftLastAccessTime: FILETIME,
+    pub ftLastWriteTime: FILETIME,
+    pub nFileSizeHigh: u32,
+    pub nFileSizeLow: u32,
+    pub dwReserved0: u32,
+    pub dwReserved1: u32,
+    pub cFileName: [u16; MAX_PATH as usize],
+    pub cAlternateFileName: [u16; 14],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct REPARSE_GUID_DATA_BUFFER {
+    pub ReparseTag: u32,
+    pub ReparseDataLength: u16,
+    pub Reserved: u16,
+    pub ReparseGuid: GUID,
+    pub GenericReparseBuffer: REPARSE_GUID_DATA_BUFFER_0,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct REPARSE_GUID_DATA_BUFFER_0 {
+    pub DataBuffer: [u8; 1],
+}
+
+#[cfg(feature = "usn")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct USN_JOURNAL_DATA_V0 {
+    pub UsnJournalID: u64,
+    pub FirstUsn: i64,
+    pub NextUsn: i64,
+    pub LowestValidUsn: i64,
+    pub MaxUsn: i64,
+    pub MaximumSize: u64,
+    pub AllocationDelta: u64,
+}
+
+#[cfg(feature = "usn")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct READ_USN_JOURNAL_DATA_V0 {
+    pub StartUsn: i64,
+    pub ReasonMask: u32,
+    pub ReturnOnlyOnClose: u32,
+    pub Timeout: u64,
+    pub BytesToWaitFor: u64,
+    pub UsnJournalID: u64,
+}
+
+#[link(name = "kernel32")]
+extern "system" {
+    pub fn GetLastError() -> u32;
+    pub fn SetLastError(dwerrcode: u32);
+    pub fn CloseHandle(hobject: HANDLE) -> BOOL;
+    pub fn LocalFree(hmem: *mut c_void) -> *mut c_void;
+    pub fn GetCurrentProcess() -> HANDLE;
+    pub fn CompareStringOrdinal(
+        lpstring1: *const u16,
+        cchcount1: i32,
+        lpstring2: *const u16,
+        cchcount2: i32,
+        bignorecase: BOOL,
+    ) -> i32;
+    pub fn DeviceIoControl(
+        hdevice: HANDLE,
+        dwiocontrolcode: u32,
+        lpinbuffer: *const c_void,
+        ninbuffersize: u32,
+        lpoutbuffer: *mut c_void,
+        noutbuffersize: u32,
+        lpbytesreturned: *mut u32,
+        lpoverlapped: *mut OVERLAPPED,
+    ) -> BOOL;
+    pub fn CreateDirectoryW(lppathname: *const u16, lpsecurityattributes: *const SECURITY_ATTRIBUTES) -> BOOL;
+    pub fn GetFullPathNameW(
+        lpfilename: *const u16,
+        nbufferlength: u32,
+        lpbuffer: *mut u16,
+        lpfilepart: *mut *mut u16,
+    ) -> u32;
+    pub fn GetFileInformationByHandle(hfile: HANDLE, lpfileinformation: *mut BY_HANDLE_FILE_INFORMATION) -> BOOL;
+    pub fn GetFinalPathNameByHandleW(hfile: HANDLE, lpszfilepath: *mut u16, cchfilepath: u32, dwflags: u32) -> u32;
+    pub fn GetFileInformationByHandleEx(
+        hfile: HANDLE,
+        fileinformationclass: FILE_INFO_BY_HANDLE_CLASS,
+        lpfileinformation: *mut c_void,
+        dwbuffersize: u32,
+    ) -> BOOL;
+    pub fn SetFileInformationByHandle(
+        hfile: HANDLE,
+        fileinformationclass: FILE_INFO_BY_HANDLE_CLASS,
+        lpfileinformation: *const c_void,
+        dwbuffersize: u32,
+    ) -> BOOL;
+    pub fn FindFirstFileExW(
+        lpfilename: *const u16,
+        finfolevelid: i32,
+        lpfindfiledata: *mut c_void,
+        fsearchop: i32,
+        lpsearchfilter: *const c_void,
+        dwadditionalflags: u32,
+    ) -> HANDLE;
+    pub fn FindNextFileW(hfindfile: HANDLE, lpfindfiledata: *mut WIN32_FIND_DATAW) -> BOOL;
+    pub fn FindClose(hfindfile: HANDLE) -> BOOL;
+    pub fn FindFirstVolumeMountPointW(
+        lpszrootpathname: *const u16,
+        lpszvolumemountpoint: *mut u16,
+        cchbufferlength: u32,
+    ) -> HANDLE;
+    pub fn FindNextVolumeMountPointW(
+        hfindvolumemountpoint: HANDLE,
+        lpszvolumemountpoint: *mut u16,
+        cchbufferlength: u32,
+    ) -> BOOL;
+    pub fn FindVolumeMountPointClose(hfindvolumemountpoint: HANDLE) -> BOOL;
+    pub fn SetVolumeMountPointW(lpszvolumemountpoint: *const u16, lpszvolumename: *const u16) -> BOOL;
+    pub fn DeleteVolumeMountPointW(lpszvolumemountpoint: *const u16) -> BOOL;
+    pub fn GetVolumeNameForVolumeMountPointW(
+        lpszvolumemountpoint: *const u16,
+        lpszvolumename: *mut u16,
+        cchbufferlength: u32,
+    ) -> BOOL;
+    pub fn GetVolumePathNameW(lpszfilename: *const u16, lpszvolumepathname: *mut u16, cchbufferlength: u32) -> BOOL;
+    pub fn GetVolumeInformationW(
+        lprootpathname: *const u16,
+        lpvolumenamebuffer: *mut u16,
+        nvolumenamesize: u32,
+        lpvolumeserialnumber: *mut u32,
+        lpmaximumcomponentlength: *mut u32,
+        lpfilesystemflags: *mut u32,
+        lpfilesystemnamebuffer: *mut u16,
+        nfilesystemnamesize: u32,
+    ) -> BOOL;
+    pub fn GetDiskFreeSpaceW(
+        lprootpathname: *const u16,
+        lpsectorspercluster: *mut u32,
+        lpbytespersector: *mut u32,
+        lpnumberoffreeclusters: *mut u32,
+        lptotalnumberofclusters: *mut u32,
+    ) -> BOOL;
+    pub fn GetDriveTypeW(lprootpathname: *const u16) -> u32;
+}
+
+#[cfg(feature = "backup")]
+#[link(name = "kernel32")]
+extern "system" {
+    pub fn BackupRead(
+        hfile: HANDLE,
+        lpbuffer: *mut u8,
+        nnumberofbytestoread: u32,
+        lpnumberofbytesread: *mut u32,
+        babort: BOOL,
+        bprocesssecurity: BOOL,
+        lpcontext: *mut *mut c_void,
+    ) -> BOOL;
+    pub fn BackupSeek(
+        hfile: HANDLE,
+        dwlowbytestoseek: u32,
+        dwhighbytestoseek: u32,
+        lpdwlowbyteseeked: *mut u32,
+        lpdwhighbyteseeked: *mut u32,
+        lpcontext: *mut *mut c_void,
+    ) -> BOOL;
+    pub fn BackupWrite(
+        hfile: HANDLE,
+        lpbuffer: *const u8,
+        nnumberofbytestowrite: u32,
+        lpnumberofbyteswritten: *mut u32,
+        babort: BOOL,
+        bprocesssecurity: BOOL,
+        lpcontext: *mut *mut c_void,
+    ) -> BOOL;
+}
+
+#[cfg(feature = "usn")]
+#[link(name = "kernel32")]
+extern "system" {
+    pub fn OpenFileById(
+        hvolumehint: HANDLE,
+        lpfileid: *const FILE_ID_DESCRIPTOR,
+        dwdesiredaccess: u32,
+        dwsharemode: u32,
+        lpsecurityattributes: *const SECURITY_ATTRIBUTES,
+        dwflagsandattributes: u32,
+    ) -> HANDLE;
+}
+
+#[cfg(feature = "watch")]
+#[link(name = "kernel32")]
+extern "system" {
+    pub fn ReadDirectoryChangesW(
+        hdirectory: HANDLE,
+        lpbuffer: *mut c_void,
+        nbufferlength: u32,
+        bwatchsubtree: BOOL,
+        dwnotifyfilter: u32,
+        lpbytesreturned: *mut u32,
+        lpoverlapped: *mut OVERLAPPED,
+        lpcompletionroutine: LPOVERLAPPED_COMPLETION_ROUTINE,
+    ) -> BOOL;
+    pub fn GetOverlappedResult(
+        hfile: HANDLE,
+        lpoverlapped: *const OVERLAPPED,
+        lpnumberofbytestransferred: *mut u32,
+        bwait: BOOL,
+    ) -> BOOL;
+    pub fn CancelIoEx(hfile: HANDLE, lpoverlapped: *const OVERLAPPED) -> BOOL;
+}
+
+#[link(name = "advapi32")]
+extern "system" {
+    pub fn OpenProcessToken(processhandle: HANDLE, desiredaccess: u32, tokenhandle: *mut HANDLE) -> BOOL;
+    pub fn GetTokenInformation(
+        tokenhandle: HANDLE,
+        tokeninformationclass: i32,
+        tokeninformation: *mut c_void,
+        tokeninformationlength: u32,
+        returnlength: *mut u32,
+    ) -> BOOL;
+    pub fn AdjustTokenPrivileges(
+        tokenhandle: HANDLE,
+        disableallprivileges: BOOL,
+        newstate: *const TOKEN_PRIVILEGES,
+        bufferlength: u32,
+        previousstate: *mut TOKEN_PRIVILEGES,
+        returnlength: *mut u32,
+    ) -> BOOL;
+    pub fn LookupPrivilegeNameW(
+        lpsystemname: *const u16,
+        lpluid: *const LUID,
+        lpname: *mut u16,
+        cchname: *mut u32,
+    ) -> BOOL;
+    pub fn LookupPrivilegeValueW(lpsystemname: *const u16, lpname: *const u16, lpluid: *mut LUID) -> BOOL;
+    pub fn GetKernelObjectSecurity(
+        handle: HANDLE,
+        requestedinformation: u32,
+        psecuritydescriptor: PSECURITY_DESCRIPTOR,
+        nlength: u32,
+        lpnlengthneeded: *mut u32,
+    ) -> BOOL;
+    pub fn SetKernelObjectSecurity(
+        handle: HANDLE,
+        securityinformation: u32,
+        securitydescriptor: PSECURITY_DESCRIPTOR,
+    ) -> BOOL;
+    pub fn ConvertStringSecurityDescriptorToSecurityDescriptorW(
+        stringsecuritydescriptor: *const u16,
+        stringsdrevision: u32,
+        securitydescriptor: *mut PSECURITY_DESCRIPTOR,
+        securitydescriptorsize: *mut u32,
+    ) -> BOOL;
+}
+
+#[link(name = "ntdll")]
+extern "system" {
+    pub fn RtlNtStatusToDosError(status: NTSTATUS) -> u32;
+}
@@ -0,0 +1,52 @@
+//! Bindings from `windows-sys`, the default backend.
+
+pub use windows_sys::Win32::Foundation::{
+    CloseHandle, GetLastError, LocalFree, RtlNtStatusToDosError, SetLastError, ERROR_INSUFFICIENT_BUFFER,
+    ERROR_MORE_DATA, ERROR_NOT_SAME_DEVICE, ERROR_NO_MORE_FILES, FALSE, GENERIC_READ, GENERIC_WRITE, HANDLE,
+    INVALID_HANDLE_VALUE, LUID, MAX_PATH, NTSTATUS, TRUE, UNICODE_STRING,
+};
+pub use windows_sys::Win32::Globalization::{CompareStringOrdinal, CSTR_EQUAL};
+pub use windows_sys::Win32::Security::Authorization::{
+    ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1,
+};
+pub use windows_sys::Win32::Security::{
+    AdjustTokenPrivileges, GetKernelObjectSecurity, GetTokenInformation, LookupPrivilegeNameW, LookupPrivilegeValueW,
+    SetKernelObjectSecurity, TokenPrivileges, DACL_SECURITY_INFORMATION, LUID_AND_ATTRIBUTES,
+    OWNER_SECURITY_INFORMATION, PSECURITY_DESCRIPTOR, SECURITY_ATTRIBUTES, SE_PRIVILEGE_ENABLED,
+    TOKEN_ADJUST_PRIVILEGES, TOKEN_PRIVILEGES, TOKEN_QUERY,
+};
+#[cfg(feature = "backup")]
+pub use windows_sys::Win32::Storage::FileSystem::{BackupRead, BackupSeek, BackupWrite, BACKUP_REPARSE_DATA};
+pub use windows_sys::Win32::Storage::FileSystem::{
+    CreateDirectoryW, DeleteVolumeMountPointW, FileBasicInfo, FileIdInfo, FindClose, FindExInfoBasic,
+    FindExSearchNameMatch, FindFirstFileExW, FindFirstVolumeMountPointW, FindNextFileW, FindNextVolumeMountPointW,
+    FindVolumeMountPointClose, GetDiskFreeSpaceW, GetDriveTypeW, GetFileInformationByHandle,
+    GetFileInformationByHandleEx, GetFinalPathNameByHandleW, GetFullPathNameW, GetVolumeInformationW,
+    GetVolumeNameForVolumeMountPointW, GetVolumePathNameW, SetFileInformationByHandle, SetVolumeMountPointW,
+    BY_HANDLE_FILE_INFORMATION, FILE_ATTRIBUTE_DIRECTORY, FILE_ATTRIBUTE_HIDDEN, FILE_ATTRIBUTE_NOT_CONTENT_INDEXED,
+    FILE_ATTRIBUTE_REPARSE_POINT, FILE_ATTRIBUTE_SYSTEM, FILE_BASIC_INFO, FILE_FLAG_BACKUP_SEMANTICS,
+    FILE_FLAG_OPEN_REPARSE_POINT, FILE_ID_INFO, FILE_INFO_BY_HANDLE_CLASS, FILE_READ_ATTRIBUTES, FILE_SHARE_DELETE,
+    FILE_SHARE_READ, FILE_SHARE_WRITE, FILE_WRITE_ATTRIBUTES, FIND_FIRST_EX_LARGE_FETCH,
+    MAXIMUM_REPARSE_DATA_BUFFER_SIZE, READ_CONTROL, REPARSE_GUID_DATA_BUFFER, WIN32_FIND_DATAW, WRITE_DAC, WRITE_OWNER,
+};
+#[cfg(feature = "usn")]
+pub use windows_sys::Win32::Storage::FileSystem::{FileIdType, OpenFileById, FILE_ID_DESCRIPTOR, FILE_ID_DESCRIPTOR_0};
+#[cfg(feature = "watch")]
+pub use windows_sys::Win32::Storage::FileSystem::{
+    ReadDirectoryChangesW, FILE_FLAG_OVERLAPPED, FILE_LIST_DIRECTORY, FILE_NOTIFY_CHANGE_ATTRIBUTES,
+    FILE_NOTIFY_CHANGE_DIR_NAME, FILE_NOTIFY_CHANGE_FILE_NAME, FILE_NOTIFY_CHANGE_LAST_WRITE,
+};
+pub use windows_sys::Win32::System::Ioctl::{
+    FSCTL_DELETE_REPARSE_POINT, FSCTL_GET_REPARSE_POINT, FSCTL_SET_REPARSE_POINT,
+};
+#[cfg(feature = "usn")]
+pub use windows_sys::Win32::System::Ioctl::{
+    FSCTL_QUERY_USN_JOURNAL, FSCTL_READ_USN_JOURNAL, READ_USN_JOURNAL_DATA_V0, USN_JOURNAL_DATA_V0,
+};
+pub use windows_sys::Win32::System::SystemServices::{
+    FILE_SUPPORTS_REPARSE_POINTS, IO_REPARSE_TAG_APPEXECLINK, IO_REPARSE_TAG_MOUNT_POINT,
+};
+pub use windows_sys::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+#[cfg(feature = "watch")]
+pub use windows_sys::Win32::System::IO::{CancelIoEx, GetOverlappedResult, OVERLAPPED};
+pub use windows_sys::Win32::System::IO::{DeviceIoControl, IO_STATUS_BLOCK};
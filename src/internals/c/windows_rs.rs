@@ -0,0 +1,689 @@
+//! Bindings from the `windows` crate, selected by the `windows` feature.
+//!
+//! The rest of the crate is written against the raw signatures of `windows-sys`:
+//! handles are `isize`, flags are plain integers and failures are reported as
+//! `FALSE` or `INVALID_HANDLE_VALUE` with the cause in `GetLastError`. The
+//! wrappers below restore those conventions on top of the typed `windows` API.
+#![allow(non_camel_case_types, non_upper_case_globals, clippy::too_many_arguments)]
+
+use std::ffi::c_void;
+use std::ptr::null_mut;
+use std::{io, slice};
+
+use windows::core::{PCWSTR, PWSTR};
+use windows::Win32::Foundation as foundation;
+use windows::Win32::Globalization as globalization;
+use windows::Win32::Security as security;
+use windows::Win32::Security::Authorization as authorization;
+use windows::Win32::Storage::FileSystem as fs;
+use windows::Win32::System::Threading as threading;
+use windows::Win32::System::IO as sysio;
+
+pub use windows::Win32::Foundation::{LUID, MAX_PATH};
+pub use windows::Win32::Security::Authorization::SDDL_REVISION_1;
+pub use windows::Win32::Storage::FileSystem::{
+    BY_HANDLE_FILE_INFORMATION, FILE_BASIC_INFO, FILE_ID_INFO, MAXIMUM_REPARSE_DATA_BUFFER_SIZE,
+    REPARSE_GUID_DATA_BUFFER, WIN32_FIND_DATAW,
+};
+pub use windows::Win32::System::Ioctl::{FSCTL_DELETE_REPARSE_POINT, FSCTL_GET_REPARSE_POINT, FSCTL_SET_REPARSE_POINT};
+#[cfg(feature = "usn")]
+pub use windows::Win32::System::Ioctl::{
+    FSCTL_QUERY_USN_JOURNAL, FSCTL_READ_USN_JOURNAL, READ_USN_JOURNAL_DATA_V0, USN_JOURNAL_DATA_V0,
+};
+pub use windows::Win32::System::SystemServices::{
+    FILE_SUPPORTS_REPARSE_POINTS, IO_REPARSE_TAG_APPEXECLINK, IO_REPARSE_TAG_MOUNT_POINT,
+};
+pub use windows::Win32::System::IO::{IO_STATUS_BLOCK, OVERLAPPED};
+
+pub type BOOL = i32;
+pub type HANDLE = isize;
+pub type NTSTATUS = i32;
+pub type PSECURITY_DESCRIPTOR = *mut c_void;
+pub type FILE_INFO_BY_HANDLE_CLASS = i32;
+
+pub const FALSE: BOOL = foundation::FALSE.0;
+pub const TRUE: BOOL = foundation::TRUE.0;
+pub const INVALID_HANDLE_VALUE: HANDLE = foundation::INVALID_HANDLE_VALUE.0;
+pub const ERROR_INSUFFICIENT_BUFFER: u32 = foundation::ERROR_INSUFFICIENT_BUFFER.0;
+pub const ERROR_MORE_DATA: u32 = foundation::ERROR_MORE_DATA.0;
+pub const ERROR_NOT_SAME_DEVICE: u32 = foundation::ERROR_NOT_SAME_DEVICE.0;
+pub const ERROR_NO_MORE_FILES: u32 = foundation::ERROR_NO_MORE_FILES.0;
+pub const GENERIC_READ: u32 = foundation::GENERIC_READ.0;
+pub const GENERIC_WRITE: u32 = foundation::GENERIC_WRITE.0;
+pub const CSTR_EQUAL: i32 = globalization::CSTR_EQUAL.0;
+pub const TokenPrivileges: i32 = security::TokenPrivileges.0;
+pub const DACL_SECURITY_INFORMATION: u32 = security::DACL_SECURITY_INFORMATION.0;
+pub const OWNER_SECURITY_INFORMATION: u32 = security::OWNER_SECURITY_INFORMATION.0;
+pub const SE_PRIVILEGE_ENABLED: u32 = security::SE_PRIVILEGE_ENABLED.0;
+pub const TOKEN_ADJUST_PRIVILEGES: u32 = security::TOKEN_ADJUST_PRIVILEGES.0;
+pub const TOKEN_QUERY: u32 = security::TOKEN_QUERY.0;
+#[cfg(feature = "backup")]
+pub const BACKUP_REPARSE_DATA: u32 = fs::BACKUP_REPARSE_DATA.0;
+pub const FileBasicInfo: FILE_INFO_BY_HANDLE_CLASS = fs::FileBasicInfo.0;
+pub const FileIdInfo: FILE_INFO_BY_HANDLE_CLASS = fs::FileIdInfo.0;
+pub const FindExInfoBasic: i32 = fs::FindExInfoBasic.0;
+pub const FindExSearchNameMatch: i32 = fs::FindExSearchNameMatch.0;
+pub const FILE_ATTRIBUTE_DIRECTORY: u32 = fs::FILE_ATTRIBUTE_DIRECTORY.0;
+pub const FILE_ATTRIBUTE_HIDDEN: u32 = fs::FILE_ATTRIBUTE_HIDDEN.0;
+pub const FILE_ATTRIBUTE_NOT_CONTENT_INDEXED: u32 = fs::FILE_ATTRIBUTE_NOT_CONTENT_INDEXED.0;
+pub const FILE_ATTRIBUTE_REPARSE_POINT: u32 = fs::FILE_ATTRIBUTE_REPARSE_POINT.0;
+pub const FILE_ATTRIBUTE_SYSTEM: u32 = fs::FILE_ATTRIBUTE_SYSTEM.0;
+pub const FILE_FLAG_BACKUP_SEMANTICS: u32 = fs::FILE_FLAG_BACKUP_SEMANTICS.0;
+pub const FILE_FLAG_OPEN_REPARSE_POINT: u32 = fs::FILE_FLAG_OPEN_REPARSE_POINT.0;
+pub const FILE_READ_ATTRIBUTES: u32 = fs::FILE_READ_ATTRIBUTES.0;
+pub const FILE_WRITE_ATTRIBUTES: u32 = fs::FILE_WRITE_ATTRIBUTES.0;
+pub const FILE_SHARE_DELETE: u32 = fs::FILE_SHARE_DELETE.0;
+pub const FILE_SHARE_READ: u32 = fs::FILE_SHARE_READ.0;
+pub const FILE_SHARE_WRITE: u32 = fs::FILE_SHARE_WRITE.0;
+pub const FIND_FIRST_EX_LARGE_FETCH: u32 = fs::FIND_FIRST_EX_LARGE_FETCH.0;
+pub const READ_CONTROL: u32 = fs::READ_CONTROL.0;
+pub const WRITE_DAC: u32 = fs::WRITE_DAC.0;
+pub const WRITE_OWNER: u32 = fs::WRITE_OWNER.0;
+#[cfg(feature = "usn")]
+pub const FileIdType: i32 = fs::FileIdType.0;
+#[cfg(feature = "watch")]
+pub const FILE_FLAG_OVERLAPPED: u32 = fs::FILE_FLAG_OVERLAPPED.0;
+#[cfg(feature = "watch")]
+pub const FILE_LIST_DIRECTORY: u32 = fs::FILE_LIST_DIRECTORY.0;
+#[cfg(feature = "watch")]
+pub const FILE_NOTIFY_CHANGE_ATTRIBUTES: u32 = fs::FILE_NOTIFY_CHANGE_ATTRIBUTES.0;
+#[cfg(feature = "watch")]
+pub const FILE_NOTIFY_CHANGE_DIR_NAME: u32 = fs::FILE_NOTIFY_CHANGE_DIR_NAME.0;
+#[cfg(feature = "watch")]
+pub const FILE_NOTIFY_CHANGE_FILE_NAME: u32 = fs::FILE_NOTIFY_CHANGE_FILE_NAME.0;
+#[cfg(feature = "watch")]
+pub const FILE_NOTIFY_CHANGE_LAST_WRITE: u32 = fs::FILE_NOTIFY_CHANGE_LAST_WRITE.0;
+
+// The structs below hold typed handles, flags or strings in `windows`, so
+// they are declared with the plain field types of `windows-sys` instead.
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct UNICODE_STRING {
+    pub Length: u16,
+    pub MaximumLength: u16,
+    pub Buffer: *mut u16,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct SECURITY_ATTRIBUTES {
+    pub nLength: u32,
+    pub lpSecurityDescriptor: *mut c_void,
+    pub bInheritHandle: BOOL,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct LUID_AND_ATTRIBUTES {
+    pub Luid: LUID,
+    pub Attributes: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct TOKEN_PRIVILEGES {
+    pub PrivilegeCount: u32,
+    pub Privileges: [LUID_AND_ATTRIBUTES; 1],
+}
+
+#[cfg(feature = "usn")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct FILE_ID_DESCRIPTOR {
+    pub dwSize: u32,
+    pub Type: i32,
+    pub Anonymous: FILE_ID_DESCRIPTOR_0,
+}
+
+#[cfg(feature = "usn")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub union FILE_ID_DESCRIPTOR_0 {
+    pub FileId: i64,
+    pub ObjectId: windows::core::GUID,
+    pub ExtendedFileId: fs::FILE_ID_128,
+}
+
+// Hands the error of a failed `windows` call back to `GetLastError`.
+fn set_last_error(e: &windows::core::Error) {
+    // `HRESULT_FROM_WIN32` keeps the Win32 error code in the low word.
+    let code = e.code().0 as u32;
+    let code = if code & 0xFFFF_0000 == 0x8007_0000 {
+        code & 0xFFFF
+    } else {
+        code
+    };
+    unsafe { foundation::SetLastError(foundation::WIN32_ERROR(code)) }
+}
+
+fn to_bool(result: windows::core::Result<()>) -> BOOL {
+    match result {
+        Ok(()) => TRUE,
+        Err(e) => {
+            set_last_error(&e);
+            FALSE
+        }
+    }
+}
+
+fn to_handle(result: windows::core::Result<foundation::HANDLE>) -> HANDLE {
+    match result {
+        Ok(handle) => handle.0,
+        Err(e) => {
+            set_last_error(&e);
+            INVALID_HANDLE_VALUE
+        }
+    }
+}
+
+#[cfg(feature = "backup")]
+unsafe fn buffer<'a, T>(ptr: *const T, len: u32) -> &'a [T] {
+    if ptr.is_null() {
+        &[]
+    } else {
+        slice::from_raw_parts(ptr, len as usize)
+    }
+}
+
+unsafe fn buffer_mut<'a, T>(ptr: *mut T, len: u32) -> &'a mut [T] {
+    if ptr.is_null() {
+        &mut []
+    } else {
+        slice::from_raw_parts_mut(ptr, len as usize)
+    }
+}
+
+unsafe fn optional_buffer_mut<'a, T>(ptr: *mut T, len: u32) -> Option<&'a mut [T]> {
+    if ptr.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts_mut(ptr, len as usize))
+    }
+}
+
+// A negative count stands for a nul-terminated string, as in `CompareStringOrdinal`.
+unsafe fn counted_string<'a>(ptr: *const u16, count: i32) -> &'a [u16] {
+    let len = match usize::try_from(count) {
+        Ok(len) => len,
+        Err(_) => (0..).take_while(|&i| *ptr.add(i) != 0).count(),
+    };
+    slice::from_raw_parts(ptr, len)
+}
+
+pub unsafe fn GetLastError() -> u32 {
+    // The standard library reads the same thread-local value.
+    io::Error::last_os_error().raw_os_error().unwrap_or(0) as u32
+}
+
+pub unsafe fn SetLastError(dwerrcode: u32) {
+    foundation::SetLastError(foundation::WIN32_ERROR(dwerrcode))
+}
+
+pub unsafe fn CloseHandle(hobject: HANDLE) -> BOOL {
+    to_bool(foundation::CloseHandle(foundation::HANDLE(hobject)))
+}
+
+pub unsafe fn LocalFree(hmem: *mut c_void) -> *mut c_void {
+    // `LocalFree` returns null on success, which `windows` reports as an error.
+    match foundation::LocalFree(foundation::HLOCAL(hmem)) {
+        Ok(hmem) => hmem.0,
+        Err(_) => null_mut(),
+    }
+}
+
+pub unsafe fn RtlNtStatusToDosError(status: NTSTATUS) -> u32 {
+    foundation::RtlNtStatusToDosError(foundation::NTSTATUS(status))
+}
+
+pub unsafe fn CompareStringOrdinal(
+    lpstring1: *const u16,
+    cchcount1: i32,
+    lpstring2: *const u16,
+    cchcount2: i32,
+    bignorecase: BOOL,
+) -> i32 {
+    globalization::CompareStringOrdinal(
+        counted_string(lpstring1, cchcount1),
+        counted_string(lpstring2, cchcount2),
+        foundation::BOOL(bignorecase),
+    )
+    .0
+}
+
+pub unsafe fn ConvertStringSecurityDescriptorToSecurityDescriptorW(
+    stringsecuritydescriptor: *const u16,
+    stringsdrevision: u32,
+    securitydescriptor: *mut PSECURITY_DESCRIPTOR,
+    securitydescriptorsize: *mut u32,
+) -> BOOL {
+    to_bool(authorization::ConvertStringSecurityDescriptorToSecurityDescriptorW(
+        PCWSTR(stringsecuritydescriptor),
+        stringsdrevision,
+        securitydescriptor.cast(),
+        Some(securitydescriptorsize),
+    ))
+}
+
+pub unsafe fn AdjustTokenPrivileges(
+    tokenhandle: HANDLE,
+    disableallprivileges: BOOL,
+    newstate: *const TOKEN_PRIVILEGES,
+    bufferlength: u32,
+    previousstate: *mut TOKEN_PRIVILEGES,
+    returnlength: *mut u32,
+) -> BOOL {
+    to_bool(security::AdjustTokenPrivileges(
+        foundation::HANDLE(tokenhandle),
+        foundation::BOOL(disableallprivileges),
+        Some(newstate.cast()),
+        bufferlength,
+        Some(previousstate.cast()),
+        Some(returnlength),
+    ))
+}
+
+pub unsafe fn GetKernelObjectSecurity(
+    handle: HANDLE,
+    requestedinformation: u32,
+    psecuritydescriptor: PSECURITY_DESCRIPTOR,
+    nlength: u32,
+    lpnlengthneeded: *mut u32,
+) -> BOOL {
+    to_bool(security::GetKernelObjectSecurity(
+        foundation::HANDLE(handle),
+        requestedinformation,
+        security::PSECURITY_DESCRIPTOR(psecuritydescriptor),
+        nlength,
+        lpnlengthneeded,
+    ))
+}
+
+pub unsafe fn SetKernelObjectSecurity(
+    handle: HANDLE,
+    securityinformation: u32,
+    securitydescriptor: PSECURITY_DESCRIPTOR,
+) -> BOOL {
+    to_bool(security::SetKernelObjectSecurity(
+        foundation::HANDLE(handle),
+        security::OBJECT_SECURITY_INFORMATION(securityinformation),
+        security::PSECURITY_DESCRIPTOR(securitydescriptor),
+    ))
+}
+
+pub unsafe fn GetTokenInformation(
+    tokenhandle: HANDLE,
+    tokeninformationclass: i32,
+    tokeninformation: *mut c_void,
+    tokeninformationlength: u32,
+    returnlength: *mut u32,
+) -> BOOL {
+    to_bool(security::GetTokenInformation(
+        foundation::HANDLE(tokenhandle),
+        security::TOKEN_INFORMATION_CLASS(tokeninformationclass),
+        Some(tokeninformation),
+        tokeninformationlength,
+        returnlength,
+    ))
+}
+
+pub unsafe fn LookupPrivilegeNameW(
+    lpsystemname: *const u16,
+    lpluid: *const LUID,
+    lpname: *mut u16,
+    cchname: *mut u32,
+) -> BOOL {
+    to_bool(security::LookupPrivilegeNameW(
+        PCWSTR(lpsystemname),
+        lpluid,
+        PWSTR(lpname),
+        cchname,
+    ))
+}
+
+pub unsafe fn LookupPrivilegeValueW(lpsystemname: *const u16, lpname: *const u16, lpluid: *mut LUID) -> BOOL {
+    to_bool(security::LookupPrivilegeValueW(
+        PCWSTR(lpsystemname),
+        PCWSTR(lpname),
+        lpluid,
+    ))
+}
+
+pub unsafe fn GetCurrentProcess() -> HANDLE {
+    threading::GetCurrentProcess().0
+}
+
+pub unsafe fn OpenProcessToken(processhandle: HANDLE, desiredaccess: u32, tokenhandle: *mut HANDLE) -> BOOL {
+    to_bool(threading::OpenProcessToken(
+        foundation::HANDLE(processhandle),
+        security::TOKEN_ACCESS_MASK(desiredaccess),
+        tokenhandle.cast(),
+    ))
+}
+
+pub unsafe fn DeviceIoControl(
+    hdevice: HANDLE,
+    dwiocontrolcode: u32,
+    lpinbuffer: *const c_void,
+    ninbuffersize: u32,
+    lpoutbuffer: *mut c_void,
+    noutbuffersize: u32,
+    lpbytesreturned: *mut u32,
+    lpoverlapped: *mut OVERLAPPED,
+) -> BOOL {
+    to_bool(sysio::DeviceIoControl(
+        foundation::HANDLE(hdevice),
+        dwiocontrolcode,
+        Some(lpinbuffer),
+        ninbuffersize,
+        Some(lpoutbuffer),
+        noutbuffersize,
+        Some(lpbytesreturned),
+        Some(lpoverlapped),
+    ))
+}
+
+#[cfg(feature = "watch")]
+pub unsafe fn CancelIoEx(hfile: HANDLE, lpoverlapped: *const OVERLAPPED) -> BOOL {
+    to_bool(sysio::CancelIoEx(foundation::HANDLE(hfile), Some(lpoverlapped)))
+}
+
+#[cfg(feature = "watch")]
+pub unsafe fn GetOverlappedResult(
+    hfile: HANDLE,
+    lpoverlapped: *const OVERLAPPED,
+    lpnumberofbytestransferred: *mut u32,
+    bwait: BOOL,
+) -> BOOL {
+    to_bool(sysio::GetOverlappedResult(
+        foundation::HANDLE(hfile),
+        lpoverlapped,
+        lpnumberofbytestransferred,
+        foundation::BOOL(bwait),
+    ))
+}
+
+#[cfg(feature = "watch")]
+pub unsafe fn ReadDirectoryChangesW(
+    hdirectory: HANDLE,
+    lpbuffer: *mut c_void,
+    nbufferlength: u32,
+    bwatchsubtree: BOOL,
+    dwnotifyfilter: u32,
+    lpbytesreturned: *mut u32,
+    lpoverlapped: *mut OVERLAPPED,
+    lpcompletionroutine: sysio::LPOVERLAPPED_COMPLETION_ROUTINE,
+) -> BOOL {
+    to_bool(fs::ReadDirectoryChangesW(
+        foundation::HANDLE(hdirectory),
+        lpbuffer,
+        nbufferlength,
+        foundation::BOOL(bwatchsubtree),
+        fs::FILE_NOTIFY_CHANGE(dwnotifyfilter),
+        Some(lpbytesreturned),
+        Some(lpoverlapped),
+        lpcompletionroutine,
+    ))
+}
+
+#[cfg(feature = "backup")]
+pub unsafe fn BackupRead(
+    hfile: HANDLE,
+    lpbuffer: *mut u8,
+    nnumberofbytestoread: u32,
+    lpnumberofbytesread: *mut u32,
+    babort: BOOL,
+    bprocesssecurity: BOOL,
+    lpcontext: *mut *mut c_void,
+) -> BOOL {
+    to_bool(fs::BackupRead(
+        foundation::HANDLE(hfile),
+        buffer_mut(lpbuffer, nnumberofbytestoread),
+        lpnumberofbytesread,
+        foundation::BOOL(babort),
+        foundation::BOOL(bprocesssecurity),
+        lpcontext,
+    ))
+}
+
+#[cfg(feature = "backup")]
+pub unsafe fn BackupSeek(
+    hfile: HANDLE,
+    dwlowbytestoseek: u32,
+    dwhighbytestoseek: u32,
+    lpdwlowbyteseeked: *mut u32,
+    lpdwhighbyteseeked: *mut u32,
+    lpcontext: *mut *mut c_void,
+) -> BOOL {
+    to_bool(fs::BackupSeek(
+        foundation::HANDLE(hfile),
+        dwlowbytestoseek,
+        dwhighbytestoseek,
+        lpdwlowbyteseeked,
+        lpdwhighbyteseeked,
+        lpcontext,
+    ))
+}
+
+#[cfg(feature = "backup")]
+pub unsafe fn BackupWrite(
+    hfile: HANDLE,
+    lpbuffer: *const u8,
+    nnumberofbytestowrite: u32,
+    lpnumberofbyteswritten: *mut u32,
+    babort: BOOL,
+    bprocesssecurity: BOOL,
+    lpcontext: *mut *mut c_void,
+) -> BOOL {
+    to_bool(fs::BackupWrite(
+        foundation::HANDLE(hfile),
+        buffer(lpbuffer, nnumberofbytestowrite),
+        lpnumberofbyteswritten,
+        foundation::BOOL(babort),
+        foundation::BOOL(bprocesssecurity),
+        lpcontext,
+    ))
+}
+
+pub unsafe fn CreateDirectoryW(lppathname: *const u16, lpsecurityattributes: *const SECURITY_ATTRIBUTES) -> BOOL {
+    to_bool(fs::CreateDirectoryW(
+        PCWSTR(lppathname),
+        Some(lpsecurityattributes.cast()),
+    ))
+}
+
+pub unsafe fn FindFirstFileExW(
+    lpfilename: *const u16,
+    finfolevelid: i32,
+    lpfindfiledata: *mut c_void,
+    fsearchop: i32,
+    lpsearchfilter: *const c_void,
+    dwadditionalflags: u32,
+) -> HANDLE {
+    to_handle(fs::FindFirstFileExW(
+        PCWSTR(lpfilename),
+        fs::FINDEX_INFO_LEVELS(finfolevelid),
+        lpfindfiledata,
+        fs::FINDEX_SEARCH_OPS(fsearchop),
+        Some(lpsearchfilter),
+        fs::FIND_FIRST_EX_FLAGS(dwadditionalflags),
+    ))
+}
+
+pub unsafe fn FindNextFileW(hfindfile: HANDLE, lpfindfiledata: *mut WIN32_FIND_DATAW) -> BOOL {
+    to_bool(fs::FindNextFileW(foundation::HANDLE(hfindfile), lpfindfiledata))
+}
+
+pub unsafe fn FindClose(hfindfile: HANDLE) -> BOOL {
+    to_bool(fs::FindClose(foundation::HANDLE(hfindfile)))
+}
+
+pub unsafe fn FindFirstVolumeMountPointW(
+    lpszrootpathname: *const u16,
+    lpszvolumemountpoint: *mut u16,
+    cchbufferlength: u32,
+) -> HANDLE {
+    to_handle(fs::FindFirstVolumeMountPointW(
+        PCWSTR(lpszrootpathname),
+        buffer_mut(lpszvolumemountpoint, cchbufferlength),
+    ))
+}
+
+pub unsafe fn FindNextVolumeMountPointW(
+    hfindvolumemountpoint: HANDLE,
+    lpszvolumemountpoint: *mut u16,
+    cchbufferlength: u32,
+) -> BOOL {
+    to_bool(fs::FindNextVolumeMountPointW(
+        foundation::HANDLE(hfindvolumemountpoint),
+        buffer_mut(lpszvolumemountpoint, cchbufferlength),
+    ))
+}
+
+pub unsafe fn FindVolumeMountPointClose(hfindvolumemountpoint: HANDLE) -> BOOL {
+    to_bool(fs::FindVolumeMountPointClose(foundation::HANDLE(hfindvolumemountpoint)))
+}
+
+pub unsafe fn SetVolumeMountPointW(lpszvolumemountpoint: *const u16, lpszvolumename: *const u16) -> BOOL {
+    to_bool(fs::SetVolumeMountPointW(
+        PCWSTR(lpszvolumemountpoint),
+        PCWSTR(lpszvolumename),
+    ))
+}
+
+pub unsafe fn DeleteVolumeMountPointW(lpszvolumemountpoint: *const u16) -> BOOL {
+    to_bool(fs::DeleteVolumeMountPointW(PCWSTR(lpszvolumemountpoint)))
+}
+
+pub unsafe fn GetVolumeNameForVolumeMountPointW(
+    lpszvolumemountpoint: *const u16,
+    lpszvolumename: *mut u16,
+    cchbufferlength: u32,
+) -> BOOL {
+    to_bool(fs::GetVolumeNameForVolumeMountPointW(
+        PCWSTR(lpszvolumemountpoint),
+        buffer_mut(lpszvolumename, cchbufferlength),
+    ))
+}
+
+pub unsafe fn GetVolumePathNameW(lpszfilename: *const u16, lpszvolumepathname: *mut u16, cchbufferlength: u32) -> BOOL {
+    to_bool(fs::GetVolumePathNameW(
+        PCWSTR(lpszfilename),
+        buffer_mut(lpszvolumepathname, cchbufferlength),
+    ))
+}
+
+pub unsafe fn GetVolumeInformationW(
+    lprootpathname: *const u16,
+    lpvolumenamebuffer: *mut u16,
+    nvolumenamesize: u32,
+    lpvolumeserialnumber: *mut u32,
+    lpmaximumcomponentlength: *mut u32,
+    lpfilesystemflags: *mut u32,
+    lpfilesystemnamebuffer: *mut u16,
+    nfilesystemnamesize: u32,
+) -> BOOL {
+    to_bool(fs::GetVolumeInformationW(
+        PCWSTR(lprootpathname),
+        optional_buffer_mut(lpvolumenamebuffer, nvolumenamesize),
+        Some(lpvolumeserialnumber),
+        Some(lpmaximumcomponentlength),
+        Some(lpfilesystemflags),
+        optional_buffer_mut(lpfilesystemnamebuffer, nfilesystemnamesize),
+    ))
+}
+
+pub unsafe fn GetDiskFreeSpaceW(
+    lprootpathname: *const u16,
+    lpsectorspercluster: *mut u32,
+    lpbytespersector: *mut u32,
+    lpnumberoffreeclusters: *mut u32,
+    lptotalnumberofclusters: *mut u32,
+) -> BOOL {
+    to_bool(fs::GetDiskFreeSpaceW(
+        PCWSTR(lprootpathname),
+        Some(lpsectorspercluster),
+        Some(lpbytespersector),
+        Some(lpnumberoffreeclusters),
+        Some(lptotalnumberofclusters),
+    ))
+}
+
+pub unsafe fn GetDriveTypeW(lprootpathname: *const u16) -> u32 {
+    fs::GetDriveTypeW(PCWSTR(lprootpathname))
+}
+
+pub unsafe fn GetFullPathNameW(
+    lpfilename: *const u16,
+    nbufferlength: u32,
+    lpbuffer: *mut u16,
+    lpfilepart: *mut *mut u16,
+) -> u32 {
+    fs::GetFullPathNameW(
+        PCWSTR(lpfilename),
+        optional_buffer_mut(lpbuffer, nbufferlength),
+        Some(lpfilepart.cast()),
+    )
+}
+
+pub unsafe fn GetFileInformationByHandle(hfile: HANDLE, lpfileinformation: *mut BY_HANDLE_FILE_INFORMATION) -> BOOL {
+    to_bool(fs::GetFileInformationByHandle(
+        foundation::HANDLE(hfile),
+        lpfileinformation,
+    ))
+}
+
+pub unsafe fn GetFinalPathNameByHandleW(hfile: HANDLE, lpszfilepath: *mut u16, cchfilepath: u32, dwflags: u32) -> u32 {
+    fs::GetFinalPathNameByHandleW(
+        foundation::HANDLE(hfile),
+        buffer_mut(lpszfilepath, cchfilepath),
+        fs::GETFINALPATHNAMEBYHANDLE_FLAGS(dwflags),
+    )
+}
+
+pub unsafe fn GetFileInformationByHandleEx(
+    hfile: HANDLE,
+    fileinformationclass: FILE_INFO_BY_HANDLE_CLASS,
+    lpfileinformation: *mut c_void,
+    dwbuffersize: u32,
+) -> BOOL {
+    to_bool(fs::GetFileInformationByHandleEx(
+        foundation::HANDLE(hfile),
+        fs::FILE_INFO_BY_HANDLE_CLASS(fileinformationclass),
+        lpfileinformation,
+        dwbuffersize,
+    ))
+}
+
+pub unsafe fn SetFileInformationByHandle(
+    hfile: HANDLE,
+    fileinformationclass: FILE_INFO_BY_HANDLE_CLASS,
+    lpfileinformation: *const c_void,
+    dwbuffersize: u32,
+) -> BOOL {
+    to_bool(fs::SetFileInformationByHandle(
+        foundation::HANDLE(hfile),
+        fs::FILE_INFO_BY_HANDLE_CLASS(fileinformationclass),
+        lpfileinformation,
+        dwbuffersize,
+    ))
+}
+
+#[cfg(feature = "usn")]
+pub unsafe fn OpenFileById(
+    hvolumehint: HANDLE,
+    lpfileid: *const FILE_ID_DESCRIPTOR,
+    dwdesiredaccess: u32,
+    dwsharemode: u32,
+    lpsecurityattributes: *const SECURITY_ATTRIBUTES,
+    dwflagsandattributes: u32,
+) -> HANDLE {
+    to_handle(fs::OpenFileById(
+        foundation::HANDLE(hvolumehint),
+        lpfileid.cast(),
+        dwdesiredaccess,
+        fs::FILE_SHARE_MODE(dwsharemode),
+        Some(lpsecurityattributes.cast()),
+        fs::FILE_FLAGS_AND_ATTRIBUTES(dwflagsandattributes),
+    ))
+}
@@ -1,13 +1,27 @@
-use std::alloc::{alloc, handle_alloc_error, Layout};
-use std::mem::align_of;
+use std::cell::Cell;
+use std::io;
+use std::mem::{align_of, ManuallyDrop};
+use std::slice;
 
-use super::c::{MAXIMUM_REPARSE_DATA_BUFFER_SIZE, REPARSE_DATA_BUFFER};
+use super::c::{self, MAXIMUM_REPARSE_DATA_BUFFER_SIZE, REPARSE_DATA_BUFFER};
+use super::helpers;
+use crate::reparse::REPARSE_DATA_BUFFER_HEADER_SIZE;
 
 type MaybeU8 = std::mem::MaybeUninit<u8>;
 
+type Raw = [MaybeU8; MAXIMUM_REPARSE_DATA_BUFFER_SIZE as usize];
+
+thread_local! {
+    // The buffer of the last `BytesAsReparseDataBuffer` dropped on this thread,
+    // so that bulk operations do not allocate and free one for every call.
+    static SPARE: Cell<Option<Box<Raw>>> = Cell::new(None);
+}
+
 #[repr(align(4))]
 pub struct BytesAsReparseDataBuffer {
-    value: Box<[MaybeU8; MAXIMUM_REPARSE_DATA_BUFFER_SIZE as usize]>,
+    value: ManuallyDrop<Box<Raw>>,
+    // Number of leading bytes of `value` written by the last read.
+    len: usize,
 }
 
 // Asserts that pointers of `BytesAsReparseDataBuffer` can be casted to
@@ -18,26 +32,160 @@ const _: () = {
     assert!((a % b) == 0);
 };
 
-impl BytesAsReparseDataBuffer {
-    // MSRV(1.82): Use `Box::new_uninit_slice` instead.
+/// Size of the buffer tried before `BytesAsReparseDataBuffer`. It fits the
+/// reparse data of junctions with targets of up to about 240 characters.
+const SMALL_REPARSE_DATA_BUFFER_SIZE: usize = 1024;
+
+// A reparse data buffer on the stack, for the common case of short targets.
+#[repr(align(4))]
+pub struct SmallReparseDataBuffer {
+    value: [MaybeU8; SMALL_REPARSE_DATA_BUFFER_SIZE],
+    len: usize,
+}
+
+const _: () = {
+    let a = align_of::<SmallReparseDataBuffer>();
+    let b = align_of::<REPARSE_DATA_BUFFER>();
+    assert!((a % b) == 0);
+};
+
+impl SmallReparseDataBuffer {
     pub fn new() -> Self {
-        type Raw = [MaybeU8; MAXIMUM_REPARSE_DATA_BUFFER_SIZE as usize];
-        const LAYOUT: Layout = Layout::new::<Raw>();
-        let boxed = unsafe {
-            let ptr = alloc(LAYOUT).cast::<Raw>();
-            if ptr.is_null() {
-                handle_alloc_error(LAYOUT);
+        Self {
+            value: [MaybeU8::uninit(); SMALL_REPARSE_DATA_BUFFER_SIZE],
+            len: 0,
+        }
+    }
+
+    // Reads the reparse data of `handle`. Fails with `ERROR_MORE_DATA` if it
+    // does not fit, in which case only the header is available from `tag`.
+    pub fn read(&mut self, handle: c::HANDLE) -> io::Result<&[u8]> {
+        self.len = 0;
+        match read_into(handle, &mut self.value) {
+            Ok(len) => self.len = len,
+            Err(e) => {
+                if e.raw_os_error() == Some(c::ERROR_MORE_DATA as i32) {
+                    self.len = REPARSE_DATA_BUFFER_HEADER_SIZE;
+                }
+                return Err(e);
             }
-            Box::from_raw(ptr)
-        };
-        Self { value: boxed }
+        }
+        Ok(self.as_bytes())
+    }
+
+    // Returns the reparse tag of the last read, if it got as far as the header.
+    pub fn tag(&self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.as_bytes().get(..4)?.try_into().ok()?))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        // SAFETY: the first `len` bytes have been written by the last read.
+        unsafe { slice::from_raw_parts(self.value.as_ptr().cast::<u8>(), self.len) }
+    }
+}
+
+// A reparse data buffer of the maximum size on the stack, filled in order.
+#[repr(align(4))]
+pub struct StackReparseDataBuffer {
+    value: [MaybeU8; MAXIMUM_REPARSE_DATA_BUFFER_SIZE as usize],
+    len: usize,
+}
+
+const _: () = {
+    let a = align_of::<StackReparseDataBuffer>();
+    let b = align_of::<REPARSE_DATA_BUFFER>();
+    assert!((a % b) == 0);
+};
+
+impl StackReparseDataBuffer {
+    pub fn new() -> Self {
+        Self {
+            value: [MaybeU8::uninit(); MAXIMUM_REPARSE_DATA_BUFFER_SIZE as usize],
+            len: 0,
+        }
+    }
+
+    // Panics if `bytes` do not fit, so callers must check the total size first.
+    pub fn push(&mut self, bytes: &[u8]) {
+        for (dst, &b) in self.value[self.len..][..bytes.len()].iter_mut().zip(bytes) {
+            *dst = MaybeU8::new(b);
+        }
+        self.len += bytes.len();
+    }
+
+    pub fn push_wide(&mut self, wide: &[u16]) {
+        for c in wide {
+            self.push(&c.to_le_bytes());
+        }
     }
 
     pub fn as_mut_ptr(&mut self) -> *mut REPARSE_DATA_BUFFER {
         self.value.as_mut_ptr().cast::<REPARSE_DATA_BUFFER>()
     }
 
-    pub unsafe fn assume_init(&mut self) -> &REPARSE_DATA_BUFFER {
-        &*self.as_mut_ptr()
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        // SAFETY: the first `len` bytes have been pushed.
+        unsafe { slice::from_raw_parts(self.value.as_ptr().cast::<u8>(), self.len) }
+    }
+}
+
+impl BytesAsReparseDataBuffer {
+    // Reuses the spare buffer of this thread if there is one. Its content is
+    // left over from a previous call, which is fine as it is never assumed to
+    // be initialized.
+    pub fn new() -> Self {
+        let boxed = SPARE.try_with(Cell::take).ok().flatten().unwrap_or_else(Self::alloc);
+        Self {
+            value: ManuallyDrop::new(boxed),
+            len: 0,
+        }
+    }
+
+    // MSRV(1.82): Use `Box::new_uninit` instead.
+    fn alloc() -> Box<Raw> {
+        let boxed = vec![MaybeU8::uninit(); MAXIMUM_REPARSE_DATA_BUFFER_SIZE as usize].into_boxed_slice();
+        match boxed.try_into() {
+            Ok(raw) => raw,
+            Err(_) => unreachable!("the slice has the length of `Raw`"),
+        }
+    }
+
+    // Reads the reparse data of `handle`, which always fits.
+    pub fn read(&mut self, handle: c::HANDLE) -> io::Result<&[u8]> {
+        self.len = 0;
+        self.len = read_into(handle, &mut self.value[..])?;
+        Ok(self.as_bytes())
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        // SAFETY: the first `len` bytes have been written by the last read.
+        unsafe { slice::from_raw_parts(self.value.as_ptr().cast::<u8>(), self.len) }
+    }
+}
+
+// Reads the reparse data of `handle` into `buf`, and returns the number of
+// bytes written, checked against the size of `buf`.
+fn read_into(handle: c::HANDLE, buf: &mut [MaybeU8]) -> io::Result<usize> {
+    let len = helpers::get_reparse_data_point(handle, buf.as_mut_ptr().cast::<REPARSE_DATA_BUFFER>(), buf.len())?;
+    let len = len as usize;
+    if len > buf.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "reparse data larger than the buffer",
+        ));
+    }
+    Ok(len)
+}
+
+impl Drop for BytesAsReparseDataBuffer {
+    fn drop(&mut self) {
+        // SAFETY: `value` is not used after this.
+        let boxed = unsafe { ManuallyDrop::take(&mut self.value) };
+        // The buffer is freed instead if the thread is exiting.
+        let _ = SPARE.try_with(move |spare| spare.set(Some(boxed)));
     }
 }
@@ -0,0 +1,67 @@
+// The ETW provider of `crate::etw`, registered on first use and never unregistered.
+
+use std::ffi::OsStr;
+use std::fmt::Write;
+use std::io;
+use std::os::windows::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Once;
+
+use super::{c, helpers};
+use crate::etw::PROVIDER_ID;
+
+const TRACE_LEVEL_ERROR: u8 = 2;
+const TRACE_LEVEL_INFORMATION: u8 = 4;
+
+static REGISTER: Once = Once::new();
+// Zero until the provider is registered, or if registering failed.
+static HANDLE: AtomicU64 = AtomicU64::new(0);
+
+fn handle() -> u64 {
+    REGISTER.call_once(|| {
+        let id = c::GUID {
+            data1: PROVIDER_ID.data1,
+            data2: PROVIDER_ID.data2,
+            data3: PROVIDER_ID.data3,
+            data4: PROVIDER_ID.data4,
+        };
+        let mut handle = 0;
+        // SAFETY: No enable callback is given, and `handle` outlives the call.
+        if unsafe { c::EventRegister(&id, None, ptr::null(), &mut handle) } == 0 {
+            HANDLE.store(handle, Ordering::Release);
+        }
+    });
+    HANDLE.load(Ordering::Acquire)
+}
+
+// Writes the event of a change of `junction`, unless no session listens.
+pub fn report(operation: &str, junction: &Path, target: Option<&Path>, result: Result<(), &io::Error>) {
+    let level = if result.is_ok() {
+        TRACE_LEVEL_INFORMATION
+    } else {
+        TRACE_LEVEL_ERROR
+    };
+    let handle = handle();
+    // SAFETY: `EventProviderEnabled` accepts any handle.
+    if handle == 0 || unsafe { c::EventProviderEnabled(handle, level, 0) } == 0 {
+        return;
+    }
+    let mut message = format!("{} {}", operation, junction.display());
+    if let Some(target) = target {
+        let _ = write!(message, " -> {}", target.display());
+    }
+    if let Err(e) = result {
+        let _ = write!(message, ": {}", e);
+    }
+    let message: Vec<u16> = OsStr::new(&message).encode_wide().chain(Some(0)).collect();
+    // SAFETY: `message` is null-terminated.
+    unsafe { c::EventWriteString(handle, level, 0, message.as_ptr()) };
+}
+
+// Returns the path of the file open as `handle`, for reporting changes made
+// through a handle. The path is `?` if it cannot be queried.
+pub fn handle_path(handle: c::HANDLE) -> PathBuf {
+    helpers::final_path(handle).unwrap_or_else(|_| PathBuf::from("?"))
+}
@@ -1,146 +1,1145 @@
 mod utf16;
 
-use std::ffi::OsStr;
+use std::ffi::{c_void, OsStr, OsString};
 use std::fs::{File, OpenOptions};
 use std::io;
 use std::mem::{size_of, zeroed, MaybeUninit};
-use std::os::windows::ffi::OsStrExt;
+use std::ops::Deref;
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
 use std::os::windows::fs::OpenOptionsExt;
-use std::path::Path;
-use std::ptr::{addr_of_mut, null, null_mut};
+use std::os::windows::io::{AsRawHandle, FromRawHandle, RawHandle};
+use std::path::{Component, Path, PathBuf};
+use std::ptr::{addr_of, addr_of_mut, null, null_mut};
+use std::sync::atomic::{AtomicPtr, AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, Once, PoisonError};
+use std::thread;
 
 pub(crate) use utf16::utf16s;
 
 use super::c;
+use crate::{Options, PrivilegePolicy, UnsupportedFilesystem};
 
-pub fn open_reparse_point(reparse_point: &Path, write: bool) -> io::Result<File> {
+// An open reparse point, together with the privileges enabled to open it.
+// The privileges are reverted after the handle is closed.
+pub struct ReparsePoint {
+    file: File,
+    _privileges: Vec<PrivilegeGuard>,
+}
+
+impl From<File> for ReparsePoint {
+    // Wraps a file opened elsewhere, for which no privileges were enabled.
+    fn from(file: File) -> Self {
+        ReparsePoint {
+            file,
+            _privileges: Vec::new(),
+        }
+    }
+}
+
+#[cfg(feature = "raw")]
+impl ReparsePoint {
+    // Returns the handle alone. Privileges only matter for opening, so they
+    // can be reverted while the handle stays open.
+    pub fn into_file(self) -> File {
+        self.file
+    }
+}
+
+impl Deref for ReparsePoint {
+    type Target = File;
+
+    fn deref(&self) -> &File {
+        &self.file
+    }
+}
+
+pub fn open_reparse_point(reparse_point: &Path, write: bool, options: &Options) -> io::Result<ReparsePoint> {
     let access = c::GENERIC_READ | if write { c::GENERIC_WRITE } else { 0 };
+    open_reparse_point_with_access(reparse_point, access, write, options)
+}
+
+// Opens `reparse_point` for changing its owner, DACL and attributes.
+pub fn open_reparse_point_for_security(reparse_point: &Path, options: &Options) -> io::Result<ReparsePoint> {
+    let access = c::READ_CONTROL | c::WRITE_DAC | c::WRITE_OWNER | c::FILE_READ_ATTRIBUTES | c::FILE_WRITE_ATTRIBUTES;
+    open_reparse_point_with_access(reparse_point, access, true, options)
+}
+
+// Opens `reparse_point` for changing its attributes, and with `dacl` also its DACL.
+pub fn open_reparse_point_for_attributes(
+    reparse_point: &Path,
+    dacl: bool,
+    options: &Options,
+) -> io::Result<ReparsePoint> {
+    let access =
+        c::FILE_READ_ATTRIBUTES | c::FILE_WRITE_ATTRIBUTES | if dacl { c::READ_CONTROL | c::WRITE_DAC } else { 0 };
+    open_reparse_point_with_access(reparse_point, access, true, options)
+}
+
+// Opens `reparse_point` with `access`. `write` tells which privilege to enable
+// when access is denied.
+fn open_reparse_point_with_access(
+    reparse_point: &Path,
+    access: u32,
+    write: bool,
+    options: &Options,
+) -> io::Result<ReparsePoint> {
+    trace_span!("open_reparse_point", path = ?reparse_point, write);
     // Set this flag to obtain a handle to a directory. Appropriate security checks
     // still apply when this flag is used without SE_BACKUP_NAME and SE_RESTORE_NAME
     // privileges.
     // Ref <https://learn.microsoft.com/en-us/windows/win32/api/fileapi/nf-fileapi-createfilea#directories>
     let dir_attrs = c::FILE_FLAG_OPEN_REPARSE_POINT | c::FILE_FLAG_BACKUP_SEMANTICS;
     let mut opts = OpenOptions::new();
-    opts.access_mode(access).share_mode(0).custom_flags(dir_attrs);
+    opts.access_mode(access)
+        .share_mode(options.share_mode)
+        .custom_flags(dir_attrs);
+    let mut privileges = Vec::new();
+    if let PrivilegePolicy::Always(names) = options.privileges {
+        for name in names {
+            privileges.extend(set_privilege(
+                &os_str_to_utf16(OsStr::new(name)),
+                options.restore_privileges,
+            )?);
+        }
+    }
     // Opens existing directory path
-    match opts.open(reparse_point) {
-        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
-            set_privilege(write)?;
+    let open = || {
+        if options.nt_paths {
+            nt_open_reparse_point(reparse_point, access, options.share_mode)
+        } else {
             opts.open(reparse_point)
         }
+    };
+    let result = match open() {
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied && options.privileges == PrivilegePolicy::Auto => {
+            trace_event!(error = e.raw_os_error(), "access denied, adjusting privileges");
+            match set_privilege(auto_privilege(write), options.restore_privileges) {
+                Ok(guard) => {
+                    privileges.extend(guard);
+                    open()
+                }
+                // The token cannot be adjusted at all, as in AppContainers, so
+                // the access error is the one worth reporting.
+                Err(_) => Err(e),
+            }
+        }
         other => other,
+    };
+    #[cfg(feature = "tracing")]
+    if let Err(e) = &result {
+        tracing::debug!(error = e.raw_os_error(), "failed to open reparse point");
+    }
+    Ok(ReparsePoint {
+        file: result?,
+        _privileges: privileges,
+    })
+}
+
+// Opens `reparse_point` with `NtCreateFile`, like `open_reparse_point` does with
+// `CreateFileW`. The path is converted by `nt_path`, so it is not limited to
+// `MAX_PATH` even if long paths are disabled for the process.
+fn nt_open_reparse_point(reparse_point: &Path, access: u32, share_mode: u32) -> io::Result<File> {
+    nt_open(0, nt_path(reparse_point)?, access, share_mode)
+}
+
+// Opens the entry `name` of the directory `dir` with `NtCreateFile`, without
+// following it. The entry is looked up in the directory that the handle refers
+// to, whatever its path is by now.
+pub fn nt_open_reparse_point_at(dir: c::HANDLE, name: &OsStr, write: bool, share_mode: u32) -> io::Result<File> {
+    let access = c::GENERIC_READ | if write { c::GENERIC_WRITE } else { 0 };
+    nt_open(dir, name.encode_wide().collect(), access, share_mode)
+}
+
+// Opens `path` with `NtCreateFile`, relative to `root` unless it is `0`.
+fn nt_open(root: c::HANDLE, mut path: Vec<u16>, access: u32, share_mode: u32) -> io::Result<File> {
+    let len = path.len() * size_of::<u16>();
+    if len > usize::from(u16::MAX) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "path is too long"));
+    }
+    let name = c::UNICODE_STRING {
+        Length: len as u16,
+        MaximumLength: len as u16,
+        Buffer: path.as_mut_ptr(),
+    };
+    let attributes = c::OBJECT_ATTRIBUTES {
+        Length: size_of::<c::OBJECT_ATTRIBUTES>() as u32,
+        RootDirectory: root,
+        ObjectName: &name,
+        Attributes: c::OBJ_CASE_INSENSITIVE,
+        SecurityDescriptor: null(),
+        SecurityQualityOfService: null(),
+    };
+    let mut handle: c::HANDLE = 0;
+    let status = unsafe {
+        let mut io_status: c::IO_STATUS_BLOCK = zeroed();
+        c::NtCreateFile(
+            &mut handle,
+            access | c::SYNCHRONIZE,
+            &attributes,
+            &mut io_status,
+            null(),
+            0,
+            share_mode,
+            c::FILE_OPEN,
+            c::FILE_OPEN_REPARSE_POINT | c::FILE_OPEN_FOR_BACKUP_INTENT | c::FILE_SYNCHRONOUS_IO_NONALERT,
+            null(),
+            0,
+        )
+    };
+    if status < 0 {
+        return Err(nt_error(status));
+    }
+    // SAFETY: `NtCreateFile` succeeded, so `handle` is an open handle owned by us.
+    Ok(unsafe { File::from_raw_handle(handle as RawHandle) })
+}
+
+// Converts a Win32 path to an NT path such as `\??\C:\dir`, resolving it
+// against the current directory like `get_full_path` does, but without the
+// `MAX_PATH` limit. Returns the path without the terminating null character.
+pub fn nt_path(path: &Path) -> io::Result<Vec<u16>> {
+    let path = os_str_to_utf16(path.as_os_str());
+    unsafe {
+        let mut name: c::UNICODE_STRING = zeroed();
+        let status = c::RtlDosPathNameToNtPathName_U_WithStatus(path.as_ptr(), &mut name, null_mut(), null_mut());
+        if status < 0 {
+            return Err(nt_error(status));
+        }
+        let len = usize::from(name.Length) / size_of::<u16>();
+        let nt_path = std::slice::from_raw_parts(name.Buffer, len).to_vec();
+        c::RtlFreeUnicodeString(&mut name);
+        Ok(nt_path)
+    }
+}
+
+fn nt_error(status: c::NTSTATUS) -> io::Error {
+    io::Error::from_raw_os_error(unsafe { c::RtlNtStatusToDosError(status) } as i32)
+}
+
+// Runs `f` until it succeeds, fails with an error that is not transient, or
+// the attempts configured in `options` are used up.
+pub fn retry<T, F: FnMut() -> io::Result<T>>(options: &Options, mut f: F) -> io::Result<T> {
+    let mut delay = options.backoff;
+    let mut attempt = 1;
+    loop {
+        match f() {
+            Err(e) if attempt < options.attempts && is_transient(&e) => {
+                trace_event!(
+                    error = e.raw_os_error(),
+                    attempt,
+                    ?delay,
+                    "retrying after transient error"
+                );
+                thread::sleep(delay);
+                delay = delay.checked_mul(2).unwrap_or(delay);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+// Whether `e` is worth retrying. Access denied is not: it cannot be told apart
+// from missing permissions once the error is returned.
+fn is_transient(e: &io::Error) -> bool {
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    const ERROR_LOCK_VIOLATION: i32 = 33;
+
+    matches!(e.raw_os_error(), Some(ERROR_SHARING_VIOLATION | ERROR_LOCK_VIOLATION))
+}
+
+// The privilege enabled by `PrivilegePolicy::Auto`, NUL-terminated.
+fn auto_privilege(write: bool) -> &'static [u16] {
+    // See more in <https://learn.microsoft.com/en-us/windows/win32/secauthz/privilege-constants>.
+    const SE_BACKUP_NAME: [u16; 18] = utf16s(b"SeBackupPrivilege\0");
+    const SE_RESTORE_NAME: [u16; 19] = utf16s(b"SeRestorePrivilege\0");
+    const SE_CREATE_SYMBOLIC_LINK_NAME: [u16; 30] = utf16s(b"SeCreateSymbolicLinkPrivilege\0");
+
+    if cfg!(feature = "unstable_admin") {
+        if write {
+            &SE_RESTORE_NAME
+        } else {
+            &SE_BACKUP_NAME
+        }
+    } else {
+        // FSCTL_SET_REPARSE_POINT requires below privilege.
+        // Ref <https://learn.microsoft.com/en-us/windows/win32/api/winioctl/ni-winioctl-fsctl_set_reparse_point>
+        &SE_CREATE_SYMBOLIC_LINK_NAME
+    }
+}
+
+// Opens `reparse_point` relative to `dir`, without following it. Unlike
+// `open_reparse_point`, no privileges are adjusted on failure.
+#[cfg(feature = "cap-std")]
+pub fn open_reparse_point_at(
+    dir: &::cap_std::fs::Dir,
+    reparse_point: &Path,
+    write: bool,
+    share_mode: u32,
+) -> io::Result<File> {
+    use ::cap_std::fs::OpenOptionsExt;
+
+    trace_span!("open_reparse_point_at", path = ?reparse_point, write);
+    let access = c::GENERIC_READ | if write { c::GENERIC_WRITE } else { 0 };
+    let dir_attrs = c::FILE_FLAG_OPEN_REPARSE_POINT | c::FILE_FLAG_BACKUP_SEMANTICS;
+    let mut opts = ::cap_std::fs::OpenOptions::new();
+    opts.access_mode(access).share_mode(share_mode).custom_flags(dir_attrs);
+    Ok(dir.open_with(reparse_point, &opts)?.into_std())
+}
+
+// Enables the privilege with the NUL-terminated `name` in the process token.
+// With `restore`, returns a guard that disables it again if it was disabled before.
+fn set_privilege(name: &[u16], restore: bool) -> io::Result<Option<PrivilegeGuard>> {
+    trace_span!("set_privilege", name = %String::from_utf16_lossy(&name[..name.len() - 1]), restore);
+    let result = set_privilege_inner(name, restore);
+    #[cfg(feature = "tracing")]
+    if let Err(e) = &result {
+        tracing::debug!(error = e.raw_os_error(), "failed to adjust privileges");
     }
+    result
 }
 
-fn set_privilege(write: bool) -> io::Result<()> {
+// Privileges that this process already enabled, as a bitset indexed by the
+// low part of their LUID. All predefined privileges have LUIDs below 64.
+static ENABLED_PRIVILEGES: AtomicU64 = AtomicU64::new(0);
+
+pub fn clear_privilege_cache() {
+    ENABLED_PRIVILEGES.store(0, Ordering::Relaxed);
+}
+
+fn privilege_bit(luid: &c::LUID) -> Option<u64> {
+    if luid.HighPart == 0 && luid.LowPart < 64 {
+        Some(1 << luid.LowPart)
+    } else {
+        None
+    }
+}
+
+const TOKEN_PRIVILEGES_SIZE: u32 = size_of::<c::TOKEN_PRIVILEGES>() as _;
+
+// How many operations currently use each privilege enabled with `restore`,
+// indexed like `privilege_bit`. The lock also serializes enabling privileges
+// with reverting them, so that a privilege is reverted only once the last
+// operation using it completed, and never while another one enables it.
+fn privilege_users() -> MutexGuard<'static, [u32; 64]> {
+    // MSRV(1.63): use a `static Mutex` when `Mutex::new` is `const`.
+    static USERS: AtomicPtr<Mutex<[u32; 64]>> = AtomicPtr::new(null_mut());
+    static INIT: Once = Once::new();
+    INIT.call_once(|| USERS.store(Box::into_raw(Box::new(Mutex::new([0; 64]))), Ordering::Release));
+    // SAFETY: The mutex is stored once above, and never freed.
+    let users = unsafe { &*USERS.load(Ordering::Acquire) };
+    users.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// Disables the privilege enabled by `set_privilege` with `restore`, once no
+// other operation uses it and no operation without `restore` enabled it for good.
+struct PrivilegeGuard {
+    luid: c::LUID,
+}
+
+impl Drop for PrivilegeGuard {
+    fn drop(&mut self) {
+        let mut users = privilege_users();
+        if let Some(bit) = privilege_bit(&self.luid) {
+            let users = &mut users[bit.trailing_zeros() as usize];
+            *users -= 1;
+            if *users > 0 || ENABLED_PRIVILEGES.load(Ordering::Relaxed) & bit != 0 {
+                return;
+            }
+        }
+        trace_event!("reverting privileges");
+        unsafe {
+            let mut tp: c::TOKEN_PRIVILEGES = zeroed();
+            tp.PrivilegeCount = 1;
+            tp.Privileges[0].Luid = self.luid;
+            let mut handle: c::HANDLE = c::INVALID_HANDLE_VALUE;
+            if c::OpenProcessToken(c::GetCurrentProcess(), c::TOKEN_ADJUST_PRIVILEGES, &mut handle) != 0 {
+                c::AdjustTokenPrivileges(handle, c::FALSE, &tp, 0, null_mut(), null_mut());
+                c::CloseHandle(handle);
+            }
+        }
+    }
+}
+
+fn set_privilege_inner(name: &[u16], restore: bool) -> io::Result<Option<PrivilegeGuard>> {
     const ERROR_NOT_ALL_ASSIGNED: u32 = 1300;
-    const TOKEN_PRIVILEGES_SIZE: u32 = size_of::<c::TOKEN_PRIVILEGES>() as _;
     unsafe {
+        let mut tp: c::TOKEN_PRIVILEGES = zeroed();
+        if c::LookupPrivilegeValueW(null(), name.as_ptr(), &mut tp.Privileges[0].Luid) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let luid = tp.Privileges[0].Luid;
+        let bit = privilege_bit(&luid);
+        let mut users = privilege_users();
+        if let Some(bit) = bit {
+            if ENABLED_PRIVILEGES.load(Ordering::Relaxed) & bit != 0 {
+                trace_event!("privilege already enabled");
+                return Ok(None);
+            }
+            let users = &mut users[bit.trailing_zeros() as usize];
+            if restore && *users > 0 {
+                trace_event!("privilege already enabled by another operation");
+                *users += 1;
+                return Ok(Some(PrivilegeGuard { luid }));
+            }
+        }
+        tp.Privileges[0].Attributes = c::SE_PRIVILEGE_ENABLED;
+        tp.PrivilegeCount = 1;
+
+        // Querying the previous state needs `TOKEN_QUERY` as well.
+        let access = c::TOKEN_ADJUST_PRIVILEGES | if restore { c::TOKEN_QUERY } else { 0 };
         let mut handle: c::HANDLE = c::INVALID_HANDLE_VALUE;
-        if c::OpenProcessToken(c::GetCurrentProcess(), c::TOKEN_ADJUST_PRIVILEGES, &mut handle) == 0 {
+        if c::OpenProcessToken(c::GetCurrentProcess(), access, &mut handle) == 0 {
             return Err(io::Error::last_os_error());
         }
         let handle = scopeguard::guard(handle, |h| {
             c::CloseHandle(h);
         });
-        let name = if cfg!(feature = "unstable_admin") {
-            if write {
-                c::SE_RESTORE_NAME
-            } else {
-                c::SE_BACKUP_NAME
-            }
+        let mut previous: c::TOKEN_PRIVILEGES = zeroed();
+        let mut previous_len = 0;
+        let (previous_ptr, previous_size) = if restore {
+            (addr_of_mut!(previous), TOKEN_PRIVILEGES_SIZE)
         } else {
-            // FSCTL_SET_REPARSE_POINT requires below privilege.
-            // Ref <https://learn.microsoft.com/en-us/windows/win32/api/winioctl/ni-winioctl-fsctl_set_reparse_point>
-            c::SE_CREATE_SYMBOLIC_LINK_NAME
+            (null_mut(), 0)
         };
-        let mut tp: c::TOKEN_PRIVILEGES = zeroed();
-        if c::LookupPrivilegeValueW(null(), name, &mut tp.Privileges[0].Luid) == 0 {
+        if c::AdjustTokenPrivileges(*handle, c::FALSE, &tp, previous_size, previous_ptr, &mut previous_len) == 0 {
             return Err(io::Error::last_os_error());
         }
-        tp.Privileges[0].Attributes = c::SE_PRIVILEGE_ENABLED;
-        tp.PrivilegeCount = 1;
+        if c::GetLastError() == ERROR_NOT_ALL_ASSIGNED {
+            return Err(io::Error::from_raw_os_error(ERROR_NOT_ALL_ASSIGNED as i32));
+        }
+        if !restore {
+            if let Some(bit) = bit {
+                ENABLED_PRIVILEGES.fetch_or(bit, Ordering::Relaxed);
+            }
+            return Ok(None);
+        }
+        // An empty previous state means that the privilege was already enabled.
+        if previous.PrivilegeCount == 0 {
+            return Ok(None);
+        }
+        if let Some(bit) = bit {
+            users[bit.trailing_zeros() as usize] = 1;
+        }
+        Ok(Some(PrivilegeGuard { luid }))
+    }
+}
 
-        if c::AdjustTokenPrivileges(*handle, c::FALSE, &tp, TOKEN_PRIVILEGES_SIZE, null_mut(), null_mut()) == 0 {
+// Returns the names of the privileges held by the process token, and whether
+// each of them is enabled.
+pub fn token_privileges() -> io::Result<Vec<(String, bool)>> {
+    // Long enough for the name of every privilege defined by Windows.
+    const PRIVILEGE_NAME_LEN: usize = 64;
+    unsafe {
+        let mut handle: c::HANDLE = c::INVALID_HANDLE_VALUE;
+        if c::OpenProcessToken(c::GetCurrentProcess(), c::TOKEN_QUERY, &mut handle) == 0 {
             return Err(io::Error::last_os_error());
         }
-        if c::GetLastError() == ERROR_NOT_ALL_ASSIGNED {
-            return Err(io::Error::from_raw_os_error(ERROR_NOT_ALL_ASSIGNED as i32));
+        let handle = scopeguard::guard(handle, |h| {
+            c::CloseHandle(h);
+        });
+        let mut len = 0;
+        if c::GetTokenInformation(*handle, c::TokenPrivileges, null_mut(), 0, &mut len) == 0
+            && c::GetLastError() != c::ERROR_INSUFFICIENT_BUFFER
+        {
+            return Err(io::Error::last_os_error());
         }
+        // `u64` elements keep the buffer aligned for `TOKEN_PRIVILEGES`.
+        let mut buf = vec![0u64; (len as usize + 7) / 8];
+        if c::GetTokenInformation(*handle, c::TokenPrivileges, buf.as_mut_ptr().cast(), len, &mut len) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let tp = buf.as_ptr().cast::<c::TOKEN_PRIVILEGES>();
+        let privileges: &[c::LUID_AND_ATTRIBUTES] =
+            std::slice::from_raw_parts(addr_of!((*tp).Privileges).cast(), (*tp).PrivilegeCount as usize);
+        privileges
+            .iter()
+            .map(|privilege| {
+                let mut name = [0u16; PRIVILEGE_NAME_LEN];
+                let mut name_len = name.len() as u32;
+                if c::LookupPrivilegeNameW(null(), &privilege.Luid, name.as_mut_ptr(), &mut name_len) == 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                let name = String::from_utf16_lossy(&name[..name_len as usize]);
+                Ok((name, privilege.Attributes & c::SE_PRIVILEGE_ENABLED != 0))
+            })
+            .collect()
     }
-    Ok(())
 }
 
-pub fn get_reparse_data_point(handle: c::HANDLE, rdb: *mut c::REPARSE_DATA_BUFFER) -> io::Result<()> {
+// Returns the number of bytes written into `rdb`.
+// Fails with `ERROR_MORE_DATA` if the reparse data does not fit into the `len`
+// bytes of `rdb`, in which case only the header has been written.
+pub fn get_reparse_data_point(handle: c::HANDLE, rdb: *mut c::REPARSE_DATA_BUFFER, len: usize) -> io::Result<u32> {
     // Call DeviceIoControl to get the reparse point data
+    device_io_control(handle, c::FSCTL_GET_REPARSE_POINT, null(), 0, rdb.cast(), len as u32)
+}
+
+pub fn set_reparse_point(handle: c::HANDLE, rdb: *mut c::REPARSE_DATA_BUFFER, len: u32) -> io::Result<()> {
+    device_io_control(handle, c::FSCTL_SET_REPARSE_POINT, rdb.cast(), len, null_mut(), 0)?;
+    Ok(())
+}
+
+// Deletes the reparse point whose header, as built by `reparse::build_reparse_data`
+// without data, is `header`. The tag and GUID must match those of the reparse point.
+#[cfg(feature = "raw")]
+pub fn delete_reparse_point_with_header(handle: c::HANDLE, header: &mut [u8]) -> io::Result<()> {
+    device_io_control(
+        handle,
+        c::FSCTL_DELETE_REPARSE_POINT,
+        header.as_mut_ptr().cast(),
+        header.len() as u32,
+        null_mut(),
+        0,
+    )?;
+    Ok(())
+}
+
+// See https://msdn.microsoft.com/en-us/library/windows/desktop/aa364560(v=vs.85).aspx
+pub fn delete_reparse_point(handle: c::HANDLE) -> io::Result<()> {
+    // TODO: Should we use REPARSE_DATA_BUFFER instead?
+    let mut rgdb: c::REPARSE_GUID_DATA_BUFFER = unsafe { zeroed() };
+    rgdb.ReparseTag = c::IO_REPARSE_TAG_MOUNT_POINT;
+    device_io_control(
+        handle,
+        c::FSCTL_DELETE_REPARSE_POINT,
+        addr_of_mut!(rgdb).cast(),
+        u32::from(c::REPARSE_GUID_DATA_BUFFER_HEADER_SIZE),
+        null_mut(),
+        0,
+    )?;
+    Ok(())
+}
+
+// Returns the number of bytes written into `out_buffer`.
+fn device_io_control(
+    handle: c::HANDLE,
+    code: u32,
+    in_buffer: *const c_void,
+    in_len: u32,
+    out_buffer: *mut c_void,
+    out_len: u32,
+) -> io::Result<u32> {
     let mut bytes_returned: u32 = 0;
     if unsafe {
         c::DeviceIoControl(
             handle,
-            c::FSCTL_GET_REPARSE_POINT,
+            code,
+            in_buffer,
+            in_len,
+            out_buffer,
+            out_len,
+            &mut bytes_returned,
+            null_mut(),
+        )
+    } == 0
+    {
+        let err = io::Error::last_os_error();
+        trace_event!(
+            code,
+            in_len,
+            out_len,
+            error = err.raw_os_error(),
+            "DeviceIoControl failed"
+        );
+        return Err(err);
+    }
+    trace_event!(code, in_len, out_len, bytes_returned, "DeviceIoControl succeeded");
+    Ok(bytes_returned)
+}
+
+// Fails with `UnsupportedFilesystem` if the volume containing `path` cannot
+// store reparse points. Volumes whose information cannot be queried, such as
+// some network shares, are let through so that the actual operation reports
+// the error.
+pub fn check_reparse_point_support(path: &Path) -> io::Result<()> {
+    let (volume, filesystem, flags) = match volume_information(path) {
+        Ok(info) => info,
+        Err(_) => return Ok(()),
+    };
+    if flags & c::FILE_SUPPORTS_REPARSE_POINTS != 0 {
+        return Ok(());
+    }
+    trace_event!(?volume, %filesystem, "file system does not support reparse points");
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        UnsupportedFilesystem::new(volume, filesystem),
+    ))
+}
+
+// Returns the root of the volume holding `path`, the name of its file system
+// and its file system flags, such as `FILE_SUPPORTS_REPARSE_POINTS`.
+pub fn volume_information(path: &Path) -> io::Result<(PathBuf, String, u32)> {
+    // The documented maximum length of a file system name, plus the terminating null character.
+    const FILESYSTEM_NAME_LEN: usize = c::MAX_PATH as usize + 1;
+
+    let path = os_str_to_utf16(path.as_os_str());
+    // The volume path is never longer than the path itself.
+    let mut volume = vec![0u16; path.len().max(c::MAX_PATH as usize)];
+    if unsafe { c::GetVolumePathNameW(path.as_ptr(), volume.as_mut_ptr(), volume.len() as u32) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let mut flags = 0;
+    let mut filesystem = [0u16; FILESYSTEM_NAME_LEN];
+    if unsafe {
+        c::GetVolumeInformationW(
+            volume.as_ptr(),
             null_mut(),
             0,
-            rdb.cast(),
-            c::MAXIMUM_REPARSE_DATA_BUFFER_SIZE,
-            &mut bytes_returned,
             null_mut(),
+            null_mut(),
+            &mut flags,
+            filesystem.as_mut_ptr(),
+            filesystem.len() as u32,
         )
     } == 0
     {
         return Err(io::Error::last_os_error());
     }
-    Ok(())
+    let volume = PathBuf::from(utf16_until_nul(&volume));
+    let filesystem = utf16_until_nul(&filesystem).to_string_lossy().into_owned();
+    Ok((volume, filesystem, flags))
 }
 
-pub fn set_reparse_point(handle: c::HANDLE, rdb: *mut c::REPARSE_DATA_BUFFER, len: u32) -> io::Result<()> {
-    let mut bytes_returned: u32 = 0;
+// Returns the type of the drive holding `path`, such as `DRIVE_REMOTE` for
+// network shares and mapped network drives, as reported by `GetDriveTypeW`.
+pub fn drive_type(path: &Path) -> io::Result<u32> {
+    let path = os_str_to_utf16(path.as_os_str());
+    let mut volume = vec![0u16; path.len().max(c::MAX_PATH as usize)];
+    if unsafe { c::GetVolumePathNameW(path.as_ptr(), volume.as_mut_ptr(), volume.len() as u32) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(unsafe { c::GetDriveTypeW(volume.as_ptr()) })
+}
+
+// Returns the sectors per cluster and the bytes per sector of the volume
+// whose root is `volume`.
+pub fn cluster_geometry(volume: &Path) -> io::Result<(u32, u32)> {
+    let volume = dir_path_to_utf16(volume);
+    let (mut sectors_per_cluster, mut bytes_per_sector) = (0, 0);
     if unsafe {
-        c::DeviceIoControl(
-            handle,
-            c::FSCTL_SET_REPARSE_POINT,
-            rdb.cast(),
-            len,
+        c::GetDiskFreeSpaceW(
+            volume.as_ptr(),
+            &mut sectors_per_cluster,
+            &mut bytes_per_sector,
             null_mut(),
-            0,
-            &mut bytes_returned,
             null_mut(),
         )
     } == 0
     {
         return Err(io::Error::last_os_error());
     }
-    Ok(())
+    Ok((sectors_per_cluster, bytes_per_sector))
 }
 
-// See https://msdn.microsoft.com/en-us/library/windows/desktop/aa364560(v=vs.85).aspx
-pub fn delete_reparse_point(handle: c::HANDLE) -> io::Result<()> {
-    // TODO: Should we use REPARSE_DATA_BUFFER instead?
-    let mut rgdb: c::REPARSE_GUID_DATA_BUFFER = unsafe { zeroed() };
-    rgdb.ReparseTag = c::IO_REPARSE_TAG_MOUNT_POINT;
-    let mut bytes_returned: u32 = 0;
+// Whether the volume whose root is `volume` is a Dev Drive. Fails on Windows
+// versions that do not know about persistent volume states.
+pub fn is_dev_drive(volume: &Path) -> io::Result<bool> {
+    const FILE_SHARE_ALL: u32 = c::FILE_SHARE_READ | c::FILE_SHARE_WRITE | c::FILE_SHARE_DELETE;
+
+    // The control code needs no access rights, so this never conflicts with other handles.
+    let root = OpenOptions::new()
+        .access_mode(0)
+        .share_mode(FILE_SHARE_ALL)
+        .custom_flags(c::FILE_FLAG_BACKUP_SEMANTICS)
+        .open(volume)?;
+    let mut info = c::FILE_FS_PERSISTENT_VOLUME_INFORMATION {
+        VolumeFlags: 0,
+        FlagMask: c::PERSISTENT_VOLUME_STATE_DEV_VOLUME,
+        Version: 1,
+        Reserved: 0,
+    };
+    let len = size_of::<c::FILE_FS_PERSISTENT_VOLUME_INFORMATION>() as u32;
+    device_io_control(
+        root.as_raw_handle() as isize,
+        c::FSCTL_QUERY_PERSISTENT_VOLUME_STATE,
+        addr_of!(info).cast(),
+        len,
+        addr_of_mut!(info).cast(),
+        len,
+    )?;
+    Ok(info.VolumeFlags & c::PERSISTENT_VOLUME_STATE_DEV_VOLUME != 0)
+}
 
+// Whether the directory `path` is case-sensitive, without following it.
+pub fn is_case_sensitive_dir(path: &Path) -> io::Result<bool> {
+    const FILE_SHARE_ALL: u32 = c::FILE_SHARE_READ | c::FILE_SHARE_WRITE | c::FILE_SHARE_DELETE;
+
+    let dir = OpenOptions::new()
+        .access_mode(c::FILE_READ_ATTRIBUTES)
+        .share_mode(FILE_SHARE_ALL)
+        .custom_flags(c::FILE_FLAG_BACKUP_SEMANTICS | c::FILE_FLAG_OPEN_REPARSE_POINT)
+        .open(path)?;
+    let mut flags = 0u32;
     if unsafe {
-        c::DeviceIoControl(
+        c::GetFileInformationByHandleEx(
+            dir.as_raw_handle() as c::HANDLE,
+            c::FILE_CASE_SENSITIVE_INFO_CLASS,
+            addr_of_mut!(flags).cast(),
+            size_of::<u32>() as u32,
+        )
+    } == 0
+    {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(flags & c::FILE_CS_FLAG_CASE_SENSITIVE_DIR != 0)
+}
+
+// Returns the volume serial number and the file ID of `path`. With `follow`,
+// reparse points are resolved and the identity of their final target is returned.
+pub fn file_id(path: &Path, follow: bool) -> io::Result<(u64, u128)> {
+    const FILE_SHARE_ALL: u32 = c::FILE_SHARE_READ | c::FILE_SHARE_WRITE | c::FILE_SHARE_DELETE;
+
+    let flags = c::FILE_FLAG_BACKUP_SEMANTICS | if follow { 0 } else { c::FILE_FLAG_OPEN_REPARSE_POINT };
+    // Querying attributes needs no access rights, so this never conflicts with other handles.
+    let file = OpenOptions::new()
+        .access_mode(0)
+        .share_mode(FILE_SHARE_ALL)
+        .custom_flags(flags)
+        .open(path)?;
+    let handle = file.as_raw_handle() as c::HANDLE;
+    let mut info = MaybeUninit::<c::FILE_ID_INFO>::uninit();
+    if unsafe {
+        c::GetFileInformationByHandleEx(
             handle,
-            c::FSCTL_DELETE_REPARSE_POINT,
-            addr_of_mut!(rgdb).cast(),
-            u32::from(c::REPARSE_GUID_DATA_BUFFER_HEADER_SIZE),
-            null_mut(),
-            0,
-            &mut bytes_returned,
+            c::FileIdInfo,
+            info.as_mut_ptr().cast(),
+            size_of::<c::FILE_ID_INFO>() as u32,
+        )
+    } != 0
+    {
+        // SAFETY: `GetFileInformationByHandleEx` succeeded, so `info` is initialized.
+        let info = unsafe { info.assume_init() };
+        return Ok((info.VolumeSerialNumber, u128::from_le_bytes(info.FileId.Identifier)));
+    }
+    // `FileIdInfo` is not available before Windows 8 and on some file systems,
+    // such as FAT, which only have 64-bit file indices anyway.
+    trace_event!(
+        error = io::Error::last_os_error().raw_os_error(),
+        "falling back to the file index"
+    );
+    let mut info = MaybeUninit::<c::BY_HANDLE_FILE_INFORMATION>::uninit();
+    if unsafe { c::GetFileInformationByHandle(handle, info.as_mut_ptr()) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: `GetFileInformationByHandle` succeeded, so `info` is initialized.
+    let info = unsafe { info.assume_init() };
+    let index = (u64::from(info.nFileIndexHigh) << 32) | u64::from(info.nFileIndexLow);
+    Ok((u64::from(info.dwVolumeSerialNumber), u128::from(index)))
+}
+
+/// Size of the `WIN32_STREAM_ID` header that precedes each stream, up to its name.
+#[cfg(feature = "backup")]
+const WIN32_STREAM_ID_HEADER_SIZE: usize = 20;
+
+// The context of a sequence of `BackupRead` or `BackupWrite` calls on a handle,
+// which these functions allocate on the first call and free when aborted.
+#[cfg(feature = "backup")]
+struct BackupContext {
+    handle: c::HANDLE,
+    context: *mut c_void,
+    write: bool,
+}
+
+#[cfg(feature = "backup")]
+impl BackupContext {
+    // Fills `buf`, returning `false` if there is nothing left to read.
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<bool> {
+        let mut filled = 0;
+        while filled < buf.len() {
+            let mut read = 0;
+            let rest = &mut buf[filled..];
+            if unsafe {
+                c::BackupRead(
+                    self.handle,
+                    rest.as_mut_ptr(),
+                    rest.len() as u32,
+                    &mut read,
+                    c::FALSE,
+                    c::FALSE,
+                    &mut self.context,
+                )
+            } == 0
+            {
+                return Err(io::Error::last_os_error());
+            }
+            if read == 0 {
+                return match filled {
+                    0 => Ok(false),
+                    _ => Err(io::ErrorKind::UnexpectedEof.into()),
+                };
+            }
+            filled += read as usize;
+        }
+        Ok(true)
+    }
+
+    // Skips `len` bytes of the data of the current stream.
+    fn skip(&mut self, len: u64) -> io::Result<()> {
+        let (mut low, mut high) = (0, 0);
+        if unsafe {
+            c::BackupSeek(
+                self.handle,
+                len as u32,
+                (len >> 32) as u32,
+                &mut low,
+                &mut high,
+                &mut self.context,
+            )
+        } == 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        let mut written = 0;
+        if unsafe {
+            c::BackupWrite(
+                self.handle,
+                buf.as_ptr(),
+                buf.len() as u32,
+                &mut written,
+                c::FALSE,
+                c::FALSE,
+                &mut self.context,
+            )
+        } == 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+        if written as usize != buf.len() {
+            return Err(io::ErrorKind::WriteZero.into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "backup")]
+impl Drop for BackupContext {
+    fn drop(&mut self) {
+        if self.context.is_null() {
+            return;
+        }
+        let mut n = 0;
+        unsafe {
+            if self.write {
+                c::BackupWrite(self.handle, null(), 0, &mut n, c::TRUE, c::FALSE, &mut self.context);
+            } else {
+                c::BackupRead(self.handle, null_mut(), 0, &mut n, c::TRUE, c::FALSE, &mut self.context);
+            }
+        }
+    }
+}
+
+// Reads the data of the first stream of kind `stream_id`, such as `BACKUP_REPARSE_DATA`,
+// with `BackupRead`. Returns `None` if `handle` has no such stream.
+#[cfg(feature = "backup")]
+pub fn backup_read_stream(handle: c::HANDLE, stream_id: u32) -> io::Result<Option<Vec<u8>>> {
+    let mut backup = BackupContext {
+        handle,
+        context: null_mut(),
+        write: false,
+    };
+    let mut header = [0u8; WIN32_STREAM_ID_HEADER_SIZE];
+    while backup.read_exact(&mut header)? {
+        let id = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let mut size = [0u8; 8];
+        size.copy_from_slice(&header[8..16]);
+        let size = u64::from_le_bytes(size);
+        let name_size = u32::from_le_bytes([header[16], header[17], header[18], header[19]]);
+        // The name is part of the header, which cannot be skipped.
+        backup.read_exact(&mut vec![0; name_size as usize])?;
+        if id == stream_id {
+            let mut data = vec![0; size as usize];
+            if !backup.read_exact(&mut data)? {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            return Ok(Some(data));
+        }
+        backup.skip(size)?;
+    }
+    Ok(None)
+}
+
+// Writes `data` as an unnamed stream of kind `stream_id` with `BackupWrite`.
+#[cfg(feature = "backup")]
+pub fn backup_write_stream(handle: c::HANDLE, stream_id: u32, data: &[u8]) -> io::Result<()> {
+    let mut backup = BackupContext {
+        handle,
+        context: null_mut(),
+        write: true,
+    };
+    let mut header = [0u8; WIN32_STREAM_ID_HEADER_SIZE];
+    header[..4].copy_from_slice(&stream_id.to_le_bytes());
+    header[8..16].copy_from_slice(&(data.len() as u64).to_le_bytes());
+    backup.write_all(&header)?;
+    backup.write_all(data)
+}
+
+// The parts of a security descriptor that are copied between reparse points.
+const OWNER_AND_DACL: u32 = c::OWNER_SECURITY_INFORMATION | c::DACL_SECURITY_INFORMATION;
+
+// Reads the owner and DACL of `handle` as a self-relative security descriptor.
+pub fn security_descriptor(handle: c::HANDLE) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::<u8>::new();
+    loop {
+        let mut needed = 0;
+        if unsafe {
+            c::GetKernelObjectSecurity(
+                handle,
+                OWNER_AND_DACL,
+                buf.as_mut_ptr().cast(),
+                buf.len() as u32,
+                &mut needed,
+            )
+        } != 0
+        {
+            return Ok(buf);
+        }
+        let e = io::Error::last_os_error();
+        if e.raw_os_error() != Some(c::ERROR_INSUFFICIENT_BUFFER as i32) {
+            return Err(e);
+        }
+        buf.resize(needed as usize, 0);
+    }
+}
+
+// Sets the owner and DACL of `handle` from a security descriptor read by
+// `security_descriptor`. `handle` needs `WRITE_DAC` and `WRITE_OWNER` access,
+// and an owner other than the current user needs `SeRestorePrivilege`.
+pub fn set_security_descriptor(handle: c::HANDLE, descriptor: &mut [u8]) -> io::Result<()> {
+    if unsafe { c::SetKernelObjectSecurity(handle, OWNER_AND_DACL, descriptor.as_mut_ptr().cast()) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+// Converts the security descriptor `sddl` for the duration of `f`.
+fn with_sddl<T, F: FnOnce(c::PSECURITY_DESCRIPTOR) -> io::Result<T>>(sddl: &str, f: F) -> io::Result<T> {
+    let sddl = os_str_to_utf16(OsStr::new(sddl));
+    let mut descriptor = null_mut();
+    if unsafe {
+        c::ConvertStringSecurityDescriptorToSecurityDescriptorW(
+            sddl.as_ptr(),
+            c::SDDL_REVISION_1,
+            &mut descriptor,
             null_mut(),
         )
     } == 0
     {
         return Err(io::Error::last_os_error());
     }
+    let descriptor = scopeguard::guard(descriptor, |descriptor| unsafe {
+        c::LocalFree(descriptor);
+    });
+    f(*descriptor)
+}
+
+// Creates the directory `path` with the security descriptor given in SDDL,
+// such as `D:P(A;OICI;FA;;;SY)`, instead of the one inherited from its parent.
+pub fn create_dir_with_security(path: &Path, sddl: &str) -> io::Result<()> {
+    with_sddl(sddl, |descriptor| {
+        let attributes = c::SECURITY_ATTRIBUTES {
+            nLength: size_of::<c::SECURITY_ATTRIBUTES>() as u32,
+            lpSecurityDescriptor: descriptor,
+            bInheritHandle: c::FALSE,
+        };
+        let path = os_str_to_utf16(path.as_os_str());
+        if unsafe { c::CreateDirectoryW(path.as_ptr(), &attributes) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    })
+}
+
+// Replaces the DACL of `handle` with the one of the security descriptor `sddl`.
+// `handle` needs `WRITE_DAC` access.
+pub fn set_dacl(handle: c::HANDLE, sddl: &str) -> io::Result<()> {
+    with_sddl(sddl, |descriptor| {
+        if unsafe { c::SetKernelObjectSecurity(handle, c::DACL_SECURITY_INFORMATION, descriptor) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    })
+}
+
+// Reads the attributes and timestamps of `handle`.
+pub fn basic_info(handle: c::HANDLE) -> io::Result<c::FILE_BASIC_INFO> {
+    let mut info = MaybeUninit::<c::FILE_BASIC_INFO>::uninit();
+    if unsafe {
+        c::GetFileInformationByHandleEx(
+            handle,
+            c::FileBasicInfo,
+            info.as_mut_ptr().cast(),
+            size_of::<c::FILE_BASIC_INFO>() as u32,
+        )
+    } == 0
+    {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: `GetFileInformationByHandleEx` succeeded, so `info` is initialized.
+    Ok(unsafe { info.assume_init() })
+}
+
+// Sets the attributes and timestamps of `handle`, which requires write access.
+// Zero fields are left unchanged.
+pub fn set_basic_info(handle: c::HANDLE, info: &c::FILE_BASIC_INFO) -> io::Result<()> {
+    if unsafe {
+        c::SetFileInformationByHandle(
+            handle,
+            c::FileBasicInfo,
+            (info as *const c::FILE_BASIC_INFO).cast(),
+            size_of::<c::FILE_BASIC_INFO>() as u32,
+        )
+    } == 0
+    {
+        return Err(io::Error::last_os_error());
+    }
     Ok(())
 }
 
+// Returns the path of the file open as `handle`, in its `\\?\` form.
+pub fn final_path(handle: c::HANDLE) -> io::Result<PathBuf> {
+    let mut buf = vec![0u16; c::MAX_PATH as usize];
+    loop {
+        // SAFETY: `buf` holds `buf.len()` characters.
+        let len = unsafe { c::GetFinalPathNameByHandleW(handle, buf.as_mut_ptr(), buf.len() as u32, 0) } as usize;
+        if len == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if len < buf.len() {
+            buf.truncate(len);
+            return Ok(PathBuf::from(OsString::from_wide(&buf)));
+        }
+        // `len` includes the terminating null character if the buffer is too small.
+        buf.resize(len, 0);
+    }
+}
+
+// Reads the reparse tag of `path` from its directory entry with `FindFirstFileExW`,
+// which requires neither a handle to `path` nor any privilege.
+pub fn find_reparse_tag(path: &Path) -> io::Result<Option<u32>> {
+    let path = os_str_to_utf16(path.as_os_str());
+    unsafe {
+        let mut data: c::WIN32_FIND_DATAW = zeroed();
+        let handle = c::FindFirstFileExW(
+            path.as_ptr(),
+            c::FindExInfoBasic,
+            addr_of_mut!(data).cast(),
+            c::FindExSearchNameMatch,
+            null(),
+            0,
+        );
+        if handle == c::INVALID_HANDLE_VALUE {
+            return Err(io::Error::last_os_error());
+        }
+        c::FindClose(handle);
+        // `dwReserved0` holds the reparse tag only for reparse points.
+        if data.dwFileAttributes & c::FILE_ATTRIBUTE_REPARSE_POINT != 0 {
+            Ok(Some(data.dwReserved0))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+// Lists `dir` with `FindFirstFileExW`, calling `f` with every entry except `.` and `..`.
+pub fn find_entries<F: FnMut(&c::WIN32_FIND_DATAW)>(dir: &Path, mut f: F) -> io::Result<()> {
+    const ERROR_FILE_NOT_FOUND: i32 = 2;
+    const DOT: u16 = b'.' as u16;
+
+    let pattern = os_str_to_utf16(dir.join("*").as_os_str());
+    unsafe {
+        let mut data: c::WIN32_FIND_DATAW = zeroed();
+        let handle = c::FindFirstFileExW(
+            pattern.as_ptr(),
+            c::FindExInfoBasic,
+            addr_of_mut!(data).cast(),
+            c::FindExSearchNameMatch,
+            null(),
+            c::FIND_FIRST_EX_LARGE_FETCH,
+        );
+        if handle == c::INVALID_HANDLE_VALUE {
+            let err = io::Error::last_os_error();
+            // Root directories have no `.` and `..` entries, so they may match nothing.
+            return match err.raw_os_error() {
+                Some(ERROR_FILE_NOT_FOUND) => Ok(()),
+                _ => Err(err),
+            };
+        }
+        let _guard = scopeguard::guard(handle, |handle| {
+            c::FindClose(handle);
+        });
+        loop {
+            if !matches!(data.cFileName, [DOT, 0, ..] | [DOT, DOT, 0, ..]) {
+                f(&data);
+            }
+            if c::FindNextFileW(handle, &mut data) == 0 {
+                let err = io::Error::last_os_error();
+                return match err.raw_os_error() {
+                    Some(e) if e == c::ERROR_NO_MORE_FILES as i32 => Ok(()),
+                    _ => Err(err),
+                };
+            }
+        }
+    }
+}
+
 fn os_str_to_utf16(s: &OsStr) -> Vec<u16> {
     s.encode_wide().chain(std::iter::once(0)).collect()
 }
 
+// Compares two paths the way NTFS compares names by default: ordinally and
+// ignoring case. A trailing backslash is not significant.
+pub fn paths_equal(a: &[u16], b: &[u16]) -> bool {
+    fn trim(path: &[u16]) -> &[u16] {
+        path.strip_suffix(&[u16::from(b'\\')]).unwrap_or(path)
+    }
+    let (a, b) = (trim(a), trim(b));
+    unsafe { c::CompareStringOrdinal(a.as_ptr(), a.len() as i32, b.as_ptr(), b.len() as i32, c::TRUE) == c::CSTR_EQUAL }
+}
+
+// Volume management functions require paths to end with a backslash.
+pub fn dir_path_to_utf16(path: &Path) -> Vec<u16> {
+    let mut wide: Vec<u16> = path.as_os_str().encode_wide().collect();
+    if !matches!(wide.last(), Some(&c) if c == u16::from(b'\\') || c == u16::from(b'/')) {
+        wide.push(u16::from(b'\\'));
+    }
+    wide.push(0);
+    wide
+}
+
+// Converts a NUL-terminated wide string buffer filled by Win32 APIs.
+pub fn utf16_until_nul(buf: &[u16]) -> OsString {
+    let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+    OsString::from_wide(&buf[..len])
+}
+
+// Joins `path` to the absolute `base` and removes `.` and `..` components
+// without consulting the current directory, unlike `get_full_path`.
+pub fn join_lexically(base: &Path, path: &Path) -> io::Result<PathBuf> {
+    let joined = base.join(path);
+    if !joined.is_absolute() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "`base` must be an absolute path",
+        ));
+    }
+    let mut normalized = PathBuf::new();
+    for component in joined.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir | Component::Normal(_) => normalized.push(component),
+            Component::CurDir => {}
+            // Like Windows, stop at the root.
+            Component::ParentDir => {
+                normalized.pop();
+            }
+        }
+    }
+    Ok(normalized)
+}
+
 type MaybeU16 = MaybeUninit<u16>;
 // Returns canonical path without the terminating null character.
 // Ref: rust-lang/rust/blob/master/library/std/src/sys/windows/mod.rs#L198
@@ -201,3 +1200,165 @@ fn maybe_slice_assume_init(s: &[MaybeU16]) -> &[u16] {
     // SAFETY: `MaybeUninit<T>` and T are guaranteed to have the same layout
     unsafe { &*(s as *const [MaybeU16] as *const [u16]) }
 }
+
+// Opens the volume holding `path` for reading its change journal, as
+// `\\?\Volume{GUID}`, so that volumes mounted in folders work too.
+#[cfg(feature = "usn")]
+pub fn open_volume(path: &Path) -> io::Result<File> {
+    // `\\?\Volume{GUID}\` and the terminating null character.
+    const VOLUME_NAME_LEN: usize = 50;
+
+    let path = os_str_to_utf16(path.as_os_str());
+    let mut root = vec![0u16; path.len().max(c::MAX_PATH as usize)];
+    if unsafe { c::GetVolumePathNameW(path.as_ptr(), root.as_mut_ptr(), root.len() as u32) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let mut volume = [0u16; VOLUME_NAME_LEN];
+    if unsafe { c::GetVolumeNameForVolumeMountPointW(root.as_ptr(), volume.as_mut_ptr(), volume.len() as u32) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let volume = utf16_until_nul(&volume);
+    // The volume itself is opened without the trailing backslash, which would open its root directory.
+    let volume = volume.to_string_lossy();
+    OpenOptions::new()
+        .read(true)
+        .share_mode(c::FILE_SHARE_READ | c::FILE_SHARE_WRITE)
+        .open(volume.trim_end_matches('\\'))
+}
+
+// Returns the ID of the change journal of `volume` and the USN of its next record.
+#[cfg(feature = "usn")]
+pub fn query_usn_journal(volume: &File) -> io::Result<(u64, i64)> {
+    let mut journal = MaybeUninit::<c::USN_JOURNAL_DATA_V0>::uninit();
+    device_io_control(
+        volume.as_raw_handle() as isize,
+        c::FSCTL_QUERY_USN_JOURNAL,
+        null(),
+        0,
+        journal.as_mut_ptr().cast(),
+        size_of::<c::USN_JOURNAL_DATA_V0>() as u32,
+    )?;
+    // SAFETY: `FSCTL_QUERY_USN_JOURNAL` initialized the structure.
+    let journal = unsafe { journal.assume_init() };
+    Ok((journal.UsnJournalID, journal.NextUsn))
+}
+
+// Reads the records of the change journal `journal_id` from `start` into
+// `buffer`, with the reasons in `reasons`, and returns the number of bytes
+// written. The buffer starts with the USN to read from next. With `wait`, this
+// blocks until there is at least one record.
+#[cfg(feature = "usn")]
+pub fn read_usn_journal(
+    volume: &File,
+    journal_id: u64,
+    start: i64,
+    reasons: u32,
+    wait: bool,
+    buffer: &mut [u8],
+) -> io::Result<usize> {
+    let input = c::READ_USN_JOURNAL_DATA_V0 {
+        StartUsn: start,
+        ReasonMask: reasons,
+        // Changes made through one handle are reported once, when it is closed.
+        ReturnOnlyOnClose: 1,
+        // Waits without time-out while `BytesToWaitFor` is not zero.
+        Timeout: 0,
+        BytesToWaitFor: wait as u64,
+        UsnJournalID: journal_id,
+    };
+    let len = device_io_control(
+        volume.as_raw_handle() as isize,
+        c::FSCTL_READ_USN_JOURNAL,
+        addr_of!(input).cast(),
+        size_of::<c::READ_USN_JOURNAL_DATA_V0>() as u32,
+        buffer.as_mut_ptr().cast(),
+        buffer.len() as u32,
+    )?;
+    Ok(len as usize)
+}
+
+// Opens the file `file_id` of `volume` without following it if it is a reparse point.
+#[cfg(feature = "usn")]
+pub fn open_file_by_id(volume: &File, file_id: u64) -> io::Result<File> {
+    const FILE_SHARE_ALL: u32 = c::FILE_SHARE_READ | c::FILE_SHARE_WRITE | c::FILE_SHARE_DELETE;
+
+    let descriptor = c::FILE_ID_DESCRIPTOR {
+        dwSize: size_of::<c::FILE_ID_DESCRIPTOR>() as u32,
+        Type: c::FileIdType,
+        Anonymous: c::FILE_ID_DESCRIPTOR_0 { FileId: file_id as i64 },
+    };
+    let handle = unsafe {
+        c::OpenFileById(
+            volume.as_raw_handle() as isize,
+            &descriptor,
+            c::FILE_READ_ATTRIBUTES,
+            FILE_SHARE_ALL,
+            null(),
+            c::FILE_FLAG_OPEN_REPARSE_POINT | c::FILE_FLAG_BACKUP_SEMANTICS,
+        )
+    };
+    if handle == c::INVALID_HANDLE_VALUE {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: `OpenFileById` returned a new handle, which the file now owns.
+    Ok(unsafe { File::from_raw_handle(handle as RawHandle) })
+}
+
+// Opens the directory `path` for watching its entries with overlapped reads.
+#[cfg(feature = "watch")]
+pub fn open_dir_for_watch(path: &Path) -> io::Result<File> {
+    const FILE_SHARE_ALL: u32 = c::FILE_SHARE_READ | c::FILE_SHARE_WRITE | c::FILE_SHARE_DELETE;
+
+    OpenOptions::new()
+        .access_mode(c::FILE_LIST_DIRECTORY)
+        .share_mode(FILE_SHARE_ALL)
+        .custom_flags(c::FILE_FLAG_BACKUP_SEMANTICS | c::FILE_FLAG_OVERLAPPED)
+        .open(path)
+}
+
+// Starts reading changes of the direct entries of the directory `dir` into `buffer`.
+#[cfg(feature = "watch")]
+pub fn read_directory_changes(dir: c::HANDLE, buffer: &mut [u32], overlapped: &mut c::OVERLAPPED) -> io::Result<()> {
+    const FILTER: u32 = c::FILE_NOTIFY_CHANGE_FILE_NAME
+        | c::FILE_NOTIFY_CHANGE_DIR_NAME
+        | c::FILE_NOTIFY_CHANGE_ATTRIBUTES
+        | c::FILE_NOTIFY_CHANGE_LAST_WRITE;
+
+    // SAFETY: `OVERLAPPED` is plain data, for which zeroes are valid.
+    *overlapped = unsafe { zeroed() };
+    // SAFETY: The caller keeps `buffer` and `overlapped` alive until the read completes.
+    if unsafe {
+        c::ReadDirectoryChangesW(
+            dir,
+            buffer.as_mut_ptr().cast(),
+            std::mem::size_of_val(buffer) as u32,
+            c::FALSE,
+            FILTER,
+            null_mut(),
+            overlapped,
+            None,
+        )
+    } == 0
+    {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+// Waits for the overlapped operation on `handle` to complete, and returns the number of bytes transferred.
+#[cfg(feature = "watch")]
+pub fn wait_overlapped(handle: c::HANDLE, overlapped: &c::OVERLAPPED) -> io::Result<u32> {
+    let mut len = 0;
+    if unsafe { c::GetOverlappedResult(handle, overlapped, &mut len, c::TRUE) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(len)
+}
+
+// Cancels the overlapped operation on `handle`, and waits until the system no longer uses its buffers.
+#[cfg(feature = "watch")]
+pub fn cancel_overlapped(handle: c::HANDLE, overlapped: &c::OVERLAPPED) {
+    unsafe { c::CancelIoEx(handle, overlapped) };
+    // The operation completes with `ERROR_OPERATION_ABORTED` once canceled.
+    let _ = wait_overlapped(handle, overlapped);
+}
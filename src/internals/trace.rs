@@ -0,0 +1,27 @@
+// Instrumentation of Win32 calls, compiled to nothing unless the `tracing` feature is enabled.
+
+// Emits a debug event with the same syntax as `tracing::debug!`.
+macro_rules! trace_event {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
+
+// Enters a debug span with the same syntax as `tracing::debug_span!`,
+// which stays entered until the end of the enclosing block.
+macro_rules! trace_span {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!($($arg)*).entered();
+    };
+}
+
+// Reports the result of a change of a junction point to the ETW provider of
+// `crate::etw`. The arguments are only evaluated if the `etw` feature is enabled.
+macro_rules! etw_event {
+    ($operation:expr, $junction:expr, $target:expr, $result:expr) => {
+        #[cfg(feature = "etw")]
+        etw::report($operation, $junction, $target, $result);
+    };
+}
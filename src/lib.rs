@@ -9,18 +9,85 @@ serves as a symbolic link to another directory on the computer. For example,
 if the directory `D:\SYMLINK` specified `C:\WINNT\SYSTEM32` as its target, then
 an application accessing `D:\SYMLINK\DRIVERS` would in reality be accessing
 `C:\WINNT\SYSTEM32\DRIVERS`.
+
+This crate can be depended on unconditionally by cross-platform code. On
+platforms other than Windows, every function touching the file system returns
+an error of kind [`std::io::ErrorKind::Unsupported`]. Parsing and serializing
+reparse point data with [`parse_reparse_data`] and [`ReparseData::to_bytes`]
+works everywhere.
 */
 #![doc(html_root_url = "https://docs.rs/junction/~1")]
-#![cfg(windows)]
 #![deny(rust_2021_compatibility)]
 
+#[cfg(any(feature = "async", feature = "tokio"))]
+#[macro_use]
+mod async_wrappers;
+mod audit;
+#[cfg(feature = "backup")]
+pub mod backup;
+mod batch;
+#[cfg(feature = "camino")]
+pub mod camino;
+#[cfg(feature = "cap-std")]
+pub mod cap_std;
+mod cleanup;
+pub mod consts;
+mod diagnostics;
+mod error;
+#[cfg(feature = "etw")]
+pub mod etw;
+mod file;
+pub mod fs;
+mod identity;
+#[cfg(windows)]
 mod internals;
+mod list;
+pub mod manifest;
+#[cfg(feature = "async")]
+pub mod nonblocking;
+mod ops;
+mod options;
+mod probe;
+#[cfg(feature = "raw")]
+pub mod raw;
+mod reparse;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "tokio")]
+pub mod tokio;
+#[cfg(not(windows))]
+mod unsupported;
+#[cfg(feature = "usn")]
+pub mod usn;
+mod walk;
+#[cfg(feature = "watch")]
+pub mod watch;
+#[cfg(not(windows))]
+use unsupported as internals;
 
-#[cfg(test)]
+#[cfg(all(test, windows))]
 mod tests;
 
-use std::io;
+use std::ffi::{OsStr, OsString};
 use std::path::{Path, PathBuf};
+use std::{fmt, io};
+
+pub use audit::{AuditHook, OperationKind, OperationRecord};
+pub use batch::Batch;
+pub use cleanup::{delete_all, delete_all_and_remove_dirs};
+pub use diagnostics::{debug_dump, explain, Diagnostics, Privilege};
+pub use error::{
+    PolicyViolation, RollbackFailed, TagMismatch, TargetMismatch, UnsupportedFilesystem, UnsupportedTarget,
+    UnsupportedTargetReason,
+};
+pub use file::JunctionFile;
+pub use identity::{file_id, target_file_id, FileId};
+pub use list::{read_dir_no_follow, DirEntry};
+pub use ops::JunctionOps;
+pub use options::{clear_privilege_cache, Options, Policy, PrivilegePolicy, TargetNormalization};
+pub use probe::{can_create_junctions, filesystem_capabilities, Capabilities, FilesystemCapabilities};
+pub use reparse::{parse_reparse_data, Guid, MountPointData, ReparseData, ReparseDump, ReparseTag, SymlinkData};
+pub use walk::{walk, EntryKind, Walk, WalkEntry};
 
 /// Creates a junction point from the specified directory to the specified target directory.
 ///
@@ -28,7 +95,15 @@ use std::path::{Path, PathBuf};
 ///
 /// # Error
 ///
-/// This function may error if the `junction` path already exists.
+/// This function may error if the `junction` path already exists. If the file
+/// system of the volume does not support reparse points, as is the case for
+/// FAT32 and exFAT, the error is of kind [`io::ErrorKind::Unsupported`] and
+/// carries an [`UnsupportedFilesystem`]. Network targets such as `\\server\share`
+/// and targets with names ending in a dot or a space are rejected with an
+/// [`UnsupportedTarget`].
+///
+/// If the directory was created but could not be turned into a junction, it is
+/// removed again before the error is returned.
 ///
 /// # Example
 ///
@@ -37,6 +112,7 @@ use std::path::{Path, PathBuf};
 /// use std::path::Path;
 /// # use std::fs;
 /// # use junction::create;
+/// # #[cfg(windows)]
 /// fn main() -> io::Result<()> {
 ///     let tmpdir = tempfile::tempdir()?;
 ///     let target = tmpdir.path().join("target");
@@ -44,74 +120,1382 @@ use std::path::{Path, PathBuf};
 ///     # fs::create_dir_all(&target)?;
 ///     create(&target, &junction)
 /// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
 /// ```
 pub fn create<P, Q>(target: P, junction: Q) -> io::Result<()>
 where
     P: AsRef<Path>,
     Q: AsRef<Path>,
 {
-    internals::create(target.as_ref(), junction.as_ref())
+    internals::create(target.as_ref(), junction.as_ref(), &Options::new())
 }
 
-/// Deletes a `junction` reparse point from the specified file or directory.
+/// Creates a junction point like [`create`], but only if `target` is an existing directory.
 ///
 /// N.B. Only works on NTFS.
 ///
-/// This function delete the junction point only, leaving the target directory
-/// and its content as is. It does nothing if the `junction` point does not exist.
+/// [`create`] accepts missing targets, as junction points may be created
+/// before their target. This variant checks the target first, for callers to
+/// whom a dangling junction point is a bug. The target itself is checked, not
+/// followed: a target that is a junction point counts as a directory, whether
+/// or not its own target exists. Relative targets are resolved like [`create`]
+/// does. The target could still be removed between the check and the creation.
+///
+/// # Error
+///
+/// This function errors with [`io::ErrorKind::NotFound`] if `target` does not
+/// exist, with [`io::ErrorKind::InvalidInput`] if it is not a directory, and
+/// like [`create`] otherwise.
 ///
 /// # Example
 ///
 /// ```rust
 /// use std::io;
-/// use std::path::Path;
 /// # use std::fs;
-/// # use junction::{create, delete};
+/// # use junction::create_checked;
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     let tmpdir = tempfile::tempdir()?;
+///     let target = tmpdir.path().join("target");
+///     let junction = tmpdir.path().join("junction");
+///     let err = create_checked(&target, &junction).unwrap_err();
+///     assert_eq!(err.kind(), io::ErrorKind::NotFound);
+///     # fs::create_dir_all(&target)?;
+///     create_checked(&target, &junction)
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub fn create_checked<P, Q>(target: P, junction: Q) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    internals::create_checked(target.as_ref(), junction.as_ref(), &Options::new())
+}
+
+/// The paths of a junction point created by [`create_resolved`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedJunction {
+    pub(crate) junction: PathBuf,
+    pub(crate) target: PathBuf,
+    pub(crate) substitute_name: OsString,
+}
+
+impl ResolvedJunction {
+    /// Returns the full path of the junction point, as made absolute by `GetFullPathNameW`.
+    pub fn junction(&self) -> &Path {
+        &self.junction
+    }
+
+    /// Returns the target as [`get_target`] would read it back.
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+
+    /// Returns the substitute name written into the junction point, such as `\??\C:\target`.
+    pub fn substitute_name(&self) -> &OsStr {
+        &self.substitute_name
+    }
+}
+
+/// Creates a junction point like [`create`], and returns the paths it resolved.
+///
+/// N.B. Only works on NTFS.
+///
+/// The target is normalized before it is written, so the junction point may
+/// not refer to it with the spelling given. The returned [`ResolvedJunction`]
+/// tells what was actually written, without resolving the paths a second time.
+///
+/// # Error
+///
+/// This function errors like [`create`].
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use std::fs;
+/// # use junction::create_resolved;
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     let tmpdir = tempfile::tempdir()?;
+///     let target = tmpdir.path().join("target");
+///     let junction = tmpdir.path().join("junction");
+///     # fs::create_dir_all(&target)?;
+///     let resolved = create_resolved(target.join("sub").join(".."), &junction)?;
+///     assert_eq!(resolved.target(), target);
+///     Ok(())
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub fn create_resolved<P, Q>(target: P, junction: Q) -> io::Result<ResolvedJunction>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    internals::create_resolved(target.as_ref(), junction.as_ref(), &Options::new())
+}
+
+/// Creates a junction point like [`create`], but resolves relative `target`
+/// and `junction` paths against the absolute `base` directory.
+///
+/// N.B. Only works on NTFS.
+///
+/// [`create`] resolves relative paths with `GetFullPathNameW`, which consults
+/// the current directory of the process. That is shared by all threads, so
+/// concurrent callers changing it get nondeterministic results. This function
+/// joins the paths to `base` and removes `.` and `..` components itself instead.
+///
+/// # Error
+///
+/// This function errors if `base` is not absolute, and otherwise like [`create`].
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use std::fs;
+/// # use junction::{create_with_base, get_target};
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     let tmpdir = tempfile::tempdir()?;
+///     # fs::create_dir_all(tmpdir.path().join("target"))?;
+///     create_with_base(tmpdir.path(), r"sub\..\target", "junction")?;
+///     assert_eq!(get_target(tmpdir.path().join("junction"))?, tmpdir.path().join("target"));
+///     Ok(())
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub fn create_with_base<B, P, Q>(base: B, target: P, junction: Q) -> io::Result<()>
+where
+    B: AsRef<Path>,
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    internals::create_with_base(base.as_ref(), target.as_ref(), junction.as_ref(), &Options::new())
+}
+
+/// Builds the reparse point data that [`create`] would write for the specified target.
+///
+/// The target is canonicalized the same way as in [`create`], but no file system
+/// object is created or modified. This is useful for tools writing reparse points
+/// through other means, such as `BackupWrite` or archive formats.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use junction::{mount_point_data, parse_reparse_data, ReparseData};
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     let data = mount_point_data(r"C:\foo")?;
+///     match parse_reparse_data(&data)? {
+///         ReparseData::MountPoint(data) => {
+///             assert_eq!(String::from_utf16_lossy(data.substitute_name()), r"\??\C:\foo");
+///         }
+///         other => panic!("not a mount point: {:?}", other),
+///     }
+///     Ok(())
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub fn mount_point_data<P: AsRef<Path>>(target: P) -> io::Result<Vec<u8>> {
+    internals::mount_point_data(target.as_ref())
+}
+
+/// Creates a mount point from the specified directory to the specified volume.
+///
+/// Unlike [`create`], the target is not canonicalized but written verbatim as
+/// `\??\Volume{GUID}\`. `volume` may be a bare GUID (with or without braces),
+/// `Volume{GUID}` or a volume GUID path such as `\\?\Volume{GUID}\`.
+///
+/// N.B. Only works on NTFS.
+///
+/// # Error
+///
+/// This function may error if `volume` is not a volume GUID or
+/// if the `junction` path already exists.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::io;
+/// # use junction::create_to_volume;
+/// fn main() -> io::Result<()> {
+///     create_to_volume(r"\\?\Volume{00000000-0000-0000-0000-000000000000}\", r"C:\mnt\data")
+/// }
+/// ```
+pub fn create_to_volume<S, P>(volume: S, junction: P) -> io::Result<()>
+where
+    S: AsRef<OsStr>,
+    P: AsRef<Path>,
+{
+    internals::create_to_volume(volume.as_ref(), junction.as_ref(), &Options::new())
+}
+
+/// The outcome of [`verify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Verification {
+    /// The junction point exists and points to the expected target.
+    Match,
+    /// The junction point exists but points to another target, which is included.
+    DifferentTarget(PathBuf),
+    /// The path exists but is not a junction point.
+    NotAJunction,
+    /// The path does not exist.
+    Missing,
+}
+
+/// Checks whether `junction` is a junction point to `expected`.
+///
+/// N.B. Only works on NTFS.
+///
+/// Both targets are compared the way NTFS compares names: after making
+/// `expected` absolute, ignoring case and a trailing backslash. `expected` may
+/// also be a verbatim path such as `\\?\Volume{GUID}\` returned by [`get_target`].
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use std::fs;
+/// # use junction::{create, verify, Verification};
+/// # #[cfg(windows)]
 /// fn main() -> io::Result<()> {
 ///     let tmpdir = tempfile::tempdir()?;
 ///     let target = tmpdir.path().join("target");
 ///     let junction = tmpdir.path().join("junction");
 ///     # fs::create_dir_all(&target)?;
+///     assert_eq!(verify(&junction, &target)?, Verification::Missing);
 ///     create(&target, &junction)?;
-///     delete(&junction)
+///     assert_eq!(verify(&junction, &target)?, Verification::Match);
+///     assert_eq!(verify(&target, &target)?, Verification::NotAJunction);
+///     Ok(())
 /// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
 /// ```
-pub fn delete<P: AsRef<Path>>(junction: P) -> io::Result<()> {
-    internals::delete(junction.as_ref())
+pub fn verify<P: AsRef<Path>, Q: AsRef<Path>>(junction: P, expected: Q) -> io::Result<Verification> {
+    internals::verify(junction.as_ref(), expected.as_ref(), &Options::new())
 }
 
-/// Determines whether the specified path exists and refers to a junction point.
+/// Gets the reparse tag of `path`, or `None` if it is not a reparse point.
+///
+/// The tag is read from the directory entry of `path` with `FindFirstFileExW`,
+/// without opening `path` itself. This works in directories where opening
+/// reparse points is denied, for example without `SeBackupPrivilege`, as long
+/// as the parent directory can be listed. Junction points and volume mount
+/// points have the tag `0xA000_0003`, symbolic links `0xA000_000C`.
+///
+/// # Error
+///
+/// This function errors if `path` does not exist or its parent directory cannot be listed.
 ///
 /// # Example
 ///
 /// ```rust
 /// use std::io;
-/// # use junction::exists;
+/// # use std::fs;
+/// # use junction::{create, reparse_tag};
+/// # #[cfg(windows)]
 /// fn main() -> io::Result<()> {
-///     # #[cfg(feature = "unstable_admin")]
-///     assert!(exists(r"C:\Users\Default User")?);
+///     let tmpdir = tempfile::tempdir()?;
+///     let target = tmpdir.path().join("target");
+///     let junction = tmpdir.path().join("junction");
+///     # fs::create_dir_all(&target)?;
+///     create(&target, &junction)?;
+///     assert_eq!(reparse_tag(&junction)?, Some(0xA000_0003));
+///     assert_eq!(reparse_tag(&target)?, None);
 ///     Ok(())
 /// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
 /// ```
-pub fn exists<P: AsRef<Path>>(junction: P) -> io::Result<bool> {
-    internals::exists(junction.as_ref())
+pub fn reparse_tag<P: AsRef<Path>>(path: P) -> io::Result<Option<u32>> {
+    internals::reparse_tag(path.as_ref())
 }
 
-/// Gets the target of the specified junction point.
+/// What [`metadata`] found at a path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JunctionMetadata {
+    pub(crate) exists: bool,
+    pub(crate) tag: Option<u32>,
+    pub(crate) target: Option<PathBuf>,
+    pub(crate) kind: Option<JunctionKind>,
+    pub(crate) target_drive_type: Option<DriveType>,
+}
+
+impl JunctionMetadata {
+    /// Returns whether the path exists, which is `true` for junction points
+    /// whose target is missing.
+    pub fn exists(&self) -> bool {
+        self.exists
+    }
+
+    /// Returns whether the path is a junction point or a volume mount point,
+    /// like [`exists`].
+    pub fn is_junction(&self) -> bool {
+        self.target.is_some()
+    }
+
+    /// Returns the reparse tag, or `None` if the path is not a reparse point.
+    pub fn tag(&self) -> Option<u32> {
+        self.tag
+    }
+
+    /// Returns the target like [`get_target`], or `None` if the path is not a junction point.
+    pub fn target(&self) -> Option<&Path> {
+        self.target.as_deref()
+    }
+
+    /// Returns the kind like [`get_kind`], or `None` if the path is not a junction point.
+    pub fn kind(&self) -> Option<JunctionKind> {
+        self.kind
+    }
+
+    /// Returns the type of the drive holding the target, or `None` if the path
+    /// is not a junction point or the drive type cannot be determined.
+    ///
+    /// The target is looked up as written, without following further junction
+    /// points. A missing target is looked up on the drive it would be on.
+    pub fn target_drive_type(&self) -> Option<DriveType> {
+        self.target_drive_type
+    }
+}
+
+/// The type of a drive, as reported by [`JunctionMetadata::target_drive_type`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum DriveType {
+    /// Removable media, such as a USB flash drive or a memory card.
+    Removable,
+    /// A fixed disk.
+    Fixed,
+    /// A network share or a mapped network drive.
+    Remote,
+    /// An optical drive.
+    CdRom,
+    /// A RAM disk, whose contents are lost on restart.
+    RamDisk,
+}
+
+impl DriveType {
+    // Converts a result of `GetDriveTypeW`. `DRIVE_UNKNOWN` and `DRIVE_NO_ROOT_DIR`
+    // give `None`.
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub(crate) fn from_raw(drive_type: u32) -> Option<Self> {
+        match drive_type {
+            2 => Some(DriveType::Removable),
+            3 => Some(DriveType::Fixed),
+            4 => Some(DriveType::Remote),
+            5 => Some(DriveType::CdRom),
+            6 => Some(DriveType::RamDisk),
+            _ => None,
+        }
+    }
+}
+
+/// Checks whether `path` exists, whether it is a junction point and where it
+/// points to, all at once.
 ///
 /// N.B. Only works on NTFS.
 ///
+/// Unlike calling [`exists`] and [`get_target`] in turn, the path is opened
+/// and its reparse data read only once, so the answers are consistent with
+/// each other. A missing path or a path that is not a reparse point is not an error.
+///
 /// # Example
 ///
 /// ```rust
 /// use std::io;
-/// # use junction::get_target;
+/// # use std::fs;
+/// # use junction::{create, metadata};
+/// # #[cfg(windows)]
 /// fn main() -> io::Result<()> {
-///     # #[cfg(feature = "unstable_admin")]
-///     assert_eq!(get_target(r"C:\Users\Default User")?.to_str(), Some(r"C:\Users\Default"));
+///     let tmpdir = tempfile::tempdir()?;
+///     let target = tmpdir.path().join("target");
+///     let junction = tmpdir.path().join("junction");
+///     # fs::create_dir_all(&target)?;
+///     assert!(!metadata(&junction)?.exists());
+///     create(&target, &junction)?;
+///     let metadata = metadata(&junction)?;
+///     assert!(metadata.is_junction());
+///     assert_eq!(metadata.target(), Some(target.as_path()));
 ///     Ok(())
 /// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
 /// ```
-pub fn get_target<P: AsRef<Path>>(junction: P) -> io::Result<PathBuf> {
-    internals::get_target(junction.as_ref())
+pub fn metadata<P: AsRef<Path>>(path: P) -> io::Result<JunctionMetadata> {
+    internals::metadata(path.as_ref(), &Options::new())
+}
+
+/// Creates a junction point, unless the same junction point already exists.
+///
+/// N.B. Only works on NTFS.
+///
+/// Returns `true` if the junction was created, and `false` if `junction` already
+/// was a junction point to `target`. Targets are compared after making them
+/// absolute, ignoring case and a trailing backslash.
+///
+/// # Error
+///
+/// This function errors like [`create`] if `junction` exists but is something
+/// else, such as a junction point to another target.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use std::fs;
+/// # use junction::create_idempotent;
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     let tmpdir = tempfile::tempdir()?;
+///     let target = tmpdir.path().join("target");
+///     let junction = tmpdir.path().join("junction");
+///     # fs::create_dir_all(&target)?;
+///     assert!(create_idempotent(&target, &junction)?);
+///     assert!(!create_idempotent(&target, &junction)?);
+///     Ok(())
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub fn create_idempotent<P: AsRef<Path>, Q: AsRef<Path>>(target: P, junction: Q) -> io::Result<bool> {
+    internals::create_idempotent(target.as_ref(), junction.as_ref(), &Options::new())
+}
+
+/// Checks whether the junction points `a` and `b` have the same target.
+///
+/// N.B. Only works on NTFS.
+///
+/// The targets are compared the way NTFS compares names, ignoring case and a
+/// trailing backslash. Targets that are spelled differently but resolve to the
+/// same directory are only recognized with [`Options::compare_file_ids`].
+///
+/// # Error
+///
+/// This function errors if `a` or `b` does not exist or is not a junction point.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use std::fs;
+/// # use junction::{create, same_target};
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     let tmpdir = tempfile::tempdir()?;
+///     let target = tmpdir.path().join("target");
+///     let a = tmpdir.path().join("a");
+///     let b = tmpdir.path().join("b");
+///     # fs::create_dir_all(&target)?;
+///     create(&target, &a)?;
+///     create(&target, &b)?;
+///     assert!(same_target(&a, &b)?);
+///     Ok(())
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub fn same_target<P: AsRef<Path>, Q: AsRef<Path>>(a: P, b: Q) -> io::Result<bool> {
+    internals::same_target(a.as_ref(), b.as_ref(), &Options::new())
+}
+
+/// Creates a junction point at `dst` with the same reparse data as the junction point `src`.
+///
+/// N.B. Only works on NTFS.
+///
+/// The reparse data is copied byte for byte, so the substitute name and the
+/// print name are reproduced exactly, even those that [`create`] would not
+/// write, such as relative or non-normalized targets. Like [`create`], `dst`
+/// must not exist yet unless allowed by the [`Options`].
+///
+/// # Error
+///
+/// This function errors if `src` does not exist or is not a junction point.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use std::fs;
+/// # use junction::{copy_junction, create, get_target};
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     let tmpdir = tempfile::tempdir()?;
+///     let target = tmpdir.path().join("target");
+///     let junction = tmpdir.path().join("junction");
+///     let copy = tmpdir.path().join("copy");
+///     # fs::create_dir_all(&target)?;
+///     create(&target, &junction)?;
+///     copy_junction(&junction, &copy)?;
+///     assert_eq!(get_target(&copy)?, target);
+///     Ok(())
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub fn copy_junction<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> io::Result<()> {
+    internals::copy_junction(src.as_ref(), dst.as_ref(), &Options::new()).map(drop)
+}
+
+/// Moves the junction point `src` to `dst`, also across volumes.
+///
+/// Within a volume, the junction point is renamed like with [`std::fs::rename`].
+/// Across volumes, where renaming is not possible, the junction point is
+/// recreated at `dst` with the same reparse data as [`copy_junction`] does,
+/// along with the attributes and timestamps of `src`, and then `src` is deleted.
+/// The target is never followed, so its content is neither copied nor removed.
+///
+/// # Error
+///
+/// This function errors if `src` does not exist or is not a junction point.
+/// If the attributes cannot be copied, or `src` cannot be deleted afterwards,
+/// `dst` is removed again and `src` is kept. The move across volumes is not
+/// atomic though: another process may see the junction point in both places,
+/// and a failure to undo the copy leaves both behind.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use std::fs;
+/// # use junction::{create, get_target, move_junction};
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     let tmpdir = tempfile::tempdir()?;
+///     let target = tmpdir.path().join("target");
+///     let junction = tmpdir.path().join("junction");
+///     let moved = tmpdir.path().join("moved");
+///     # fs::create_dir_all(&target)?;
+///     create(&target, &junction)?;
+///     move_junction(&junction, &moved)?;
+///     assert_eq!(get_target(&moved)?, target);
+///     assert!(!junction.exists());
+///     Ok(())
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub fn move_junction<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> io::Result<()> {
+    internals::move_junction(src.as_ref(), dst.as_ref(), &Options::new()).map(drop)
+}
+
+/// Changes the target of an existing junction point in place.
+///
+/// N.B. Only works on NTFS.
+///
+/// The reparse data is replaced in a single step, so readers never observe a
+/// missing or empty directory, unlike with [`delete`] followed by [`create`].
+///
+/// # Error
+///
+/// This function errors if `junction` does not exist or is not a junction point.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use std::fs;
+/// # use junction::{create, get_target, set_target};
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     let tmpdir = tempfile::tempdir()?;
+///     let v1 = tmpdir.path().join("v1");
+///     let v2 = tmpdir.path().join("v2");
+///     let current = tmpdir.path().join("current");
+///     # fs::create_dir_all(&v1)?;
+///     # fs::create_dir_all(&v2)?;
+///     create(&v1, &current)?;
+///     set_target(&current, &v2)?;
+///     assert_eq!(get_target(&current)?, v2);
+///     Ok(())
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub fn set_target<P: AsRef<Path>, Q: AsRef<Path>>(junction: P, target: Q) -> io::Result<()> {
+    internals::set_target(junction.as_ref(), target.as_ref(), &Options::new())
+}
+
+/// Turns an existing junction point into a directory symbolic link to the same target, in place.
+///
+/// N.B. Only works on NTFS, and creating symbolic links requires the
+/// `SeCreateSymbolicLinkPrivilege` privilege, which administrators hold.
+///
+/// Unlike [`delete`] followed by [`std::os::windows::fs::symlink_dir`], the
+/// directory entry is kept along with its attributes, timestamps and security
+/// descriptor. Only its reparse data is replaced. As the reparse tag of a
+/// reparse point cannot be changed, the junction point is deleted just before
+/// the symbolic link is set, through the same handle. If setting the symbolic
+/// link fails, the junction point is restored.
+///
+/// # Error
+///
+/// This function errors if `junction` does not exist or is not a junction point.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::io;
+/// use junction::convert_to_symlink;
+///
+/// fn main() -> io::Result<()> {
+///     convert_to_symlink(r"C:\workspace\current")?;
+///     Ok(())
+/// }
+/// ```
+pub fn convert_to_symlink<P: AsRef<Path>>(junction: P) -> io::Result<()> {
+    internals::convert_to_symlink(junction.as_ref(), &Options::new()).map(drop)
+}
+
+/// Turns an existing directory symbolic link into a junction point to the same target, in place.
+///
+/// N.B. Only works on NTFS.
+///
+/// This is the reverse of [`convert_to_symlink`], for junction points work for
+/// users without the privilege to create symbolic links. A relative symbolic
+/// link target is resolved against the directory containing the link, and the
+/// junction point gets the resulting absolute target. The directory entry is
+/// kept along with its attributes, and the symbolic link is restored if the
+/// junction point cannot be set.
+///
+/// # Error
+///
+/// This function errors if `symlink` does not exist, is not a symbolic link, or
+/// is a file symbolic link. It also errors if the target cannot be the target of
+/// a junction point, such as a UNC path by default; see [`Options::allow_unc_targets`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::io;
+/// use junction::convert_to_junction;
+///
+/// fn main() -> io::Result<()> {
+///     convert_to_junction(r"C:\workspace\current")?;
+///     Ok(())
+/// }
+/// ```
+pub fn convert_to_junction<P: AsRef<Path>>(symlink: P) -> io::Result<()> {
+    internals::convert_to_junction(symlink.as_ref(), &Options::new()).map(drop)
+}
+
+/// Deletes a `junction` reparse point from the specified file or directory.
+///
+/// N.B. Only works on NTFS.
+///
+/// This function delete the junction point only, leaving the target directory
+/// and its content as is. It does nothing if the `junction` point does not exist.
+///
+/// # Error
+///
+/// This function errors with a [`TagMismatch`] payload if `junction` is
+/// another kind of reparse point, such as a symbolic link, which is left as is.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// use std::path::Path;
+/// # use std::fs;
+/// # use junction::{create, delete};
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     let tmpdir = tempfile::tempdir()?;
+///     let target = tmpdir.path().join("target");
+///     let junction = tmpdir.path().join("junction");
+///     # fs::create_dir_all(&target)?;
+///     create(&target, &junction)?;
+///     delete(&junction)
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub fn delete<P: AsRef<Path>>(junction: P) -> io::Result<()> {
+    internals::delete(junction.as_ref(), &Options::new())
+}
+
+/// Deletes the junction point `name` in the open directory `dir`.
+///
+/// N.B. Only works on NTFS.
+///
+/// The junction point is opened with `NtCreateFile` relative to the handle of
+/// `dir`, so it is looked up in that very directory even if the directory or
+/// one of its parents is renamed concurrently. Cleanup code running with more
+/// rights than the owner of the parent directories thus cannot be redirected to
+/// another junction point. Unlike [`delete`], no privileges are adjusted.
+///
+/// # Error
+///
+/// This function errors with [`io::ErrorKind::InvalidInput`] if `name` is not a
+/// single file name, if `name` does not exist, and like [`delete`] otherwise.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// use std::fs::{self, File};
+/// # use junction::{create, delete_at};
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     let tmpdir = tempfile::tempdir()?;
+///     let target = tmpdir.path().join("target");
+///     # fs::create_dir_all(&target)?;
+///     create(&target, tmpdir.path().join("junction"))?;
+///     let dir = File::open(tmpdir.path())?;
+///     delete_at(&dir, "junction")
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub fn delete_at<P: AsRef<Path>>(dir: &std::fs::File, name: P) -> io::Result<()> {
+    internals::delete_in(dir, name.as_ref(), &Options::new())
+}
+
+/// Deletes a `junction` reparse point and removes the directory left behind.
+///
+/// N.B. Only works on NTFS.
+///
+/// This is [`delete`] followed by [`fs::remove_dir`](std::fs::remove_dir). The
+/// target directory and its content are left as is.
+///
+/// # Error
+///
+/// This function errors like [`delete`]. If something was written into the
+/// directory after the junction point was deleted, the directory and its new
+/// content are kept, and the error of `remove_dir` is returned.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use std::fs;
+/// # use junction::{create, delete_and_remove_dir};
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     let tmpdir = tempfile::tempdir()?;
+///     let target = tmpdir.path().join("target");
+///     let junction = tmpdir.path().join("junction");
+///     # fs::create_dir_all(&target)?;
+///     create(&target, &junction)?;
+///     delete_and_remove_dir(&junction)?;
+///     assert!(!junction.exists());
+///     assert!(target.exists());
+///     Ok(())
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub fn delete_and_remove_dir<P: AsRef<Path>>(junction: P) -> io::Result<()> {
+    Options::new().delete_and_remove_dir(junction)
+}
+
+/// Deletes a `junction` reparse point, but only if it points to `expected`.
+///
+/// N.B. Only works on NTFS.
+///
+/// Targets are compared like in [`verify`]. The target is checked on the same
+/// handle that the junction point is deleted from, so that a junction point
+/// retargeted concurrently is never deleted by mistake.
+///
+/// # Error
+///
+/// This function errors with a [`TargetMismatch`] payload if `junction` points
+/// elsewhere, and like [`delete`] otherwise.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use std::fs;
+/// # use junction::{create, delete_if_target, TargetMismatch};
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     let tmpdir = tempfile::tempdir()?;
+///     let target = tmpdir.path().join("target");
+///     let other = tmpdir.path().join("other");
+///     let junction = tmpdir.path().join("junction");
+///     # fs::create_dir_all(&target)?;
+///     create(&target, &junction)?;
+///     let err = delete_if_target(&junction, &other).unwrap_err();
+///     assert!(err.get_ref().unwrap().is::<TargetMismatch>());
+///     delete_if_target(&junction, &target)
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub fn delete_if_target<P: AsRef<Path>, Q: AsRef<Path>>(junction: P, expected: Q) -> io::Result<()> {
+    internals::delete_if_target(junction.as_ref(), expected.as_ref(), &Options::new())
+}
+
+/// The outcome of [`delete_if_target_is`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum DeleteOutcome {
+    /// The junction point pointed to the expected target, and was deleted.
+    Deleted,
+    /// The junction point points to another target, which is included. It was
+    /// left in place.
+    DifferentTarget(PathBuf),
+}
+
+/// Deletes a `junction` reparse point if it points to `expected`, and tells
+/// whether it did.
+///
+/// N.B. Only works on NTFS.
+///
+/// This is [`delete_if_target`], with a junction point pointing elsewhere
+/// reported as [`DeleteOutcome::DifferentTarget`] instead of as an error.
+///
+/// # Error
+///
+/// This function errors like [`delete`].
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use std::fs;
+/// # use junction::{create, delete_if_target_is, DeleteOutcome};
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     let tmpdir = tempfile::tempdir()?;
+///     let target = tmpdir.path().join("target");
+///     let other = tmpdir.path().join("other");
+///     let junction = tmpdir.path().join("junction");
+///     # fs::create_dir_all(&target)?;
+///     create(&target, &junction)?;
+///     assert_eq!(delete_if_target_is(&junction, &other)?, DeleteOutcome::DifferentTarget(target.clone()));
+///     assert_eq!(delete_if_target_is(&junction, &target)?, DeleteOutcome::Deleted);
+///     Ok(())
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub fn delete_if_target_is<P: AsRef<Path>, Q: AsRef<Path>>(junction: P, expected: Q) -> io::Result<DeleteOutcome> {
+    internals::delete_if_target_is(junction.as_ref(), expected.as_ref(), &Options::new())
+}
+
+/// Determines whether the specified path exists and refers to a junction point.
+///
+/// The junction point is not followed, so its target does not need to exist.
+///
+/// # Error
+///
+/// This function errors with `ERROR_NOT_A_REPARSE_POINT` if `junction` is a
+/// plain file or directory, unless [`Options::lenient_exists`] is set.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use junction::exists;
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     # #[cfg(feature = "unstable_admin")]
+///     assert!(exists(r"C:\Users\Default User")?);
+///     Ok(())
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub fn exists<P: AsRef<Path>>(junction: P) -> io::Result<bool> {
+    internals::exists(junction.as_ref(), &Options::new())
+}
+
+/// Gets the target of the specified junction point.
+///
+/// N.B. Only works on NTFS.
+///
+/// The junction point is not followed, so its target does not need to exist.
+/// This allows reading junction points from Volume Shadow Copy snapshots, such
+/// as `\\?\GLOBALROOT\Device\HarddiskVolumeShadowCopy1\dir\junction`. Their
+/// targets are stored as is, so they refer to the live volume, not the snapshot.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use junction::get_target;
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     # #[cfg(feature = "unstable_admin")]
+///     assert_eq!(get_target(r"C:\Users\Default User")?.to_str(), Some(r"C:\Users\Default"));
+///     Ok(())
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub fn get_target<P: AsRef<Path>>(junction: P) -> io::Result<PathBuf> {
+    internals::get_target(junction.as_ref(), &Options::new())
+}
+
+/// Gets the target of the specified junction point into `buf`, like [`get_target`].
+///
+/// `buf` is cleared and used as the reparse data buffer, then holds the target
+/// as UTF-16, without a terminating null character. Reusing the same buffer
+/// across calls avoids allocating for every junction, which matters when
+/// resolving many of them.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use std::fs;
+/// # use junction::{create, get_target_into};
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     use std::ffi::OsString;
+///     use std::os::windows::ffi::OsStringExt;
+///
+///     let tmpdir = tempfile::tempdir()?;
+///     let target = tmpdir.path().join("target");
+///     # fs::create_dir_all(&target)?;
+///     let mut buf = Vec::new();
+///     for name in ["a", "b"] {
+///         let junction = tmpdir.path().join(name);
+///         create(&target, &junction)?;
+///         let wide = get_target_into(&junction, &mut buf)?;
+///         assert_eq!(OsString::from_wide(wide), target.as_os_str());
+///     }
+///     Ok(())
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub fn get_target_into<P: AsRef<Path>>(junction: P, buf: &mut Vec<u16>) -> io::Result<&[u16]> {
+    internals::get_target_into(junction.as_ref(), buf, &Options::new())
+}
+
+/// Finds the first component of `path` that is a junction point or a volume mount point.
+///
+/// Returns the index of that component in [`Path::components`], or `None` if
+/// `path` does not pass through any. Each prefix of `path` is checked with
+/// [`reparse_tag`], so no junction point is opened or followed. Components after
+/// the first one that does not exist are not checked, as they cannot exist either.
+///
+/// Components are checked as written: `..` is not resolved, so a junction
+/// point followed by `..` is reported even though Windows would not pass through it.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use std::fs;
+/// # use junction::{create, junction_boundary};
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     let tmpdir = tempfile::tempdir()?;
+///     let target = tmpdir.path().join("target");
+///     let junction = tmpdir.path().join("junction");
+///     # fs::create_dir_all(&target)?;
+///     create(&target, &junction)?;
+///     let path = junction.join("file.txt");
+///     let index = junction_boundary(&path)?.unwrap();
+///     assert_eq!(path.components().nth(index).unwrap().as_os_str(), "junction");
+///     assert_eq!(junction_boundary(target.join("file.txt"))?, None);
+///     Ok(())
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub fn junction_boundary<P: AsRef<Path>>(path: P) -> io::Result<Option<usize>> {
+    let mut prefix = PathBuf::new();
+    for (index, component) in path.as_ref().components().enumerate() {
+        prefix.push(component);
+        if let std::path::Component::Normal(_) = component {
+            match internals::reparse_tag(&prefix) {
+                Ok(Some(reparse::IO_REPARSE_TAG_MOUNT_POINT)) => return Ok(Some(index)),
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Splits `path` after its first junction point or volume mount point.
+///
+/// Returns the path up to and including the junction point, and the rest of
+/// the path below it, or `None` if `path` does not pass through any, as found by
+/// [`junction_boundary`]. Joining the rest to the [target](get_target) of the
+/// junction point gives the path that Windows resolves `path` to, up to later
+/// junction points, without canonicalizing it.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use std::fs;
+/// # use std::path::Path;
+/// # use junction::{create, get_target, split_at_junction};
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     let tmpdir = tempfile::tempdir()?;
+///     let target = tmpdir.path().join("target");
+///     let junction = tmpdir.path().join("junction");
+///     # fs::create_dir_all(&target)?;
+///     create(&target, &junction)?;
+///     let (prefix, rest) = split_at_junction(junction.join("dir").join("file.txt"))?.unwrap();
+///     assert_eq!(prefix, junction);
+///     assert_eq!(rest, Path::new("dir").join("file.txt"));
+///     assert_eq!(get_target(&prefix)?.join(rest), target.join("dir").join("file.txt"));
+///     Ok(())
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub fn split_at_junction<P: AsRef<Path>>(path: P) -> io::Result<Option<(PathBuf, PathBuf)>> {
+    let path = path.as_ref();
+    Ok(junction_boundary(path)?.map(|index| {
+        let mut components = path.components();
+        let prefix = components.by_ref().take(index + 1).collect();
+        (prefix, components.collect())
+    }))
+}
+
+/// The kind of a mount point reparse point.
+///
+/// Directory junctions and volume mount points share the same reparse tag,
+/// but they differ in what their target refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum JunctionKind {
+    /// A directory junction, which targets another directory.
+    Directory,
+    /// A volume mount point, which targets a volume GUID path such as `\??\Volume{GUID}\`.
+    VolumeMountPoint,
+    /// A junction whose target is stored as an NT object manager path, such as
+    /// `\Device\HarddiskVolume2\dir`, rather than a DOS path.
+    ///
+    /// [`get_target`] returns such targets in the Win32 form
+    /// `\\?\GLOBALROOT\Device\HarddiskVolume2\dir`, which [`create`] accepts too.
+    DevicePath,
+}
+
+impl JunctionKind {
+    // Classifies a mount point by its raw substitute name.
+    pub(crate) fn of_substitute_name(name: &[u16]) -> Self {
+        fn starts_with(name: &[u16], prefix: &str) -> bool {
+            let prefix: Vec<u16> = prefix.encode_utf16().collect();
+            name.starts_with(&prefix)
+        }
+        if starts_with(name, r"\??\Volume{") {
+            JunctionKind::VolumeMountPoint
+        } else if starts_with(name, r"\") && !starts_with(name, r"\??\") {
+            JunctionKind::DevicePath
+        } else {
+            JunctionKind::Directory
+        }
+    }
+}
+
+/// Gets the kind of the specified junction point.
+///
+/// N.B. Only works on NTFS.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use junction::{get_kind, JunctionKind};
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     # #[cfg(feature = "unstable_admin")]
+///     assert_eq!(get_kind(r"C:\Users\Default User")?, JunctionKind::Directory);
+///     Ok(())
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub fn get_kind<P: AsRef<Path>>(junction: P) -> io::Result<JunctionKind> {
+    internals::get_kind(junction.as_ref(), &Options::new())
+}
+
+/// What a reparse point is, as returned by [`kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum ReparseKind {
+    /// A junction point or volume mount point.
+    Junction(JunctionKind),
+    /// A symbolic link to a directory.
+    DirSymlink,
+    /// A symbolic link to a file.
+    FileSymlink,
+    /// Any other reparse point, with its raw tag, which [`ReparseTag`] classifies further.
+    Other(u32),
+}
+
+/// Classifies the specified path without following it.
+///
+/// Returns `None` for ordinary files and directories, and what the reparse
+/// point is otherwise. Symbolic links are told apart by whether they were
+/// created for a directory, which does not depend on their target.
+///
+/// # Error
+///
+/// This function errors if `path` does not exist.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use junction::{kind, JunctionKind, ReparseKind};
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     assert_eq!(kind(r"C:\Windows")?, None);
+///     # #[cfg(feature = "unstable_admin")]
+///     assert_eq!(
+///         kind(r"C:\Users\Default User")?,
+///         Some(ReparseKind::Junction(JunctionKind::Directory))
+///     );
+///     Ok(())
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub fn kind<P: AsRef<Path>>(path: P) -> io::Result<Option<ReparseKind>> {
+    internals::kind(path.as_ref(), &Options::new())
+}
+
+/// The contents of a Windows Store app execution alias.
+///
+/// App execution aliases, such as `python.exe` in `%LOCALAPPDATA%\Microsoft\WindowsApps`,
+/// are `IO_REPARSE_TAG_APPEXECLINK` reparse points that launch a packaged application.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AppExecLink {
+    package_id: OsString,
+    entry_point: OsString,
+    target: PathBuf,
+}
+
+impl AppExecLink {
+    /// Returns the package family name of the application, such as
+    /// `PythonSoftwareFoundation.Python.3.12_qbz5n2kfra8p0`.
+    pub fn package_id(&self) -> &OsStr {
+        &self.package_id
+    }
+
+    /// Returns the application user model id of the entry point being launched.
+    pub fn entry_point(&self) -> &OsStr {
+        &self.entry_point
+    }
+
+    /// Returns the path of the executable that is actually launched.
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+}
+
+/// Reads the specified Windows Store app execution alias.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::io;
+/// # use junction::get_app_exec_link;
+/// fn main() -> io::Result<()> {
+///     let link = get_app_exec_link(r"C:\Users\Default\AppData\Local\Microsoft\WindowsApps\python.exe")?;
+///     println!("{}", link.target().display());
+///     Ok(())
+/// }
+/// ```
+pub fn get_app_exec_link<P: AsRef<Path>>(path: P) -> io::Result<AppExecLink> {
+    internals::get_app_exec_link(path.as_ref(), &Options::new())
+}
+
+/// Gets the target of the specified WSL symbolic link.
+///
+/// Symbolic links created from within WSL are stored as `IO_REPARSE_TAG_LX_SYMLINK`
+/// reparse points, which neither [`std::fs::read_link`] nor [`get_target`] can interpret.
+/// The returned target is the POSIX path as seen from WSL, such as `../lib/foo.so` or
+/// `/usr/bin/python3`.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::io;
+/// # use junction::get_lx_symlink;
+/// fn main() -> io::Result<()> {
+///     println!("{}", get_lx_symlink(r"C:\wsl\link")?);
+///     Ok(())
+/// }
+/// ```
+pub fn get_lx_symlink<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    internals::get_lx_symlink(path.as_ref(), &Options::new())
+}
+
+/// Reads the raw reparse point data of the specified file or directory.
+///
+/// Returns the reparse tag, the GUID of the reparse point owner and the
+/// tag-specific data following the reparse data header. Only non-Microsoft
+/// reparse points (with the high bit of the tag cleared) carry a GUID.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io;
+/// # use junction::read_reparse_raw;
+/// # #[cfg(windows)]
+/// fn main() -> io::Result<()> {
+///     # #[cfg(feature = "unstable_admin")]
+///     # {
+///     let (tag, guid, data) = read_reparse_raw(r"C:\Users\Default User")?;
+///     assert_eq!(tag, 0xA000_0003);
+///     assert_eq!(guid, None);
+///     assert!(!data.is_empty());
+///     # }
+///     Ok(())
+/// }
+/// # #[cfg(not(windows))]
+/// # fn main() {}
+/// ```
+pub fn read_reparse_raw<P: AsRef<Path>>(path: P) -> io::Result<(u32, Option<Guid>, Vec<u8>)> {
+    internals::read_reparse_raw(path.as_ref(), &Options::new())
+}
+
+/// Sets the raw reparse point data of the specified existing file or directory.
+///
+/// `guid` must be `Some` exactly for non-Microsoft reparse tags, in which case
+/// the data is written as a `REPARSE_GUID_DATA_BUFFER`. Any existing reparse
+/// point on `path` must have the same tag (and GUID).
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::io;
+/// # use junction::{write_reparse_raw, Guid};
+/// fn main() -> io::Result<()> {
+///     let guid = Guid::from_u128(0x01234567_89ab_cdef_0123_456789abcdef);
+///     write_reparse_raw(r"C:\data\file", 0x0000_1234, Some(guid), b"payload")
+/// }
+/// ```
+pub fn write_reparse_raw<P: AsRef<Path>>(path: P, tag: u32, guid: Option<Guid>, data: &[u8]) -> io::Result<()> {
+    internals::write_reparse_raw(path.as_ref(), tag, guid, data, &Options::new())
+}
+
+/// Mounts the specified volume at the specified mount point folder.
+///
+/// `volume` is a volume GUID path in the form of `\\?\Volume{GUID}\`, and
+/// `mount_point` must be an existing empty directory. Like junctions, volume
+/// mount points are stored as `IO_REPARSE_TAG_MOUNT_POINT` reparse points.
+///
+/// N.B. Only works on NTFS and requires administrator privileges.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::io;
+/// # use junction::mount_volume;
+/// fn main() -> io::Result<()> {
+///     mount_volume(r"\\?\Volume{00000000-0000-0000-0000-000000000000}\", r"C:\mnt\data")
+/// }
+/// ```
+pub fn mount_volume<P, Q>(volume: P, mount_point: Q) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    internals::mount_volume(volume.as_ref(), mount_point.as_ref())
+}
+
+/// Unmounts the volume mounted at the specified mount point folder.
+///
+/// The mount point folder itself is left in place.
+///
+/// N.B. Requires administrator privileges.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::io;
+/// # use junction::unmount_volume;
+/// fn main() -> io::Result<()> {
+///     unmount_volume(r"C:\mnt\data")
+/// }
+/// ```
+pub fn unmount_volume<P: AsRef<Path>>(mount_point: P) -> io::Result<()> {
+    internals::unmount_volume(mount_point.as_ref())
+}
+
+/// A volume mount point returned by [`list_volume_mount_points`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VolumeMountPoint {
+    path: PathBuf,
+    volume: PathBuf,
+}
+
+impl VolumeMountPoint {
+    /// Returns the full path of the mount point folder.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the volume GUID path of the mounted volume, in the form of `\\?\Volume{GUID}\`.
+    pub fn volume(&self) -> &Path {
+        &self.volume
+    }
+}
+
+/// Iterator over the volume mount points on a volume.
+///
+/// This struct is created by [`list_volume_mount_points`].
+pub struct VolumeMountPoints {
+    inner: internals::VolumeMountPoints,
+}
+
+impl Iterator for VolumeMountPoints {
+    type Item = io::Result<VolumeMountPoint>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next()?;
+        Some(item.map(|(path, volume)| VolumeMountPoint { path, volume }))
+    }
+}
+
+impl fmt::Debug for VolumeMountPoints {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VolumeMountPoints").finish_non_exhaustive()
+    }
+}
+
+/// Lists the volume mount points on the volume with the specified root.
+///
+/// `volume_root` is either a volume GUID path or a drive root such as `C:\`.
+/// Only mount points directly residing on that volume are returned.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::io;
+/// # use junction::list_volume_mount_points;
+/// fn main() -> io::Result<()> {
+///     for mount_point in list_volume_mount_points(r"C:\")? {
+///         let mount_point = mount_point?;
+///         println!("{} -> {}", mount_point.path().display(), mount_point.volume().display());
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn list_volume_mount_points<P: AsRef<Path>>(volume_root: P) -> io::Result<VolumeMountPoints> {
+    let inner = internals::list_volume_mount_points(volume_root.as_ref())?;
+    Ok(VolumeMountPoints { inner })
 }
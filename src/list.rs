@@ -0,0 +1,83 @@
+use std::ffi::OsStr;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::internals;
+use crate::reparse::{IO_REPARSE_TAG_MOUNT_POINT, IO_REPARSE_TAG_SYMLINK};
+
+/// Whether the attributes of an entry mark it as a directory.
+const FILE_ATTRIBUTE_DIRECTORY: u32 = 0x10;
+
+/// An entry returned by [`read_dir_no_follow`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntry {
+    pub(crate) path: PathBuf,
+    pub(crate) attributes: u32,
+    pub(crate) reparse_tag: Option<u32>,
+}
+
+impl DirEntry {
+    /// Returns the path of the entry, which is the listed directory joined with its name.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the name of the entry.
+    pub fn file_name(&self) -> &OsStr {
+        // Listed entries always have a name.
+        self.path.file_name().unwrap_or_default()
+    }
+
+    /// Returns the raw file attributes, such as `FILE_ATTRIBUTE_HIDDEN`.
+    pub fn attributes(&self) -> u32 {
+        self.attributes
+    }
+
+    /// Returns the reparse tag, or `None` if the entry is not a reparse point.
+    pub fn reparse_tag(&self) -> Option<u32> {
+        self.reparse_tag
+    }
+
+    /// Returns `true` for plain directories, which are neither junction points
+    /// nor any other reparse point.
+    pub fn is_dir(&self) -> bool {
+        self.attributes & FILE_ATTRIBUTE_DIRECTORY != 0 && self.reparse_tag.is_none()
+    }
+
+    /// Returns `true` for junction points and volume mount points.
+    pub fn is_junction(&self) -> bool {
+        self.reparse_tag == Some(IO_REPARSE_TAG_MOUNT_POINT)
+    }
+
+    /// Returns `true` for symbolic links to files or directories.
+    pub fn is_symlink(&self) -> bool {
+        self.reparse_tag == Some(IO_REPARSE_TAG_SYMLINK)
+    }
+}
+
+/// Lists the entries of the directory `path`, with their reparse tags.
+///
+/// The entries are read with `FindFirstFileExW`, which reports the attributes
+/// and the reparse tag of every entry along with its name. No entry is opened,
+/// so reparse points are never followed and listing needs no more access than
+/// `std::fs::read_dir`. `.` and `..` are skipped, and entries are returned in
+/// the order of the file system.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::io;
+/// use junction::read_dir_no_follow;
+///
+/// fn main() -> io::Result<()> {
+///     for entry in read_dir_no_follow(r"C:\workspace")? {
+///         if entry.is_junction() {
+///             println!("{}", entry.path().display());
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn read_dir_no_follow<P: AsRef<Path>>(path: P) -> io::Result<Vec<DirEntry>> {
+    internals::read_dir_no_follow(path.as_ref())
+}
@@ -0,0 +1,399 @@
+//! Snapshots of the junctions under a directory tree.
+//!
+//! A [`Manifest`] records every junction below a root directory so that the
+//! same set of junctions can be re-created later, for example after restoring
+//! a backup or on another machine.
+//!
+//! # Format
+//!
+//! [`Manifest::write_to`] produces one line per junction, made of three fields
+//! separated by tabs: the kind (`directory`, `volume` or `device`), the path relative to
+//! the root, and the target as returned by [`get_target`](crate::get_target).
+//!
+//! Empty lines and lines starting with `#` are ignored by [`Manifest::read_from`].
+//! Tabs cannot appear in Windows file names, so no escaping is needed.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use crate::JunctionKind;
+
+/// A list of junctions below a root directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Manifest {
+    entries: Vec<ManifestEntry>,
+}
+
+/// A single junction recorded in a [`Manifest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManifestEntry {
+    path: PathBuf,
+    target: PathBuf,
+    kind: JunctionKind,
+}
+
+impl ManifestEntry {
+    /// Creates an entry for the junction at `path`, relative to the manifest root.
+    pub fn new<P: Into<PathBuf>, T: Into<PathBuf>>(path: P, target: T, kind: JunctionKind) -> Self {
+        ManifestEntry {
+            path: path.into(),
+            target: target.into(),
+            kind,
+        }
+    }
+
+    /// Returns the path of the junction, relative to the manifest root.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the target of the junction, as returned by [`get_target`](crate::get_target).
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+
+    /// Returns the kind of the junction.
+    pub fn kind(&self) -> JunctionKind {
+        self.kind
+    }
+}
+
+/// A change that [`Manifest::plan`] finds necessary to bring a directory tree
+/// in line with a manifest.
+///
+/// Entries hold paths relative to the root passed to [`Manifest::plan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Operation {
+    /// Create the junction, since nothing exists at its path.
+    Create(ManifestEntry),
+    /// Change the target of the existing junction at the path of `entry` from
+    /// `from` to the target of `entry`.
+    Retarget {
+        /// The junction as recorded in the manifest.
+        entry: ManifestEntry,
+        /// The current target of the junction.
+        from: PathBuf,
+    },
+    /// Delete the junction, which exists but is not in the manifest.
+    Delete(ManifestEntry),
+    /// Leave the junction alone, since it already exists as recorded.
+    Skip(ManifestEntry),
+}
+
+impl Operation {
+    /// Returns the path of the junction, relative to the manifest root.
+    pub fn path(&self) -> &Path {
+        match self {
+            Operation::Create(entry) | Operation::Delete(entry) | Operation::Skip(entry) => entry.path(),
+            Operation::Retarget { entry, .. } => entry.path(),
+        }
+    }
+}
+
+impl Manifest {
+    /// Creates an empty manifest.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records every junction below `root`.
+    ///
+    /// Junctions are not followed, so directories reached only through a
+    /// junction are not scanned. Entries are sorted by path.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::fs::File;
+    /// use std::io;
+    /// use junction::manifest::Manifest;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let manifest = Manifest::snapshot(r"C:\workspace")?;
+    ///     manifest.write_to(File::create("junctions.txt")?)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn snapshot<P: AsRef<Path>>(root: P) -> io::Result<Self> {
+        let mut manifest = Self::new();
+        scan(root.as_ref(), Path::new(""), &mut manifest.entries)?;
+        Ok(manifest)
+    }
+
+    /// Records every junction below `root` like [`snapshot`](Manifest::snapshot),
+    /// but scans directories in parallel on up to `threads` threads.
+    ///
+    /// A `threads` of 0 picks the number of threads like `rayon` does by default.
+    /// The result is the same as the one of [`snapshot`](Manifest::snapshot),
+    /// except that the error returned is not necessarily the first one
+    /// encountered in path order.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::io;
+    /// use junction::manifest::Manifest;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let manifest = Manifest::snapshot_parallel(r"C:", 8)?;
+    ///     println!("{} junctions", manifest.entries().len());
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn snapshot_parallel<P: AsRef<Path>>(root: P, threads: usize) -> io::Result<Self> {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        let root = root.as_ref();
+        let entries = pool.install(|| scan_parallel(root, Path::new("")))?;
+        Ok(Manifest { entries })
+    }
+
+    /// Re-creates every junction of this manifest below `root`.
+    ///
+    /// Missing parent directories are created. Targets are used as recorded,
+    /// so they must exist on this machine.
+    ///
+    /// # Error
+    ///
+    /// This function stops at the first junction that cannot be created, for
+    /// example because its path already exists.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::fs::File;
+    /// use std::io::{self, BufReader};
+    /// use junction::manifest::Manifest;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let manifest = Manifest::read_from(BufReader::new(File::open("junctions.txt")?))?;
+    ///     manifest.restore(r"D:\workspace")
+    /// }
+    /// ```
+    pub fn restore<P: AsRef<Path>>(&self, root: P) -> io::Result<()> {
+        let root = root.as_ref();
+        for entry in &self.entries {
+            let junction = root.join(&entry.path);
+            if let Some(parent) = junction.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            match entry.kind {
+                JunctionKind::Directory | JunctionKind::DevicePath => crate::create(&entry.target, &junction)?,
+                JunctionKind::VolumeMountPoint => crate::create_to_volume(&entry.target, &junction)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Compares this manifest with the junctions below `root`, and returns the
+    /// operations that would make them match, without making any of them.
+    ///
+    /// Every entry of the manifest yields a [`Create`](Operation::Create),
+    /// [`Retarget`](Operation::Retarget) or [`Skip`](Operation::Skip) operation,
+    /// in manifest order, followed by a [`Delete`](Operation::Delete) operation
+    /// for every junction below `root` that is not in the manifest, in the order
+    /// of [`snapshot`](Manifest::snapshot). Paths and targets are compared as
+    /// recorded, so they should come from a snapshot of the same machine. A
+    /// missing `root` is planned as empty.
+    ///
+    /// # Error
+    ///
+    /// Returns an error of kind [`io::ErrorKind::AlreadyExists`] if the path of
+    /// an entry exists but is not a junction, since no operation of this crate
+    /// replaces it, and any error of [`snapshot`](Manifest::snapshot).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::fs::File;
+    /// use std::io::{self, BufReader};
+    /// use junction::manifest::{Manifest, Operation};
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let manifest = Manifest::read_from(BufReader::new(File::open("junctions.txt")?))?;
+    ///     for operation in manifest.plan(r"D:\workspace")? {
+    ///         if !matches!(operation, Operation::Skip(_)) {
+    ///             println!("{:?}", operation);
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn plan<P: AsRef<Path>>(&self, root: P) -> io::Result<Vec<Operation>> {
+        let root = root.as_ref();
+        let current = match Manifest::snapshot(root) {
+            Ok(current) => current,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Manifest::new(),
+            Err(e) => return Err(e),
+        };
+        diff(&self.entries, &current.entries, |path| {
+            match fs::symlink_metadata(root.join(path)) {
+                Ok(_) => Ok(true),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+                Err(e) => Err(e),
+            }
+        })
+    }
+
+    /// Returns the recorded junctions.
+    pub fn entries(&self) -> &[ManifestEntry] {
+        &self.entries
+    }
+
+    /// Adds a junction to the manifest.
+    pub fn push(&mut self, entry: ManifestEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Writes the manifest in the text format described in the [module documentation](self).
+    ///
+    /// # Error
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if a path is not valid Unicode.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        for entry in &self.entries {
+            let kind = match entry.kind {
+                JunctionKind::Directory => "directory",
+                JunctionKind::VolumeMountPoint => "volume",
+                JunctionKind::DevicePath => "device",
+            };
+            writeln!(writer, "{}\t{}\t{}", kind, to_str(&entry.path)?, to_str(&entry.target)?)?;
+        }
+        writer.flush()
+    }
+
+    /// Reads a manifest in the text format described in the [module documentation](self).
+    pub fn read_from<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut manifest = Self::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim_end_matches('\r');
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let entry = parse_line(line).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("malformed manifest line {}", i + 1))
+            })?;
+            manifest.entries.push(entry);
+        }
+        Ok(manifest)
+    }
+}
+
+fn scan(dir: &Path, relative: &Path, entries: &mut Vec<ManifestEntry>) -> io::Result<()> {
+    let mut children = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    children.sort_by_key(|child| child.file_name());
+    for child in children {
+        let path = child.path();
+        let file_type = child.file_type()?;
+        // Junctions are name surrogates, which `std` reports as symlinks.
+        if file_type.is_symlink() {
+            entries.extend(entry_of(&path, relative.join(child.file_name()))?);
+        } else if file_type.is_dir() {
+            scan(&path, &relative.join(child.file_name()), entries)?;
+        }
+    }
+    Ok(())
+}
+
+// Like `scan`, but scans the children of `dir` in parallel. Their entries are
+// concatenated in the same order as `scan` visits them.
+#[cfg(feature = "rayon")]
+fn scan_parallel(dir: &Path, relative: &Path) -> io::Result<Vec<ManifestEntry>> {
+    use rayon::prelude::*;
+
+    let mut children = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    children.sort_by_key(|child| child.file_name());
+    let entries = children
+        .par_iter()
+        .map(|child| {
+            let path = child.path();
+            let file_type = child.file_type()?;
+            if file_type.is_symlink() {
+                Ok(entry_of(&path, relative.join(child.file_name()))?.into_iter().collect())
+            } else if file_type.is_dir() {
+                scan_parallel(&path, &relative.join(child.file_name()))
+            } else {
+                Ok(Vec::new())
+            }
+        })
+        .collect::<io::Result<Vec<Vec<_>>>>()?;
+    Ok(entries.into_iter().flatten().collect())
+}
+
+// Diffs the `desired` junctions against the `current` ones. `occupied` tells
+// whether something other than a current junction exists at a relative path.
+fn diff<F>(desired: &[ManifestEntry], current: &[ManifestEntry], occupied: F) -> io::Result<Vec<Operation>>
+where
+    F: Fn(&Path) -> io::Result<bool>,
+{
+    let mut remaining: BTreeMap<&Path, &ManifestEntry> = current.iter().map(|entry| (entry.path(), entry)).collect();
+    let mut operations = Vec::with_capacity(desired.len());
+    for entry in desired {
+        let operation = match remaining.remove(entry.path()) {
+            Some(existing) if existing.target == entry.target => Operation::Skip(entry.clone()),
+            Some(existing) => Operation::Retarget {
+                entry: entry.clone(),
+                from: existing.target.clone(),
+            },
+            None if occupied(&entry.path)? => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("`{}` exists and is not a junction", entry.path.display()),
+                ))
+            }
+            None => Operation::Create(entry.clone()),
+        };
+        operations.push(operation);
+    }
+    operations.extend(
+        current
+            .iter()
+            .filter(|entry| remaining.contains_key(entry.path()))
+            .map(|entry| Operation::Delete(entry.clone())),
+    );
+    Ok(operations)
+}
+
+// Returns the entry of the junction at `path`, or `None` if `path` is another kind of symlink.
+fn entry_of(path: &Path, relative: PathBuf) -> io::Result<Option<ManifestEntry>> {
+    if !crate::exists(path)? {
+        return Ok(None);
+    }
+    let target = crate::get_target(path)?;
+    let kind = crate::get_kind(path)?;
+    Ok(Some(ManifestEntry::new(relative, target, kind)))
+}
+
+fn parse_line(line: &str) -> Option<ManifestEntry> {
+    let mut fields = line.split('\t');
+    let kind = match fields.next()? {
+        "directory" => JunctionKind::Directory,
+        "volume" => JunctionKind::VolumeMountPoint,
+        "device" => JunctionKind::DevicePath,
+        _ => return None,
+    };
+    let path = fields.next().filter(|path| !path.is_empty())?;
+    let target = fields.next().filter(|target| !target.is_empty())?;
+    if fields.next().is_some() {
+        return None;
+    }
+    Some(ManifestEntry::new(path, target, kind))
+}
+
+fn to_str(path: &Path) -> io::Result<&str> {
+    path.to_str()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "path is not valid Unicode"))
+}
@@ -0,0 +1,108 @@
+use super::*;
+
+#[test]
+fn text_round_trip() {
+    let mut manifest = Manifest::new();
+    manifest.push(ManifestEntry::new("cache", r"D:\cache", JunctionKind::Directory));
+    manifest.push(ManifestEntry::new(
+        r"mnt\data",
+        r"\\?\Volume{b75e2c83-0000-0000-0000-602f00000000}\",
+        JunctionKind::VolumeMountPoint,
+    ));
+    manifest.push(ManifestEntry::new(
+        "shadow",
+        r"\\?\GLOBALROOT\Device\HarddiskVolume1\dir",
+        JunctionKind::DevicePath,
+    ));
+    let mut text = Vec::new();
+    manifest.write_to(&mut text).unwrap();
+    assert_eq!(
+        String::from_utf8(text.clone()).unwrap(),
+        "directory\tcache\tD:\\cache\n\
+         volume\tmnt\\data\t\\\\?\\Volume{b75e2c83-0000-0000-0000-602f00000000}\\\n\
+         device\tshadow\t\\\\?\\GLOBALROOT\\Device\\HarddiskVolume1\\dir\n"
+    );
+    assert_eq!(Manifest::read_from(&text[..]).unwrap(), manifest);
+}
+
+#[test]
+fn read_skips_comments_and_blank_lines() {
+    let text = "# junctions\r\n\r\ndirectory\tcache\tD:\\cache\r\n";
+    let manifest = Manifest::read_from(text.as_bytes()).unwrap();
+    assert_eq!(manifest.entries().len(), 1);
+    assert_eq!(manifest.entries()[0].path(), Path::new("cache"));
+    assert_eq!(manifest.entries()[0].target(), Path::new(r"D:\cache"));
+    assert_eq!(manifest.entries()[0].kind(), JunctionKind::Directory);
+}
+
+#[test]
+fn read_rejects_malformed_lines() {
+    for text in ["symlink\ta\tb", "directory\ta", "directory\t\tb", "directory\ta\tb\tc"] {
+        let err = Manifest::read_from(text.as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{:?}", text);
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn parallel_snapshot_without_junctions() {
+    let root = std::env::temp_dir().join(format!("junction-manifest-{}", std::process::id()));
+    for dir in ["a/b", "a/c", "d"] {
+        fs::create_dir_all(root.join(dir)).unwrap();
+    }
+    let parallel = Manifest::snapshot_parallel(&root, 2);
+    let sequential = Manifest::snapshot(&root);
+    fs::remove_dir_all(&root).unwrap();
+    assert_eq!(parallel.unwrap(), sequential.unwrap());
+}
+
+#[test]
+fn diff_operations() {
+    let desired = [
+        ManifestEntry::new("same", r"D:\a", JunctionKind::Directory),
+        ManifestEntry::new("moved", r"D:\new", JunctionKind::Directory),
+        ManifestEntry::new("added", r"D:\b", JunctionKind::Directory),
+    ];
+    let current = [
+        ManifestEntry::new("moved", r"D:\old", JunctionKind::Directory),
+        ManifestEntry::new("same", r"D:\a", JunctionKind::Directory),
+        ManifestEntry::new("stale", r"D:\c", JunctionKind::Directory),
+    ];
+    let operations = diff(&desired, &current, |_| Ok(false)).unwrap();
+    assert_eq!(
+        operations,
+        [
+            Operation::Skip(desired[0].clone()),
+            Operation::Retarget {
+                entry: desired[1].clone(),
+                from: PathBuf::from(r"D:\old"),
+            },
+            Operation::Create(desired[2].clone()),
+            Operation::Delete(current[2].clone()),
+        ]
+    );
+    let paths: Vec<_> = operations.iter().map(Operation::path).collect();
+    assert_eq!(paths, ["same", "moved", "added", "stale"].map(Path::new));
+}
+
+#[test]
+fn diff_rejects_occupied_paths() {
+    let desired = [ManifestEntry::new("dir", r"D:\a", JunctionKind::Directory)];
+    let err = diff(&desired, &[], |path| Ok(path == Path::new("dir"))).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+}
+
+#[test]
+fn plan_without_junctions() {
+    let root = std::env::temp_dir().join(format!("junction-plan-{}", std::process::id()));
+    let mut manifest = Manifest::new();
+    manifest.push(ManifestEntry::new("a/link", "/target", JunctionKind::Directory));
+    assert_eq!(
+        manifest.plan(&root).unwrap(),
+        [Operation::Create(manifest.entries()[0].clone())]
+    );
+    fs::create_dir_all(root.join("a/link")).unwrap();
+    let occupied = manifest.plan(&root);
+    fs::remove_dir_all(&root).unwrap();
+    assert_eq!(occupied.unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+}
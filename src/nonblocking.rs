@@ -0,0 +1,30 @@
+//! Runtime-agnostic asynchronous versions of the junction operations.
+//!
+//! Each function runs its blocking counterpart on the thread pool of the
+//! [blocking] crate, so the returned futures can be awaited from any executor,
+//! such as smol, async-std or a plain `block_on`.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::io;
+//! # async fn run() -> io::Result<()> {
+//! junction::nonblocking::create(r"C:\target", r"C:\junction").await?;
+//! assert!(junction::nonblocking::exists(r"C:\junction").await?);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [blocking]: https://docs.rs/blocking
+
+use std::io;
+
+async fn asyncify<F, T>(f: F) -> io::Result<T>
+where
+    F: FnOnce() -> io::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    blocking::unblock(f).await
+}
+
+async_wrappers!();
@@ -0,0 +1,92 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{internals, Options};
+
+/// The basic junction point operations, as a trait to substitute them in tests.
+///
+/// Code that manages junction points can take a `&dyn JunctionOps` or a generic
+/// `impl JunctionOps` instead of calling the functions of the crate root, and
+/// be tested with a mock that neither touches the file system nor needs Windows.
+/// [`Options`] implements it with the real file system, so passing
+/// `&Options::new()` gives the behavior of the crate root functions.
+///
+/// # Example
+///
+/// ```rust
+/// use std::cell::RefCell;
+/// use std::collections::HashMap;
+/// use std::io;
+/// use std::path::{Path, PathBuf};
+/// use junction::{JunctionOps, Options};
+///
+/// // Points `link` to `target`, replacing the previous junction point if any.
+/// fn relink(ops: &dyn JunctionOps, target: &Path, link: &Path) -> io::Result<()> {
+///     if ops.exists(link)? {
+///         if ops.get_target(link)? == target {
+///             return Ok(());
+///         }
+///         ops.delete(link)?;
+///     }
+///     ops.create(target, link)
+/// }
+///
+/// #[derive(Default)]
+/// struct Mock(RefCell<HashMap<PathBuf, PathBuf>>);
+///
+/// impl JunctionOps for Mock {
+///     fn create(&self, target: &Path, junction: &Path) -> io::Result<()> {
+///         self.0.borrow_mut().insert(junction.to_owned(), target.to_owned());
+///         Ok(())
+///     }
+///     fn delete(&self, junction: &Path) -> io::Result<()> {
+///         self.0.borrow_mut().remove(junction);
+///         Ok(())
+///     }
+///     fn get_target(&self, junction: &Path) -> io::Result<PathBuf> {
+///         self.0.borrow().get(junction).cloned().ok_or_else(|| io::ErrorKind::NotFound.into())
+///     }
+///     fn exists(&self, junction: &Path) -> io::Result<bool> {
+///         Ok(self.0.borrow().contains_key(junction))
+///     }
+/// }
+///
+/// let mock = Mock::default();
+/// relink(&mock, Path::new(r"C:\v1"), Path::new(r"C:\current")).unwrap();
+/// relink(&mock, Path::new(r"C:\v2"), Path::new(r"C:\current")).unwrap();
+/// assert_eq!(mock.get_target(Path::new(r"C:\current")).unwrap(), Path::new(r"C:\v2"));
+///
+/// // The real file system, with the default options.
+/// let _real: &dyn JunctionOps = &Options::new();
+/// ```
+pub trait JunctionOps {
+    /// Creates a junction point, like [`create`](crate::create).
+    fn create(&self, target: &Path, junction: &Path) -> io::Result<()>;
+
+    /// Deletes a junction point, like [`delete`](crate::delete).
+    fn delete(&self, junction: &Path) -> io::Result<()>;
+
+    /// Gets the target of a junction point, like [`get_target`](crate::get_target).
+    fn get_target(&self, junction: &Path) -> io::Result<PathBuf>;
+
+    /// Determines whether a path is a junction point, like [`exists`](crate::exists).
+    fn exists(&self, junction: &Path) -> io::Result<bool>;
+}
+
+impl JunctionOps for Options {
+    fn create(&self, target: &Path, junction: &Path) -> io::Result<()> {
+        Options::create(self, target, junction)
+    }
+
+    fn delete(&self, junction: &Path) -> io::Result<()> {
+        Options::delete(self, junction)
+    }
+
+    fn get_target(&self, junction: &Path) -> io::Result<PathBuf> {
+        internals::get_target(junction, self)
+    }
+
+    fn exists(&self, junction: &Path) -> io::Result<bool> {
+        internals::exists(junction, self)
+    }
+}
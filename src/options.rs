@@ -0,0 +1,835 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use std::{fmt, fs, io};
+
+use crate::audit::{Hook, OperationKind, OperationRecord};
+use crate::{
+    internals, AuditHook, Batch, DeleteOutcome, JunctionFile, JunctionKind, JunctionMetadata, PolicyViolation,
+    ReparseKind, ResolvedJunction, Verification,
+};
+
+/// How privileges of the process token are adjusted when opening reparse points.
+///
+/// Used with [`Options::privileges`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PrivilegePolicy {
+    /// Never adjust privileges. Permission errors are returned as is.
+    ///
+    /// The process token is not even opened, which suits AppContainers and
+    /// service sandboxes where token manipulation is forbidden or audited.
+    Never,
+    /// Enable the privilege needed for the operation after the first attempt
+    /// failed with a permission error, then try again.
+    ///
+    /// This is the default. The privilege is `SeCreateSymbolicLinkPrivilege`,
+    /// or `SeBackupPrivilege` and `SeRestorePrivilege` with the `unstable_admin`
+    /// feature. If the privilege cannot be enabled, for example because the
+    /// process token cannot be opened, the original permission error is returned.
+    Auto,
+    /// Enable the named privileges, such as `"SeBackupPrivilege"`, before
+    /// opening any reparse point.
+    ///
+    /// See the [privilege constants] for the available names.
+    ///
+    /// [privilege constants]: https://learn.microsoft.com/en-us/windows/win32/secauthz/privilege-constants
+    Always(&'static [&'static str]),
+}
+
+impl Default for PrivilegePolicy {
+    fn default() -> Self {
+        PrivilegePolicy::Auto
+    }
+}
+
+/// How targets are turned into the substitute name of a junction point.
+///
+/// Used with [`Options::normalization`]. Except with [`Verbatim`](TargetNormalization::Verbatim),
+/// verbatim paths such as `\\?\C:\dir` are written as is, without their prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TargetNormalization {
+    /// Resolve the target with `GetFullPathNameW`, like most Win32 functions do.
+    ///
+    /// This is the default. Relative targets are resolved against the current
+    /// directory, `.` and `..` components are removed, forward slashes are
+    /// converted to backslashes, and trailing dots and spaces of the last name
+    /// are stripped.
+    Full,
+    /// Only convert forward slashes to backslashes.
+    ///
+    /// The target must be absolute. Components such as `..` are kept, and no
+    /// drive or current directory is consulted.
+    SeparatorsOnly,
+    /// Write the target exactly as given, for example to reproduce a junction
+    /// point from a [`Manifest`](crate::manifest::Manifest) or another machine.
+    ///
+    /// The target is prefixed with `\??\`, unless it already is an NT path
+    /// starting with a backslash, such as `\??\C:\dir` or `\Device\HarddiskVolume1\dir`.
+    /// Targets are only checked for their length, so relative or otherwise
+    /// unusable targets are written too.
+    Verbatim,
+}
+
+impl Default for TargetNormalization {
+    fn default() -> Self {
+        TargetNormalization::Full
+    }
+}
+
+/// How an operation reacts to a condition that it handles, but that is rarely intended.
+///
+/// Used with [`Options::remote_filesystems`] and [`Options::cross_volume`]. The
+/// condition is described by a [`PolicyViolation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Policy {
+    /// Go ahead silently. This is the default.
+    Allow,
+    /// Go ahead, after passing the violation to the hook set with
+    /// [`Options::on_policy_warning`], if any.
+    Warn,
+    /// Fail with an error of kind [`io::ErrorKind::InvalidInput`] carrying the violation.
+    Deny,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Policy::Allow
+    }
+}
+
+// The hook of `Options::on_policy_warning`, which must be `Debug` and `Clone`
+// like the other options.
+#[derive(Clone)]
+pub(crate) struct PolicyHook(pub(crate) Arc<dyn Fn(&PolicyViolation) + Send + Sync>);
+
+impl fmt::Debug for PolicyHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PolicyHook")
+    }
+}
+
+/// Forgets which privileges this crate has already enabled.
+///
+/// Privileges enabled through [`PrivilegePolicy::Auto`] or
+/// [`PrivilegePolicy::Always`] are remembered for the whole process, so that
+/// bulk operations adjust the process token only once. Call this function after
+/// the privileges were disabled by other means, so that the next operation
+/// enables them again.
+pub fn clear_privilege_cache() {
+    internals::clear_privilege_cache();
+}
+
+/// Options and flags which can be used to configure how junctions are accessed.
+///
+/// The functions in the crate root behave like the same methods on
+/// `Options::new()`. Use `Options` to deviate from those defaults.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::io;
+/// use junction::{Options, PrivilegePolicy};
+///
+/// fn main() -> io::Result<()> {
+///     // Fail instead of touching the process token in a hardened environment.
+///     Options::new()
+///         .privileges(PrivilegePolicy::Never)
+///         .create(r"C:\target", r"C:\junction")
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    pub(crate) privileges: PrivilegePolicy,
+    pub(crate) restore_privileges: bool,
+    pub(crate) share_mode: u32,
+    pub(crate) attempts: u32,
+    pub(crate) backoff: Duration,
+    pub(crate) adopt_existing: bool,
+    pub(crate) create_parents: bool,
+    pub(crate) overwrite: bool,
+    pub(crate) preserve_timestamps: bool,
+    pub(crate) preserve_security: bool,
+    pub(crate) security_descriptor: Option<String>,
+    pub(crate) os_compatibility: bool,
+    pub(crate) not_content_indexed: bool,
+    pub(crate) compare_file_ids: bool,
+    pub(crate) lenient_exists: bool,
+    pub(crate) relative_to_junction: bool,
+    pub(crate) allow_unc_targets: bool,
+    pub(crate) allow_trailing_dots_and_spaces: bool,
+    pub(crate) normalization: TargetNormalization,
+    pub(crate) nt_paths: bool,
+    pub(crate) mklink_compatible: bool,
+    pub(crate) audit_hook: Option<Hook>,
+    pub(crate) remote_filesystems: Policy,
+    pub(crate) cross_volume: Policy,
+    pub(crate) policy_hook: Option<PolicyHook>,
+}
+
+impl Options {
+    /// Creates a blank set of options with the default behavior of the crate root functions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how privileges are adjusted when opening reparse points.
+    ///
+    /// Defaults to [`PrivilegePolicy::Auto`].
+    pub fn privileges(&mut self, policy: PrivilegePolicy) -> &mut Self {
+        self.privileges = policy;
+        self
+    }
+
+    /// Sets whether privileges enabled by this crate are disabled again once
+    /// the operation completes.
+    ///
+    /// By default, privileges stay enabled for the rest of the process lifetime,
+    /// which spares later operations from adjusting the token again. Long-running
+    /// services may prefer not to keep powerful privileges such as
+    /// `SeRestorePrivilege` enabled. Privileges that were already enabled before
+    /// the operation are left untouched.
+    ///
+    /// The process token is shared by all threads, so a privilege is only
+    /// disabled once the last concurrent operation using it completes, and
+    /// stays enabled if an operation without this option enabled it meanwhile.
+    pub fn restore_privileges(&mut self, restore: bool) -> &mut Self {
+        self.restore_privileges = restore;
+        self
+    }
+
+    /// Sets the share mode used when opening the reparse point, as in
+    /// [`OpenOptionsExt::share_mode`](std::os::windows::fs::OpenOptionsExt::share_mode).
+    ///
+    /// Defaults to `0`, which makes the operation fail with a sharing violation
+    /// whenever another process has the directory open. Pass a combination of
+    /// `FILE_SHARE_READ` (`0x1`), `FILE_SHARE_WRITE` (`0x2`) and
+    /// `FILE_SHARE_DELETE` (`0x4`) to allow concurrent access.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::io;
+    /// use junction::Options;
+    ///
+    /// const FILE_SHARE_READ: u32 = 0x1;
+    /// const FILE_SHARE_WRITE: u32 = 0x2;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let target = Options::new()
+    ///         .share_mode(FILE_SHARE_READ | FILE_SHARE_WRITE)
+    ///         .get_target(r"C:\junction")?;
+    ///     println!("{}", target.display());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn share_mode(&mut self, share_mode: u32) -> &mut Self {
+        self.share_mode = share_mode;
+        self
+    }
+
+    /// Retries operations that fail with a transient error.
+    ///
+    /// Antivirus and indexing services briefly hold freshly created directories
+    /// open, so opening a reparse point or reading or writing its data may fail
+    /// with `ERROR_SHARING_VIOLATION` or `ERROR_LOCK_VIOLATION`. Such failures
+    /// are retried until `attempts` attempts were made in total, sleeping
+    /// `backoff` before the first retry and twice as long before each following
+    /// one. `ERROR_ACCESS_DENIED` is returned right away, even though a directory
+    /// pending deletion also reports it.
+    ///
+    /// By default, every operation is attempted once.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::io;
+    /// use std::time::Duration;
+    /// use junction::Options;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     Options::new()
+    ///         .retry(5, Duration::from_millis(10))
+    ///         .create(r"C:\target", r"C:\junction")
+    /// }
+    /// ```
+    pub fn retry(&mut self, attempts: u32, backoff: Duration) -> &mut Self {
+        self.attempts = attempts;
+        self.backoff = backoff;
+        self
+    }
+
+    /// Sets whether [`create`](Options::create) accepts an existing empty
+    /// directory as the junction, instead of failing because it already exists.
+    ///
+    /// This allows preparing the directory first, for example to set its ACLs,
+    /// and turning it into a junction afterwards. Directories that are not empty
+    /// or are already reparse points are still rejected. An adopted directory is
+    /// left in place if setting the reparse point fails.
+    pub fn adopt_existing(&mut self, adopt: bool) -> &mut Self {
+        self.adopt_existing = adopt;
+        self
+    }
+
+    /// Sets whether [`create`](Options::create) creates missing parent
+    /// directories of the junction, like [`fs::create_dir_all`](std::fs::create_dir_all).
+    ///
+    /// Parent directories created concurrently by another process are not an
+    /// error. They are not removed again if creating the junction fails.
+    pub fn create_parents(&mut self, create_parents: bool) -> &mut Self {
+        self.create_parents = create_parents;
+        self
+    }
+
+    /// Sets whether [`create`](Options::create) replaces an existing junction.
+    ///
+    /// The existing junction is retargeted in place, as with
+    /// [`set_target`](Options::set_target), so readers never observe a missing
+    /// or empty directory. Other existing paths are still an error.
+    pub fn overwrite(&mut self, overwrite: bool) -> &mut Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Sets whether retargeting a junction keeps the timestamps of its directory entry.
+    ///
+    /// Replacing the reparse data updates the last write time of the junction,
+    /// so sync tools see a change even if the target stays the same. With this
+    /// option, [`set_target`](Options::set_target) and [`create`](Options::create)
+    /// with [`overwrite`](Options::overwrite) restore the creation, last access
+    /// and last write times afterwards, through the handle used to retarget.
+    pub fn preserve_timestamps(&mut self, preserve: bool) -> &mut Self {
+        self.preserve_timestamps = preserve;
+        self
+    }
+
+    /// Sets whether junction points recreated elsewhere keep the owner and DACL of the original.
+    ///
+    /// [`copy_junction`](Options::copy_junction) and [`move_junction`](Options::move_junction)
+    /// across volumes create a new directory, which otherwise gets the default
+    /// security of its parent directory. With this option, the owner and the
+    /// DACL of the source are read beforehand and applied to the new junction
+    /// point. Setting an owner other than the current user requires the
+    /// `SeRestorePrivilege` privilege. If they cannot be applied, the error is
+    /// returned; a moved junction point then stays at its source.
+    ///
+    /// Retargeting with [`set_target`](Options::set_target) or with
+    /// [`overwrite`](Options::overwrite) keeps the directory, and with it the
+    /// security descriptor, so this option does not apply there.
+    pub fn preserve_security(&mut self, preserve: bool) -> &mut Self {
+        self.preserve_security = preserve;
+        self
+    }
+
+    /// Sets the security descriptor of the directories created for new junction points, in SDDL.
+    ///
+    /// The directory is created with this descriptor, such as `"D:P(A;OICI;FA;;;SY)(A;OICI;FA;;;BA)"`
+    /// for access by the system and administrators only, instead of inheriting
+    /// the permissions of its parent. There is thus no window in which the
+    /// junction point has the default permissions. Existing directories reused
+    /// through [`adopt_existing`](Options::adopt_existing) or
+    /// [`overwrite`](Options::overwrite) keep their own.
+    ///
+    /// An invalid string makes creating the junction fail with [`io::ErrorKind::InvalidInput`].
+    pub fn security_descriptor(&mut self, sddl: &str) -> &mut Self {
+        self.security_descriptor = Some(sddl.to_owned());
+        self
+    }
+
+    /// Sets whether new junction points are excluded from content indexing.
+    ///
+    /// Windows Search indexes the content of a junction point's target once
+    /// more through every junction point leading to it. With this option, new
+    /// junction points get `FILE_ATTRIBUTE_NOT_CONTENT_INDEXED`, which the
+    /// indexer honors, while the target itself is still indexed at its own path.
+    pub fn not_content_indexed(&mut self, not_indexed: bool) -> &mut Self {
+        self.not_content_indexed = not_indexed;
+        self
+    }
+
+    /// Sets whether new junction points are configured like the compatibility junctions of Windows.
+    ///
+    /// Windows redirects legacy paths such as `C:\Documents and Settings` with
+    /// junction points that are hidden and system, and whose DACL denies everyone
+    /// to list them while still allowing to traverse them. Paths through such a
+    /// junction point keep working, but tools that enumerate directories, such
+    /// as backups and searches, do not visit the target twice. With this option,
+    /// new junction points are configured the same way. Administrators and the
+    /// system keep full access; other users need the privileges enabled by
+    /// [`PrivilegePolicy`](crate::PrivilegePolicy) to read or delete them later.
+    ///
+    /// This replaces the DACL given with [`security_descriptor`](Options::security_descriptor).
+    pub fn os_compatibility(&mut self, compatibility: bool) -> &mut Self {
+        self.os_compatibility = compatibility;
+        self
+    }
+
+    /// Sets whether [`exists`](Options::exists) returns `Ok(false)` for plain files and directories.
+    ///
+    /// By default, `exists` fails with `ERROR_NOT_A_REPARSE_POINT` for entries
+    /// that exist but are not reparse points at all, and only returns `Ok(false)`
+    /// for missing paths and reparse points other than junction points. With
+    /// this option, it answers `Ok(false)` for anything that is not a junction point.
+    pub fn lenient_exists(&mut self, lenient: bool) -> &mut Self {
+        self.lenient_exists = lenient;
+        self
+    }
+
+    /// Sets whether relative targets are resolved against the parent directory
+    /// of the junction, instead of the current directory of the process.
+    ///
+    /// The current directory is shared by all threads, so resolving against it
+    /// is racy in multithreaded programs, and rarely what is meant anyway. With
+    /// this option, `create(r"..\shared", r"C:\app\data")` targets `C:\shared`.
+    /// This applies to every operation that takes a target, such as
+    /// [`create`](Options::create), [`set_target`](Options::set_target) and
+    /// [`verify`](Options::verify). The junction path itself should be absolute,
+    /// as a relative one is still resolved against the current directory.
+    pub fn relative_to_junction(&mut self, relative: bool) -> &mut Self {
+        self.relative_to_junction = relative;
+        self
+    }
+
+    /// Sets how targets are turned into the substitute name of a junction point.
+    ///
+    /// Defaults to [`TargetNormalization::Full`].
+    pub fn normalization(&mut self, normalization: TargetNormalization) -> &mut Self {
+        self.normalization = normalization;
+        self
+    }
+
+    /// Sets whether paths are handled through the native NT API instead of Win32.
+    ///
+    /// Junction points are then opened with `NtCreateFile`, and targets are
+    /// resolved with `RtlDosPathNameToNtPathName_U_WithStatus` under
+    /// [`TargetNormalization::Full`]. Both accept paths longer than `MAX_PATH`
+    /// (260 characters), such as deeply nested package stores, even if long
+    /// paths are not enabled machine-wide.
+    pub fn nt_paths(&mut self, nt_paths: bool) -> &mut Self {
+        self.nt_paths = nt_paths;
+        self
+    }
+
+    /// Sets whether junction points are written exactly like `mklink /J` writes them.
+    ///
+    /// By default, the print name of a new junction point is left empty, as
+    /// Windows only follows the substitute name. `mklink /J` repeats the target
+    /// there, such as `C:\target` next to the substitute name `\??\C:\target`,
+    /// which some integrity checks compare byte for byte. With this option,
+    /// [`create`](Options::create), [`set_target`](Options::set_target) and
+    /// [`convert_to_junction`](Options::convert_to_junction) write the same
+    /// reparse data as `mklink /J` for the same target, as long as it is resolved
+    /// with [`TargetNormalization::Full`], the default, as `mklink` does. Like `mklink`, an
+    /// existing `junction` is an error, unless [`overwrite`](Options::overwrite)
+    /// or [`adopt_existing`](Options::adopt_existing) is set.
+    pub fn mklink_compatible(&mut self, compatible: bool) -> &mut Self {
+        self.mklink_compatible = compatible;
+        self
+    }
+
+    /// Sets whether junction points may target network paths such as `\\server\share`.
+    ///
+    /// Windows does not follow junctions to network shares, so by default,
+    /// creating or retargeting one fails with an [`UnsupportedTarget`](crate::UnsupportedTarget)
+    /// error. Allow them to write such reparse points anyway, for example to
+    /// reproduce them exactly from a backup.
+    pub fn allow_unc_targets(&mut self, allow: bool) -> &mut Self {
+        self.allow_unc_targets = allow;
+        self
+    }
+
+    /// Sets whether junction points may target names ending in a dot or a space.
+    ///
+    /// Win32 functions strip trailing dots and spaces from names, so most tools
+    /// cannot open or delete a directory such as `\\?\C:\dir.`, and a junction
+    /// to it appears broken. By default, such targets are rejected with an
+    /// [`UnsupportedTarget`](crate::UnsupportedTarget) error. They can only be
+    /// given as verbatim paths, as [`create`](Options::create) strips them otherwise.
+    pub fn allow_trailing_dots_and_spaces(&mut self, allow: bool) -> &mut Self {
+        self.allow_trailing_dots_and_spaces = allow;
+        self
+    }
+
+    /// Sets whether [`same_target`](Options::same_target) compares the file IDs
+    /// of the resolved targets when their paths differ.
+    ///
+    /// Different paths may still lead to the same directory, for example a
+    /// drive letter and a volume GUID path, or a target that is itself a
+    /// junction. Comparing file IDs gives a definitive answer, but requires both
+    /// targets to exist and be accessible.
+    pub fn compare_file_ids(&mut self, compare: bool) -> &mut Self {
+        self.compare_file_ids = compare;
+        self
+    }
+
+    /// Sets how to react when a junction point would be created or retargeted
+    /// on a network share or a mapped network drive, or to a target there.
+    ///
+    /// Such a junction point is resolved by the server, relative to its own
+    /// drives, which is rarely intended, and creating it often fails obscurely.
+    /// Paths are checked with `GetDriveTypeW` on the root of their volume, only
+    /// if the policy is not [`Policy::Allow`], the default. Paths whose volume
+    /// cannot be queried pass.
+    pub fn remote_filesystems(&mut self, policy: Policy) -> &mut Self {
+        self.remote_filesystems = policy;
+        self
+    }
+
+    /// Sets how to react when a junction point would be created or retargeted
+    /// to a target on another volume than the directory holding it.
+    ///
+    /// Such junction points break when the target volume is not mounted, and
+    /// deleting the target through them crosses into another volume. Volumes
+    /// are compared by the serial numbers of the parent directory of the
+    /// junction point and of the target, only if the policy is not
+    /// [`Policy::Allow`], the default. Targets that do not exist yet pass, as
+    /// their volume cannot be queried.
+    pub fn cross_volume(&mut self, policy: Policy) -> &mut Self {
+        self.cross_volume = policy;
+        self
+    }
+
+    /// Sets the hook that receives violations of policies set to [`Policy::Warn`].
+    ///
+    /// The hook is called on the calling thread, before the operation goes ahead.
+    pub fn on_policy_warning<F>(&mut self, hook: F) -> &mut Self
+    where
+        F: Fn(&PolicyViolation) + Send + Sync + 'static,
+    {
+        self.policy_hook = Some(PolicyHook(Arc::new(hook)));
+        self
+    }
+
+    // Reacts to `violation` as configured by `policy`.
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub(crate) fn check_policy(&self, policy: Policy, violation: PolicyViolation) -> io::Result<()> {
+        match policy {
+            Policy::Allow => Ok(()),
+            Policy::Warn => {
+                if let Some(PolicyHook(hook)) = &self.policy_hook {
+                    hook(&violation);
+                }
+                Ok(())
+            }
+            Policy::Deny => Err(io::Error::new(io::ErrorKind::InvalidInput, violation)),
+        }
+    }
+
+    /// Sets a hook that is told about every junction point created, retargeted,
+    /// moved, converted or deleted with these options.
+    ///
+    /// See [`AuditHook`] for the methods that report to it.
+    pub fn audit_hook<H: AuditHook + 'static>(&mut self, hook: H) -> &mut Self {
+        self.audit_hook = Some(Hook(Arc::new(hook)));
+        self
+    }
+
+    // Runs `operation` on `junction`, and reports its result to the audit hook.
+    fn audited<T>(
+        &self,
+        kind: OperationKind,
+        junction: &Path,
+        target: Option<&Path>,
+        operation: impl FnOnce() -> io::Result<T>,
+    ) -> io::Result<T> {
+        let result = operation();
+        self.audit(kind, junction, None, target, result.as_ref().map(drop));
+        result
+    }
+
+    // Like `audited`, for operations that read the target of the junction point
+    // they change from `source`, or from `junction` itself, and return it.
+    fn audited_read(
+        &self,
+        kind: OperationKind,
+        junction: &Path,
+        source: Option<&Path>,
+        operation: impl FnOnce() -> io::Result<PathBuf>,
+    ) -> io::Result<()> {
+        let result = operation();
+        let target = result.as_ref().ok().map(PathBuf::as_path);
+        self.audit(kind, junction, source, target, result.as_ref().map(drop));
+        result.map(drop)
+    }
+
+    fn audit(
+        &self,
+        kind: OperationKind,
+        junction: &Path,
+        source: Option<&Path>,
+        target: Option<&Path>,
+        result: Result<(), &io::Error>,
+    ) {
+        if let Some(Hook(hook)) = &self.audit_hook {
+            hook.on_operation(&OperationRecord {
+                kind,
+                junction,
+                source,
+                target,
+                time: SystemTime::now(),
+                result,
+            });
+        }
+    }
+
+    /// Creates a junction point from the specified directory to the specified target directory.
+    ///
+    /// See [`crate::create`].
+    pub fn create<P: AsRef<Path>, Q: AsRef<Path>>(&self, target: P, junction: Q) -> io::Result<()> {
+        let (target, junction) = (target.as_ref(), junction.as_ref());
+        self.audited(OperationKind::Create, junction, Some(target), || {
+            internals::create(target, junction, self)
+        })
+    }
+
+    /// Creates a junction point, but only if `target` is an existing directory.
+    ///
+    /// See [`crate::create_checked`].
+    pub fn create_checked<P: AsRef<Path>, Q: AsRef<Path>>(&self, target: P, junction: Q) -> io::Result<()> {
+        let (target, junction) = (target.as_ref(), junction.as_ref());
+        self.audited(OperationKind::Create, junction, Some(target), || {
+            internals::create_checked(target, junction, self)
+        })
+    }
+
+    /// Creates a junction point, and returns the paths it resolved.
+    ///
+    /// See [`crate::create_resolved`].
+    pub fn create_resolved<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        target: P,
+        junction: Q,
+    ) -> io::Result<ResolvedJunction> {
+        let (target, junction) = (target.as_ref(), junction.as_ref());
+        self.audited(OperationKind::Create, junction, Some(target), || {
+            internals::create_resolved(target, junction, self)
+        })
+    }
+
+    /// Creates a junction point, resolving relative paths against `base` instead
+    /// of the current directory.
+    ///
+    /// See [`crate::create_with_base`].
+    pub fn create_with_base<B: AsRef<Path>, P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        base: B,
+        target: P,
+        junction: Q,
+    ) -> io::Result<()> {
+        let (base, target, junction) = (base.as_ref(), target.as_ref(), junction.as_ref());
+        // Report the target as written, resolved against `base`.
+        let resolved = internals::join_lexically(base, target);
+        let reported = resolved.as_deref().unwrap_or(target);
+        self.audited(OperationKind::Create, junction, Some(reported), || {
+            internals::create_with_base(base, target, junction, self)
+        })
+    }
+
+    /// Checks whether `junction` is a junction point to `expected`.
+    ///
+    /// See [`crate::verify`].
+    pub fn verify<P: AsRef<Path>, Q: AsRef<Path>>(&self, junction: P, expected: Q) -> io::Result<Verification> {
+        internals::verify(junction.as_ref(), expected.as_ref(), self)
+    }
+
+    /// Creates a junction point unless an identical one already exists.
+    ///
+    /// See [`crate::create_idempotent`].
+    pub fn create_idempotent<P: AsRef<Path>, Q: AsRef<Path>>(&self, target: P, junction: Q) -> io::Result<bool> {
+        let (target, junction) = (target.as_ref(), junction.as_ref());
+        let result = internals::create_idempotent(target, junction, self);
+        // An identical junction point is left as is, so there is nothing to report.
+        if !matches!(result, Ok(false)) {
+            self.audit(
+                OperationKind::Create,
+                junction,
+                None,
+                Some(target),
+                result.as_ref().map(drop),
+            );
+        }
+        result
+    }
+
+    /// Checks whether the junction points `a` and `b` have the same target.
+    ///
+    /// See [`crate::same_target`].
+    pub fn same_target<P: AsRef<Path>, Q: AsRef<Path>>(&self, a: P, b: Q) -> io::Result<bool> {
+        internals::same_target(a.as_ref(), b.as_ref(), self)
+    }
+
+    /// Creates a junction point at `dst` with the same reparse data as `src`.
+    ///
+    /// See [`crate::copy_junction`].
+    pub fn copy_junction<P: AsRef<Path>, Q: AsRef<Path>>(&self, src: P, dst: Q) -> io::Result<()> {
+        let (src, dst) = (src.as_ref(), dst.as_ref());
+        self.audited_read(OperationKind::Create, dst, Some(src), || {
+            internals::copy_junction(src, dst, self)
+        })
+    }
+
+    /// Moves the junction point `src` to `dst`, also across volumes.
+    ///
+    /// See [`crate::move_junction`].
+    pub fn move_junction<P: AsRef<Path>, Q: AsRef<Path>>(&self, src: P, dst: Q) -> io::Result<()> {
+        let (src, dst) = (src.as_ref(), dst.as_ref());
+        self.audited_read(OperationKind::Move, dst, Some(src), || {
+            internals::move_junction(src, dst, self)
+        })
+    }
+
+    /// Changes the target of an existing junction point in place.
+    ///
+    /// See [`crate::set_target`].
+    pub fn set_target<P: AsRef<Path>, Q: AsRef<Path>>(&self, junction: P, target: Q) -> io::Result<()> {
+        let (junction, target) = (junction.as_ref(), target.as_ref());
+        self.audited(OperationKind::Retarget, junction, Some(target), || {
+            internals::set_target(junction, target, self)
+        })
+    }
+
+    /// Turns an existing junction point into a directory symbolic link in place.
+    ///
+    /// See [`crate::convert_to_symlink`].
+    pub fn convert_to_symlink<P: AsRef<Path>>(&self, junction: P) -> io::Result<()> {
+        let junction = junction.as_ref();
+        self.audited_read(OperationKind::ConvertToSymlink, junction, None, || {
+            internals::convert_to_symlink(junction, self)
+        })
+    }
+
+    /// Turns an existing directory symbolic link into a junction point in place.
+    ///
+    /// See [`crate::convert_to_junction`].
+    pub fn convert_to_junction<P: AsRef<Path>>(&self, symlink: P) -> io::Result<()> {
+        let symlink = symlink.as_ref();
+        self.audited_read(OperationKind::ConvertToJunction, symlink, None, || {
+            internals::convert_to_junction(symlink, self)
+        })
+    }
+
+    /// Deletes a `junction` reparse point from the specified file or directory.
+    ///
+    /// See [`crate::delete`].
+    pub fn delete<P: AsRef<Path>>(&self, junction: P) -> io::Result<()> {
+        let junction = junction.as_ref();
+        self.audited(OperationKind::Delete, junction, None, || {
+            internals::delete(junction, self)
+        })
+    }
+
+    /// Deletes the junction point `name` in the open directory `dir`.
+    ///
+    /// See [`crate::delete_at`].
+    pub fn delete_at<P: AsRef<Path>>(&self, dir: &std::fs::File, name: P) -> io::Result<()> {
+        let name = name.as_ref();
+        // The bare `name` tells nothing about where the junction point was.
+        let junction = internals::dir_path(dir).map_or_else(|_| name.to_path_buf(), |dir| dir.join(name));
+        self.audited(OperationKind::Delete, &junction, None, || {
+            internals::delete_in(dir, name, self)
+        })
+    }
+
+    /// Deletes a `junction` reparse point and removes the directory left behind.
+    ///
+    /// See [`crate::delete_and_remove_dir`].
+    pub fn delete_and_remove_dir<P: AsRef<Path>>(&self, junction: P) -> io::Result<()> {
+        let junction = junction.as_ref();
+        self.audited(OperationKind::Delete, junction, None, || {
+            internals::delete(junction, self)?;
+            // Unlike `remove_dir_all`, this never touches content that appeared in between.
+            fs::remove_dir(junction)
+        })
+    }
+
+    /// Deletes a `junction` reparse point, but only if it points to `expected`.
+    ///
+    /// See [`crate::delete_if_target`].
+    pub fn delete_if_target<P: AsRef<Path>, Q: AsRef<Path>>(&self, junction: P, expected: Q) -> io::Result<()> {
+        let junction = junction.as_ref();
+        self.audited(OperationKind::Delete, junction, None, || {
+            internals::delete_if_target(junction, expected.as_ref(), self)
+        })
+    }
+
+    /// Deletes a `junction` reparse point if it points to `expected`, and tells
+    /// whether it did.
+    ///
+    /// See [`crate::delete_if_target_is`].
+    pub fn delete_if_target_is<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        junction: P,
+        expected: Q,
+    ) -> io::Result<DeleteOutcome> {
+        let junction = junction.as_ref();
+        let result = internals::delete_if_target_is(junction, expected.as_ref(), self);
+        // A junction point left in place is no change to report.
+        if !matches!(result, Ok(DeleteOutcome::DifferentTarget(_))) {
+            self.audit(OperationKind::Delete, junction, None, None, result.as_ref().map(drop));
+        }
+        result
+    }
+
+    /// Applies the operations of `batch` in order, undoing them all if one fails.
+    ///
+    /// See [`Batch`] for how operations are undone.
+    ///
+    /// # Error
+    ///
+    /// This function returns the error of the failed operation. If undoing an
+    /// operation fails too, the error has a [`RollbackFailed`](crate::RollbackFailed)
+    /// payload instead, with the same kind.
+    pub fn apply_batch(&self, batch: &Batch) -> io::Result<()> {
+        batch.apply(self)
+    }
+
+    /// Determines whether the specified path exists and refers to a junction point.
+    ///
+    /// See [`crate::exists`].
+    pub fn exists<P: AsRef<Path>>(&self, junction: P) -> io::Result<bool> {
+        internals::exists(junction.as_ref(), self)
+    }
+
+    /// Gets the target of the specified junction point.
+    ///
+    /// See [`crate::get_target`].
+    pub fn get_target<P: AsRef<Path>>(&self, junction: P) -> io::Result<PathBuf> {
+        internals::get_target(junction.as_ref(), self)
+    }
+
+    /// Opens the reparse point at `path`, for reading and also for writing if `writable` is set.
+    ///
+    /// See [`JunctionFile`].
+    pub fn open_file<P: AsRef<Path>>(&self, path: P, writable: bool) -> io::Result<JunctionFile> {
+        internals::JunctionFile::open(path.as_ref(), writable, self).map(JunctionFile::from_inner)
+    }
+
+    /// Checks whether `path` exists, whether it is a junction point and where it points to.
+    ///
+    /// See [`crate::metadata`].
+    pub fn metadata<P: AsRef<Path>>(&self, path: P) -> io::Result<JunctionMetadata> {
+        internals::metadata(path.as_ref(), self)
+    }
+
+    /// Gets the target of the specified junction point into `buf`.
+    ///
+    /// See [`crate::get_target_into`].
+    pub fn get_target_into<'a, P: AsRef<Path>>(&self, junction: P, buf: &'a mut Vec<u16>) -> io::Result<&'a [u16]> {
+        internals::get_target_into(junction.as_ref(), buf, self)
+    }
+
+    /// Gets the kind of the specified junction point.
+    ///
+    /// See [`crate::get_kind`].
+    pub fn get_kind<P: AsRef<Path>>(&self, junction: P) -> io::Result<JunctionKind> {
+        internals::get_kind(junction.as_ref(), self)
+    }
+
+    /// Classifies the specified path without following it.
+    ///
+    /// See [`crate::kind`].
+    pub fn kind<P: AsRef<Path>>(&self, path: P) -> io::Result<Option<ReparseKind>> {
+        internals::kind(path.as_ref(), self)
+    }
+}
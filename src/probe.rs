@@ -0,0 +1,250 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::internals;
+
+/// The result of probing whether junctions work in a directory.
+///
+/// Returned by [`can_create_junctions`].
+#[derive(Debug)]
+pub struct Capabilities {
+    create: bool,
+    read: bool,
+    delete: bool,
+    error: Option<io::Error>,
+}
+
+impl Capabilities {
+    /// Returns `true` if a junction could be created, read back and deleted.
+    pub fn is_supported(&self) -> bool {
+        self.create && self.read && self.delete
+    }
+
+    /// Returns `true` if a junction could be created.
+    pub fn can_create(&self) -> bool {
+        self.create
+    }
+
+    /// Returns `true` if the target of the created junction could be read back.
+    pub fn can_read(&self) -> bool {
+        self.read
+    }
+
+    /// Returns `true` if the created junction could be deleted.
+    pub fn can_delete(&self) -> bool {
+        self.delete
+    }
+
+    /// Returns the error of the first step that failed, if any.
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+}
+
+/// Checks whether junctions can be created, read and deleted in `dir`.
+///
+/// The probe creates a uniquely named scratch directory inside `dir`, creates a
+/// junction in it, reads its target back, deletes it and removes the scratch
+/// directory again. Whatever prevents junctions from working, such as the file
+/// system, missing privileges or a security policy, is reported in the returned
+/// [`Capabilities`] rather than as an error.
+///
+/// # Error
+///
+/// This function errors if the scratch directory cannot be created in `dir`.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::io;
+/// use junction::can_create_junctions;
+///
+/// fn main() -> io::Result<()> {
+///     let capabilities = can_create_junctions(r"D:\install")?;
+///     if !capabilities.is_supported() {
+///         eprintln!("junctions are not available: {:?}", capabilities.error());
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn can_create_junctions<P: AsRef<Path>>(dir: P) -> io::Result<Capabilities> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let name = format!(
+        ".junction-probe-{}-{}",
+        process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    let scratch = dir.as_ref().join(name);
+    fs::create_dir(&scratch)?;
+    let junction = scratch.join("junction");
+    let capabilities = probe(&scratch, &junction);
+    // The junction has no contents of its own, so removing it never touches the target.
+    let _ = fs::remove_dir(&junction);
+    let _ = fs::remove_dir(&scratch);
+    Ok(capabilities)
+}
+
+fn probe(target: &Path, junction: &Path) -> Capabilities {
+    let mut capabilities = Capabilities {
+        create: false,
+        read: false,
+        delete: false,
+        error: None,
+    };
+    if let Err(e) = crate::create(target, junction) {
+        capabilities.error = Some(e);
+        return capabilities;
+    }
+    capabilities.create = true;
+    match crate::get_target(junction) {
+        Ok(_) => capabilities.read = true,
+        Err(e) => {
+            capabilities.error = Some(e);
+            return capabilities;
+        }
+    }
+    match crate::delete(junction) {
+        Ok(()) => capabilities.delete = true,
+        Err(e) => capabilities.error = Some(e),
+    }
+    capabilities
+}
+
+/// What the volume holding a path supports, as reported by [`filesystem_capabilities`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilesystemCapabilities {
+    pub(crate) volume: PathBuf,
+    pub(crate) filesystem: String,
+    pub(crate) flags: u32,
+    pub(crate) dev_drive: bool,
+    pub(crate) sectors_per_cluster: u32,
+    pub(crate) bytes_per_sector: u32,
+    pub(crate) case_sensitive_dir: Option<bool>,
+}
+
+impl FilesystemCapabilities {
+    const FILE_CASE_SENSITIVE_SEARCH: u32 = 0x0000_0001;
+    const FILE_CASE_PRESERVED_NAMES: u32 = 0x0000_0002;
+    const FILE_SUPPORTS_REPARSE_POINTS: u32 = 0x0000_0080;
+    const FILE_SUPPORTS_HARD_LINKS: u32 = 0x0040_0000;
+    const FILE_SUPPORTS_BLOCK_REFCOUNTING: u32 = 0x0800_0000;
+
+    /// Returns the root of the volume, such as `C:\`.
+    pub fn volume(&self) -> &Path {
+        &self.volume
+    }
+
+    /// Returns the name of the file system, such as `NTFS`, `ReFS` or `FAT32`.
+    pub fn filesystem(&self) -> &str {
+        &self.filesystem
+    }
+
+    /// Returns the raw file system flags, as returned by `GetVolumeInformationW`.
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    /// Returns `true` if the file system can store reparse points, and with
+    /// them junction points and symbolic links.
+    pub fn supports_reparse_points(&self) -> bool {
+        self.flags & Self::FILE_SUPPORTS_REPARSE_POINTS != 0
+    }
+
+    /// Returns `true` if the file system supports hard links to files.
+    pub fn supports_hard_links(&self) -> bool {
+        self.flags & Self::FILE_SUPPORTS_HARD_LINKS != 0
+    }
+
+    /// Returns `true` if the file system can clone file blocks, which makes
+    /// copies cheap, as ReFS does.
+    pub fn supports_block_cloning(&self) -> bool {
+        self.flags & Self::FILE_SUPPORTS_BLOCK_REFCOUNTING != 0
+    }
+
+    /// Returns `true` if the file system is NTFS.
+    pub fn is_ntfs(&self) -> bool {
+        self.filesystem.eq_ignore_ascii_case("NTFS")
+    }
+
+    /// Returns `true` if the file system is ReFS.
+    pub fn is_refs(&self) -> bool {
+        self.filesystem.eq_ignore_ascii_case("ReFS")
+    }
+
+    /// Returns `true` if the volume is a Dev Drive.
+    ///
+    /// Dev Drives are ReFS volumes that antivirus scans asynchronously. Windows
+    /// versions without Dev Drives report `false`.
+    pub fn is_dev_drive(&self) -> bool {
+        self.dev_drive
+    }
+
+    /// Returns the size of a cluster in bytes, the unit in which space is allocated.
+    pub fn cluster_size(&self) -> u64 {
+        u64::from(self.sectors_per_cluster) * u64::from(self.bytes_per_sector)
+    }
+
+    /// Returns the size of a sector in bytes.
+    pub fn bytes_per_sector(&self) -> u32 {
+        self.bytes_per_sector
+    }
+
+    /// Returns `true` if the file system can tell names apart by case.
+    ///
+    /// Win32 functions still compare names case-insensitively, except in
+    /// directories that are case-sensitive, see [`is_case_sensitive_dir`](Self::is_case_sensitive_dir).
+    pub fn case_sensitive_search(&self) -> bool {
+        self.flags & Self::FILE_CASE_SENSITIVE_SEARCH != 0
+    }
+
+    /// Returns `true` if the file system keeps the case of names as given.
+    pub fn case_preserved_names(&self) -> bool {
+        self.flags & Self::FILE_CASE_PRESERVED_NAMES != 0
+    }
+
+    /// Returns whether the directory at the queried path is case-sensitive,
+    /// as set with `fsutil file setCaseSensitiveInfo`.
+    ///
+    /// New subdirectories inherit this setting. Returns `None` if the path does
+    /// not exist or is not a directory, or if the setting cannot be queried, as
+    /// before Windows 10 1803.
+    pub fn is_case_sensitive_dir(&self) -> Option<bool> {
+        self.case_sensitive_dir
+    }
+}
+
+/// Reports what the volume holding `path` supports, without creating anything.
+///
+/// Unlike [`can_create_junctions`], this only queries the volume, so it also
+/// works for read-only locations, but cannot tell whether privileges or
+/// security policies would get in the way. A junction point at `path` is not
+/// followed: the volume reported is the one holding the junction point itself.
+///
+/// # Error
+///
+/// This function errors if the volume information cannot be queried, for
+/// example because the drive does not exist.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::io;
+/// use junction::filesystem_capabilities;
+///
+/// fn main() -> io::Result<()> {
+///     let capabilities = filesystem_capabilities(r"D:\src")?;
+///     if capabilities.is_dev_drive() {
+///         println!("{} is a Dev Drive", capabilities.volume().display());
+///     } else if !capabilities.supports_reparse_points() {
+///         println!("{} cannot hold junctions", capabilities.filesystem());
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn filesystem_capabilities<P: AsRef<Path>>(path: P) -> io::Result<FilesystemCapabilities> {
+    internals::filesystem_capabilities(path.as_ref())
+}
@@ -0,0 +1,76 @@
+//! Reparse point primitives on open files, for building custom policies.
+//!
+//! The functions of the crate root open the path, check the reparse tag,
+//! normalize targets and retry on transient errors. These functions do none of
+//! that: they open a reparse point, and read, set or delete the reparse data of
+//! an already open file, leaving the rest to the caller.
+//!
+//! Files are passed as [`File`] rather than as `BorrowedHandle`, which needs a
+//! newer Rust than this crate supports. A handle obtained elsewhere can be
+//! turned into a [`File`] with [`FromRawHandle`](std::os::windows::io::FromRawHandle).
+//!
+//! The reparse data is the whole reparse data buffer, header included, in the
+//! layout that [`parse_reparse_data`](crate::parse_reparse_data) reads and
+//! [`ReparseData::to_bytes`](crate::ReparseData::to_bytes) writes.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::io;
+//! use junction::raw;
+//!
+//! fn main() -> io::Result<()> {
+//!     let file = raw::open_reparse_point(r"C:\workspace\link", true)?;
+//!     let data = raw::get_reparse_data_point(&file)?;
+//!     raw::delete_reparse_point(&file)?;
+//!     raw::set_reparse_point(&file, &data)
+//! }
+//! ```
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use crate::{internals, Options};
+
+/// Opens the file or directory `path` without following it if it is a reparse point.
+///
+/// The file is opened with `FILE_FLAG_OPEN_REPARSE_POINT` and `FILE_FLAG_BACKUP_SEMANTICS`,
+/// for reading, and for writing too if `write` is `true`, as required to set or
+/// delete its reparse point. Privileges are enabled as configured by the default
+/// [`PrivilegePolicy`](crate::PrivilegePolicy) only while opening.
+pub fn open_reparse_point<P: AsRef<Path>>(path: P, write: bool) -> io::Result<File> {
+    internals::raw_open(path.as_ref(), write, &Options::new())
+}
+
+/// Reads the reparse data of `file` with `FSCTL_GET_REPARSE_POINT`, whatever its tag.
+///
+/// # Error
+///
+/// This function errors with `ERROR_NOT_A_REPARSE_POINT` if `file` is not a reparse point.
+pub fn get_reparse_data_point(file: &File) -> io::Result<Vec<u8>> {
+    internals::raw_get(file)
+}
+
+/// Sets the reparse data of `file` with `FSCTL_SET_REPARSE_POINT`.
+///
+/// # Error
+///
+/// This function errors if `data` is malformed: shorter than its header says,
+/// longer than `MAXIMUM_REPARSE_DATA_BUFFER_SIZE`, or with a GUID for a
+/// Microsoft tag or without one for another tag. Like `FSCTL_SET_REPARSE_POINT`,
+/// it also errors if `file` is already a reparse point with another tag.
+pub fn set_reparse_point(file: &File, data: &[u8]) -> io::Result<()> {
+    internals::raw_set(file, data)
+}
+
+/// Deletes the reparse point of `file` with `FSCTL_DELETE_REPARSE_POINT`, whatever its tag.
+///
+/// The file itself is left in place.
+///
+/// # Error
+///
+/// This function errors with `ERROR_NOT_A_REPARSE_POINT` if `file` is not a reparse point.
+pub fn delete_reparse_point(file: &File) -> io::Result<()> {
+    internals::raw_delete(file)
+}
@@ -0,0 +1,546 @@
+//! Platform-independent interpretation of reparse point data.
+
+#[cfg(test)]
+mod tests;
+
+use std::ops::Range;
+use std::{fmt, io};
+
+use crate::consts::{
+    IO_REPARSE_TAG_APPEXECLINK, IO_REPARSE_TAG_CLOUD, IO_REPARSE_TAG_CLOUD_MASK, IO_REPARSE_TAG_DEDUP,
+    IO_REPARSE_TAG_LX_SYMLINK, SYMLINK_FLAG_RELATIVE, SYMLINK_REPARSE_BUFFER_HEADER_SIZE,
+};
+pub(crate) use crate::consts::{
+    IO_REPARSE_TAG_MOUNT_POINT, IO_REPARSE_TAG_SYMLINK, MAXIMUM_REPARSE_DATA_BUFFER_SIZE,
+    MOUNT_POINT_REPARSE_BUFFER_HEADER_SIZE, REPARSE_DATA_BUFFER_HEADER_SIZE, REPARSE_GUID_DATA_BUFFER_HEADER_SIZE,
+};
+
+/// A globally unique identifier, identifying the owner of a non-Microsoft reparse point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Guid {
+    pub data1: u32,
+    pub data2: u16,
+    pub data3: u16,
+    pub data4: [u8; 8],
+}
+
+impl Guid {
+    /// Creates a GUID from its 128-bit big-endian representation,
+    /// such that `0x01234567_89ab_cdef_0123_456789abcdef` is `{01234567-89AB-CDEF-0123-456789ABCDEF}`.
+    pub const fn from_u128(value: u128) -> Self {
+        Self {
+            data1: (value >> 96) as u32,
+            data2: (value >> 80) as u16,
+            data3: (value >> 64) as u16,
+            data4: (value as u64).to_be_bytes(),
+        }
+    }
+
+    /// Returns the 128-bit big-endian representation of this GUID.
+    pub const fn to_u128(self) -> u128 {
+        ((self.data1 as u128) << 96)
+            | ((self.data2 as u128) << 80)
+            | ((self.data3 as u128) << 64)
+            | (u64::from_be_bytes(self.data4) as u128)
+    }
+
+    // The in-memory layout of the Windows `GUID` struct.
+    pub(crate) fn from_le_bytes(b: [u8; 16]) -> Self {
+        let mut data4 = [0u8; 8];
+        data4.copy_from_slice(&b[8..]);
+        Self {
+            data1: u32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            data2: u16::from_le_bytes([b[4], b[5]]),
+            data3: u16::from_le_bytes([b[6], b[7]]),
+            data4,
+        }
+    }
+
+    pub(crate) fn to_le_bytes(self) -> [u8; 16] {
+        let mut b = [0u8; 16];
+        b[..4].copy_from_slice(&self.data1.to_le_bytes());
+        b[4..6].copy_from_slice(&self.data2.to_le_bytes());
+        b[6..8].copy_from_slice(&self.data3.to_le_bytes());
+        b[8..].copy_from_slice(&self.data4);
+        b
+    }
+}
+
+impl fmt::Display for Guid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let d = &self.data4;
+        write!(
+            f,
+            "{{{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}}}",
+            self.data1, self.data2, self.data3, d[0], d[1], d[2], d[3], d[4], d[5], d[6], d[7]
+        )
+    }
+}
+
+/// A reparse tag, classified among the tags of Microsoft reparse points.
+///
+/// Converted from the raw tags returned by functions such as
+/// [`reparse_tag`](crate::reparse_tag) and [`DirEntry::reparse_tag`](crate::DirEntry::reparse_tag).
+///
+/// # Example
+///
+/// ```rust
+/// # use junction::ReparseTag;
+/// assert_eq!(ReparseTag::from(0xA000_0003), ReparseTag::MountPoint);
+/// assert_eq!(ReparseTag::from(0x9000_101A), ReparseTag::CloudFile);
+/// assert_eq!(ReparseTag::from(0x1234).to_string(), "0x00001234");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum ReparseTag {
+    /// A directory junction or a volume mount point (`IO_REPARSE_TAG_MOUNT_POINT`).
+    MountPoint,
+    /// A symbolic link (`IO_REPARSE_TAG_SYMLINK`).
+    Symlink,
+    /// An app execution alias (`IO_REPARSE_TAG_APPEXECLINK`).
+    AppExecLink,
+    /// A symbolic link created by WSL (`IO_REPARSE_TAG_LX_SYMLINK`).
+    LxSymlink,
+    /// A file deduplicated by Data Deduplication (`IO_REPARSE_TAG_DEDUP`).
+    Dedup,
+    /// A placeholder of the Cloud Files API, such as a OneDrive file
+    /// (`IO_REPARSE_TAG_CLOUD` and `IO_REPARSE_TAG_CLOUD_1` to `IO_REPARSE_TAG_CLOUD_F`).
+    CloudFile,
+    /// Any other reparse tag.
+    Unknown(u32),
+}
+
+impl From<u32> for ReparseTag {
+    fn from(tag: u32) -> Self {
+        match tag {
+            IO_REPARSE_TAG_MOUNT_POINT => ReparseTag::MountPoint,
+            IO_REPARSE_TAG_SYMLINK => ReparseTag::Symlink,
+            IO_REPARSE_TAG_APPEXECLINK => ReparseTag::AppExecLink,
+            IO_REPARSE_TAG_LX_SYMLINK => ReparseTag::LxSymlink,
+            IO_REPARSE_TAG_DEDUP => ReparseTag::Dedup,
+            // The cloud tags differ only in the bits of `IO_REPARSE_TAG_CLOUD_MASK`.
+            _ if tag & !IO_REPARSE_TAG_CLOUD_MASK == IO_REPARSE_TAG_CLOUD => ReparseTag::CloudFile,
+            _ => ReparseTag::Unknown(tag),
+        }
+    }
+}
+
+impl fmt::Display for ReparseTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReparseTag::MountPoint => f.write_str("mount point"),
+            ReparseTag::Symlink => f.write_str("symbolic link"),
+            ReparseTag::AppExecLink => f.write_str("app execution alias"),
+            ReparseTag::LxSymlink => f.write_str("WSL symbolic link"),
+            ReparseTag::Dedup => f.write_str("deduplicated file"),
+            ReparseTag::CloudFile => f.write_str("cloud file"),
+            ReparseTag::Unknown(tag) => write!(f, "{:#010x}", tag),
+        }
+    }
+}
+
+/// Reparse point data parsed by [`parse_reparse_data`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum ReparseData {
+    /// A directory junction or a volume mount point (`IO_REPARSE_TAG_MOUNT_POINT`).
+    MountPoint(MountPointData),
+    /// A symbolic link (`IO_REPARSE_TAG_SYMLINK`).
+    Symlink(SymlinkData),
+    /// Any other reparse point, with its tag-specific data left uninterpreted.
+    Other {
+        tag: u32,
+        guid: Option<Guid>,
+        data: Vec<u8>,
+    },
+}
+
+impl ReparseData {
+    /// Returns the reparse tag of this reparse point.
+    pub fn tag(&self) -> u32 {
+        match self {
+            ReparseData::MountPoint(_) => IO_REPARSE_TAG_MOUNT_POINT,
+            ReparseData::Symlink(_) => IO_REPARSE_TAG_SYMLINK,
+            ReparseData::Other { tag, .. } => *tag,
+        }
+    }
+
+    /// Serializes this reparse point into the layout expected by `FSCTL_SET_REPARSE_POINT`.
+    ///
+    /// This is the inverse of [`parse_reparse_data`]. The substitute name is written
+    /// first and the print name right after it, each followed by a `UNICODE_NULL`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use junction::{parse_reparse_data, MountPointData, ReparseData};
+    /// let substitute_name: Vec<u16> = r"\??\C:\foo".encode_utf16().collect();
+    /// let data = ReparseData::MountPoint(MountPointData::new(substitute_name, Vec::new()));
+    /// let bytes = data.to_bytes().unwrap();
+    /// assert_eq!(parse_reparse_data(&bytes).unwrap(), data);
+    /// ```
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        match self {
+            ReparseData::MountPoint(data) => {
+                let data = names_data(&[], &data.substitute_name, &data.print_name)?;
+                build_reparse_data(IO_REPARSE_TAG_MOUNT_POINT, None, &data)
+            }
+            ReparseData::Symlink(data) => {
+                let data = names_data(&data.flags.to_le_bytes(), &data.substitute_name, &data.print_name)?;
+                build_reparse_data(IO_REPARSE_TAG_SYMLINK, None, &data)
+            }
+            ReparseData::Other { tag, guid, data } => build_reparse_data(*tag, *guid, data),
+        }
+    }
+}
+
+/// The names stored in a mount point reparse point.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MountPointData {
+    substitute_name: Vec<u16>,
+    print_name: Vec<u16>,
+}
+
+impl MountPointData {
+    /// Creates mount point data from UTF-16 names, without any NUL terminator.
+    pub fn new(substitute_name: Vec<u16>, print_name: Vec<u16>) -> Self {
+        Self {
+            substitute_name,
+            print_name,
+        }
+    }
+
+    /// Returns the UTF-16 substitute name, which is the NT path of the target such as `\??\C:\foo`.
+    pub fn substitute_name(&self) -> &[u16] {
+        &self.substitute_name
+    }
+
+    /// Returns the UTF-16 print name, which is meant to be displayed to users and may be empty.
+    pub fn print_name(&self) -> &[u16] {
+        &self.print_name
+    }
+}
+
+/// The names and flags stored in a symbolic link reparse point.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SymlinkData {
+    substitute_name: Vec<u16>,
+    print_name: Vec<u16>,
+    flags: u32,
+}
+
+impl SymlinkData {
+    /// Creates symbolic link data from UTF-16 names, without any NUL terminator, and flags.
+    pub fn new(substitute_name: Vec<u16>, print_name: Vec<u16>, flags: u32) -> Self {
+        Self {
+            substitute_name,
+            print_name,
+            flags,
+        }
+    }
+
+    /// Returns the UTF-16 substitute name of the link target.
+    pub fn substitute_name(&self) -> &[u16] {
+        &self.substitute_name
+    }
+
+    /// Returns the UTF-16 print name, which is meant to be displayed to users.
+    pub fn print_name(&self) -> &[u16] {
+        &self.print_name
+    }
+
+    /// Returns the raw symbolic link flags.
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    /// Returns whether the target is relative to the directory containing the link.
+    pub fn is_relative(&self) -> bool {
+        self.flags & SYMLINK_FLAG_RELATIVE != 0
+    }
+}
+
+/// Parses reparse point data as returned by `FSCTL_GET_REPARSE_POINT`.
+///
+/// `bytes` must start with the reparse data header (the reparse tag and data length).
+/// Unlike the rest of this crate, this function does not issue any system call and
+/// is available on all platforms, for example to analyze data captured from disk images.
+///
+/// # Example
+///
+/// ```rust
+/// # use junction::{parse_reparse_data, ReparseData};
+/// let bytes = [
+///     0x03, 0x00, 0x00, 0xA0, // IO_REPARSE_TAG_MOUNT_POINT
+///     0x0C, 0x00, 0x00, 0x00, // ReparseDataLength, Reserved
+///     0x00, 0x00, 0x02, 0x00, // SubstituteNameOffset, SubstituteNameLength
+///     0x04, 0x00, 0x00, 0x00, // PrintNameOffset, PrintNameLength
+///     b'X', 0x00, 0x00, 0x00, // PathBuffer: "X\0"
+/// ];
+/// match parse_reparse_data(&bytes).unwrap() {
+///     ReparseData::MountPoint(data) => assert_eq!(data.substitute_name(), &[u16::from(b'X')]),
+///     other => panic!("not a mount point: {:?}", other),
+/// }
+/// ```
+pub fn parse_reparse_data(bytes: &[u8]) -> io::Result<ReparseData> {
+    let (tag, guid, data) = split_reparse_data(bytes)?;
+    match tag {
+        IO_REPARSE_TAG_MOUNT_POINT => {
+            let (substitute_name, print_name) = parse_names(data, MOUNT_POINT_REPARSE_BUFFER_HEADER_SIZE)?;
+            Ok(ReparseData::MountPoint(MountPointData {
+                substitute_name,
+                print_name,
+            }))
+        }
+        IO_REPARSE_TAG_SYMLINK => {
+            let (substitute_name, print_name) = parse_names(data, SYMLINK_REPARSE_BUFFER_HEADER_SIZE)?;
+            let flags = read_u32(data, 8)?;
+            Ok(ReparseData::Symlink(SymlinkData {
+                substitute_name,
+                print_name,
+                flags,
+            }))
+        }
+        _ => Ok(ReparseData::Other {
+            tag,
+            guid,
+            data: data.to_vec(),
+        }),
+    }
+}
+
+/// Splits reparse point data into its tag, the GUID of non-Microsoft reparse points
+/// and the tag-specific data.
+pub(crate) fn split_reparse_data(bytes: &[u8]) -> io::Result<(u32, Option<Guid>, &[u8])> {
+    let tag = read_u32(bytes, 0)?;
+    let data_len = usize::from(read_u16(bytes, 4)?);
+    // Only non-Microsoft reparse points carry a GUID, which is not counted in `ReparseDataLength`.
+    let (guid, header_size) = if is_reparse_tag_microsoft(tag) {
+        (None, REPARSE_DATA_BUFFER_HEADER_SIZE)
+    } else {
+        let mut guid = [0u8; 16];
+        guid.copy_from_slice(get(bytes, REPARSE_DATA_BUFFER_HEADER_SIZE, 16)?);
+        (Some(Guid::from_le_bytes(guid)), REPARSE_GUID_DATA_BUFFER_HEADER_SIZE)
+    };
+    Ok((tag, guid, get(bytes, header_size, data_len)?))
+}
+
+/// Raw reparse point data, displayed as its header fields, names and bytes.
+///
+/// Returned by [`debug_dump`](crate::debug_dump). The [`Display`](fmt::Display)
+/// implementation interprets as much of the data as it can: a field that cannot
+/// be read, such as a name pointing past the end of the buffer, is printed with
+/// its error and does not prevent the remaining fields and the hex dump of all
+/// bytes from being printed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReparseDump {
+    bytes: Vec<u8>,
+}
+
+impl ReparseDump {
+    /// Wraps reparse point data obtained by other means, including its header.
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self { bytes }
+    }
+
+    /// Returns the reparse point data, including its header.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the reparse point data, including its header.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    fn fmt_names(&self, f: &mut fmt::Formatter<'_>, data: &[u8], header_size: usize) -> fmt::Result {
+        for (name, index) in [("substitute name", 0), ("print name", 4)] {
+            let (offset, len) = match (read_u16(data, index), read_u16(data, index + 2)) {
+                (Ok(offset), Ok(len)) => (offset, len),
+                (Err(e), _) | (_, Err(e)) => {
+                    writeln!(f, "{}: error: {}", name, e)?;
+                    continue;
+                }
+            };
+            write!(f, "{}: offset {}, length {}: ", name, offset, len)?;
+            match name_range(data, header_size, index) {
+                Ok(range) => writeln!(f, "{:?}", String::from_utf16_lossy(&to_wide(&data[range])))?,
+                Err(e) => writeln!(f, "error: {}", e)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ReparseDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const BYTES_PER_LINE: usize = 16;
+
+        let bytes = &self.bytes;
+        match read_u32(bytes, 0) {
+            Ok(tag) => match ReparseTag::from(tag) {
+                ReparseTag::Unknown(_) => writeln!(f, "tag: {:#010x}", tag)?,
+                known => writeln!(f, "tag: {:#010x} ({})", tag, known)?,
+            },
+            Err(e) => writeln!(f, "tag: error: {}", e)?,
+        }
+        match read_u16(bytes, 4) {
+            Ok(len) => writeln!(f, "data length: {}", len)?,
+            Err(e) => writeln!(f, "data length: error: {}", e)?,
+        }
+        match read_u16(bytes, 6) {
+            Ok(reserved) => writeln!(f, "reserved: {:#06x}", reserved)?,
+            Err(e) => writeln!(f, "reserved: error: {}", e)?,
+        }
+        match split_reparse_data(bytes) {
+            Ok((tag, guid, data)) => {
+                if let Some(guid) = guid {
+                    writeln!(f, "GUID: {}", guid)?;
+                }
+                match tag {
+                    IO_REPARSE_TAG_MOUNT_POINT => self.fmt_names(f, data, MOUNT_POINT_REPARSE_BUFFER_HEADER_SIZE)?,
+                    IO_REPARSE_TAG_SYMLINK => {
+                        self.fmt_names(f, data, SYMLINK_REPARSE_BUFFER_HEADER_SIZE)?;
+                        match read_u32(data, 8) {
+                            Ok(flags) => writeln!(f, "flags: {:#010x}", flags)?,
+                            Err(e) => writeln!(f, "flags: error: {}", e)?,
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Err(e) => writeln!(f, "data: error: {}", e)?,
+        }
+        write!(f, "bytes: {}", bytes.len())?;
+        for (i, line) in bytes.chunks(BYTES_PER_LINE).enumerate() {
+            write!(f, "\n{:04x} ", i * BYTES_PER_LINE)?;
+            for j in 0..BYTES_PER_LINE {
+                if j % 8 == 0 {
+                    f.write_str(" ")?;
+                }
+                match line.get(j) {
+                    Some(b) => write!(f, "{:02x} ", b)?,
+                    None => f.write_str("   ")?,
+                }
+            }
+            let ascii: String = line
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        char::from(b)
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            write!(f, " {}", ascii)?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds reparse point data from its tag, the GUID of non-Microsoft reparse points
+/// and the tag-specific data.
+pub(crate) fn build_reparse_data(tag: u32, guid: Option<Guid>, data: &[u8]) -> io::Result<Vec<u8>> {
+    let too_long = || io::Error::new(io::ErrorKind::InvalidInput, "reparse data is too long");
+    let data_len = u16::try_from(data.len()).map_err(|_| too_long())?;
+    let mut bytes = Vec::with_capacity(REPARSE_GUID_DATA_BUFFER_HEADER_SIZE + data.len());
+    bytes.extend_from_slice(&tag.to_le_bytes());
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    // Reserved
+    bytes.extend_from_slice(&0u16.to_le_bytes());
+    match (is_reparse_tag_microsoft(tag), guid) {
+        (true, None) => {}
+        (false, Some(guid)) => bytes.extend_from_slice(&guid.to_le_bytes()),
+        (true, Some(_)) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Microsoft reparse tags must not have a GUID",
+            ))
+        }
+        (false, None) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "non-Microsoft reparse tags require a GUID",
+            ))
+        }
+    }
+    bytes.extend_from_slice(data);
+    if bytes.len() > MAXIMUM_REPARSE_DATA_BUFFER_SIZE {
+        return Err(too_long());
+    }
+    Ok(bytes)
+}
+
+// Builds the data of a mount point or symlink buffer: name offsets and lengths,
+// then `extra` fields, then the path buffer.
+fn names_data(extra: &[u8], substitute_name: &[u16], print_name: &[u16]) -> io::Result<Vec<u8>> {
+    const UNICODE_NULL: [u8; 2] = [0; 2];
+    let too_long = || io::Error::new(io::ErrorKind::InvalidInput, "reparse data is too long");
+    let substitute_name_len = u16::try_from(substitute_name.len() * 2).map_err(|_| too_long())?;
+    let print_name_len = u16::try_from(print_name.len() * 2).map_err(|_| too_long())?;
+    let print_name_offset = substitute_name_len.checked_add(2).ok_or_else(too_long)?;
+
+    let mut data = Vec::with_capacity(8 + extra.len() + (substitute_name.len() + print_name.len() + 2) * 2);
+    data.extend_from_slice(&0u16.to_le_bytes());
+    data.extend_from_slice(&substitute_name_len.to_le_bytes());
+    data.extend_from_slice(&print_name_offset.to_le_bytes());
+    data.extend_from_slice(&print_name_len.to_le_bytes());
+    data.extend_from_slice(extra);
+    data.extend(substitute_name.iter().flat_map(|c| c.to_le_bytes()));
+    data.extend_from_slice(&UNICODE_NULL);
+    data.extend(print_name.iter().flat_map(|c| c.to_le_bytes()));
+    data.extend_from_slice(&UNICODE_NULL);
+    Ok(data)
+}
+
+// See `IsReparseTagMicrosoft` in winnt.h.
+pub(crate) fn is_reparse_tag_microsoft(tag: u32) -> bool {
+    tag & 0x8000_0000 != 0
+}
+
+// Reads the substitute name and print name from a mount point or symlink buffer,
+// whose names are stored in the path buffer after `header_size` bytes.
+fn parse_names(data: &[u8], header_size: usize) -> io::Result<(Vec<u16>, Vec<u16>)> {
+    let name = |index: usize| -> io::Result<Vec<u16>> { Ok(to_wide(&data[name_range(data, header_size, index)?])) };
+    Ok((name(0)?, name(4)?))
+}
+
+// Returns the byte range within `data` of the name whose offset and length are
+// stored at `index`, that is 0 for the substitute name and 4 for the print name.
+pub(crate) fn name_range(data: &[u8], header_size: usize, index: usize) -> io::Result<Range<usize>> {
+    let path_buffer = get(data, header_size, data.len().saturating_sub(header_size))?;
+    let offset = usize::from(read_u16(data, index)?);
+    let len = usize::from(read_u16(data, index + 2)?);
+    get(path_buffer, offset, len)?;
+    Ok(header_size + offset..header_size + offset + len)
+}
+
+pub(crate) fn to_wide(bytes: &[u8]) -> Vec<u16> {
+    bytes
+        .chunks_exact(2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .collect()
+}
+
+pub(crate) fn read_u32(bytes: &[u8], offset: usize) -> io::Result<u32> {
+    let b = get(bytes, offset, 4)?;
+    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> io::Result<u16> {
+    let b = get(bytes, offset, 2)?;
+    Ok(u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn get(bytes: &[u8], offset: usize, len: usize) -> io::Result<&[u8]> {
+    offset
+        .checked_add(len)
+        .and_then(|end| bytes.get(offset..end))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "reparse data is too short"))
+}
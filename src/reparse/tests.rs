@@ -0,0 +1,211 @@
+use super::*;
+
+fn wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().collect()
+}
+
+fn wide_bytes(s: &str) -> Vec<u8> {
+    wide(s).iter().flat_map(|c| c.to_le_bytes()).collect()
+}
+
+// Builds a mount point or symlink buffer with the substitute name followed by the print name.
+fn name_buffer(tag: u32, substitute_name: &str, print_name: &str, flags: Option<u32>) -> Vec<u8> {
+    let substitute_name = wide_bytes(substitute_name);
+    let print_name = wide_bytes(print_name);
+    let mut data = Vec::new();
+    data.extend_from_slice(&0u16.to_le_bytes());
+    data.extend_from_slice(&(substitute_name.len() as u16).to_le_bytes());
+    data.extend_from_slice(&(substitute_name.len() as u16 + 2).to_le_bytes());
+    data.extend_from_slice(&(print_name.len() as u16).to_le_bytes());
+    if let Some(flags) = flags {
+        data.extend_from_slice(&flags.to_le_bytes());
+    }
+    data.extend_from_slice(&substitute_name);
+    data.extend_from_slice(&[0, 0]);
+    data.extend_from_slice(&print_name);
+    data.extend_from_slice(&[0, 0]);
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&tag.to_le_bytes());
+    bytes.extend_from_slice(&(data.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(&0u16.to_le_bytes());
+    bytes.extend_from_slice(&data);
+    bytes
+}
+
+#[test]
+fn parse_mount_point() {
+    let bytes = name_buffer(IO_REPARSE_TAG_MOUNT_POINT, r"\??\C:\foo", r"C:\foo", None);
+    match parse_reparse_data(&bytes).unwrap() {
+        ReparseData::MountPoint(data) => {
+            assert_eq!(data.substitute_name(), &wide(r"\??\C:\foo")[..]);
+            assert_eq!(data.print_name(), &wide(r"C:\foo")[..]);
+        }
+        other => panic!("not a mount point: {:?}", other),
+    }
+}
+
+#[test]
+fn parse_symlink() {
+    let bytes = name_buffer(
+        IO_REPARSE_TAG_SYMLINK,
+        r"..\foo",
+        r"..\foo",
+        Some(SYMLINK_FLAG_RELATIVE),
+    );
+    match parse_reparse_data(&bytes).unwrap() {
+        ReparseData::Symlink(data) => {
+            assert_eq!(data.substitute_name(), &wide(r"..\foo")[..]);
+            assert_eq!(data.print_name(), &wide(r"..\foo")[..]);
+            assert!(data.is_relative());
+        }
+        other => panic!("not a symlink: {:?}", other),
+    }
+}
+
+#[test]
+fn parse_other_with_guid() {
+    let guid = Guid::from_u128(0x01234567_89ab_cdef_0123_456789abcdef);
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&0x1234u32.to_le_bytes());
+    bytes.extend_from_slice(&3u16.to_le_bytes());
+    bytes.extend_from_slice(&0u16.to_le_bytes());
+    bytes.extend_from_slice(&guid.to_le_bytes());
+    bytes.extend_from_slice(b"abc");
+    let data = parse_reparse_data(&bytes).unwrap();
+    assert_eq!(data.tag(), 0x1234);
+    assert_eq!(
+        data,
+        ReparseData::Other {
+            tag: 0x1234,
+            guid: Some(guid),
+            data: b"abc".to_vec(),
+        }
+    );
+}
+
+#[test]
+fn parse_truncated() {
+    let bytes = name_buffer(IO_REPARSE_TAG_MOUNT_POINT, r"\??\C:\foo", "", None);
+    for len in 0..bytes.len() - 2 {
+        match parse_reparse_data(&bytes[..len]) {
+            Err(ref e) if e.kind() == io::ErrorKind::InvalidData => {}
+            other => panic!("truncated to {} bytes: {:?}", len, other),
+        }
+    }
+}
+
+#[test]
+fn guid_round_trip() {
+    let guid = Guid::from_u128(0x01234567_89ab_cdef_0123_456789abcdef);
+    assert_eq!(guid.to_u128(), 0x01234567_89ab_cdef_0123_456789abcdef);
+    assert_eq!(Guid::from_le_bytes(guid.to_le_bytes()), guid);
+    assert_eq!(guid.to_string(), "{01234567-89AB-CDEF-0123-456789ABCDEF}");
+}
+
+#[test]
+fn serialize_round_trip() {
+    let guid = Guid::from_u128(0x01234567_89ab_cdef_0123_456789abcdef);
+    let all = [
+        ReparseData::MountPoint(MountPointData::new(wide(r"\??\C:\foo"), wide(r"C:\foo"))),
+        ReparseData::Symlink(SymlinkData::new(
+            wide(r"..\foo"),
+            wide(r"..\foo"),
+            SYMLINK_FLAG_RELATIVE,
+        )),
+        ReparseData::Other {
+            tag: 0x1234,
+            guid: Some(guid),
+            data: b"abc".to_vec(),
+        },
+    ];
+    for data in &all {
+        assert_eq!(&parse_reparse_data(&data.to_bytes().unwrap()).unwrap(), data);
+    }
+
+    // Same layout as written by `create`
+    let bytes = name_buffer(IO_REPARSE_TAG_MOUNT_POINT, r"\??\C:\foo", "", None);
+    let data = ReparseData::MountPoint(MountPointData::new(wide(r"\??\C:\foo"), Vec::new()));
+    assert_eq!(data.to_bytes().unwrap(), bytes);
+}
+
+#[test]
+fn serialize_too_long() {
+    let name = vec![u16::from(b'a'); MAXIMUM_REPARSE_DATA_BUFFER_SIZE / 2];
+    let data = ReparseData::MountPoint(MountPointData::new(name, Vec::new()));
+    match data.to_bytes() {
+        Err(ref e) if e.kind() == io::ErrorKind::InvalidInput => {}
+        other => panic!("reparse data is too long: {:?}", other),
+    }
+
+    let data = ReparseData::Other {
+        tag: 0x1234,
+        guid: None,
+        data: Vec::new(),
+    };
+    match data.to_bytes() {
+        Err(ref e) if e.kind() == io::ErrorKind::InvalidInput => {}
+        other => panic!("non-Microsoft reparse tags require a GUID: {:?}", other),
+    }
+}
+
+#[test]
+fn dump_mount_point() {
+    let bytes = name_buffer(IO_REPARSE_TAG_MOUNT_POINT, r"\??\C:\foo", "", None);
+    let dump = ReparseDump::new(bytes.clone()).to_string();
+    assert!(
+        dump.starts_with("tag: 0xa0000003 (mount point)\ndata length: 32\n"),
+        "{}",
+        dump
+    );
+    assert!(
+        dump.contains(r#"substitute name: offset 0, length 20: "\\??\\C:\\foo""#),
+        "{}",
+        dump
+    );
+    assert!(dump.contains("print name: offset 22, length 0: \"\"\n"), "{}", dump);
+    assert!(
+        dump.contains("\n0000  03 00 00 a0 20 00 00 00  00 00 14 00 16 00 00 00  .... ...........\n"),
+        "{}",
+        dump
+    );
+    assert_eq!(ReparseDump::new(bytes.clone()).into_bytes(), bytes);
+}
+
+#[test]
+fn dump_truncated() {
+    let bytes = name_buffer(IO_REPARSE_TAG_MOUNT_POINT, r"\??\C:\foo", "", None);
+    let dump = ReparseDump::new(bytes[..20].to_vec()).to_string();
+    assert!(dump.contains("data: error: reparse data is too short\n"), "{}", dump);
+    assert!(
+        dump.ends_with("\n0010  5c 00 3f 00                                       \\.?."),
+        "{}",
+        dump
+    );
+}
+
+#[test]
+fn reparse_tag_classification() {
+    assert_eq!(ReparseTag::from(IO_REPARSE_TAG_MOUNT_POINT), ReparseTag::MountPoint);
+    assert_eq!(ReparseTag::from(IO_REPARSE_TAG_SYMLINK), ReparseTag::Symlink);
+    assert_eq!(ReparseTag::from(0x8000_001B), ReparseTag::AppExecLink);
+    assert_eq!(ReparseTag::from(0xA000_001D), ReparseTag::LxSymlink);
+    assert_eq!(ReparseTag::from(0x8000_0013), ReparseTag::Dedup);
+    assert_eq!(ReparseTag::from(0x9000_001A), ReparseTag::CloudFile);
+    assert_eq!(ReparseTag::from(0x9000_F01A), ReparseTag::CloudFile);
+    assert_eq!(ReparseTag::from(0x9000_001B), ReparseTag::Unknown(0x9000_001B));
+    assert_eq!(ReparseTag::MountPoint.to_string(), "mount point");
+    assert_eq!(ReparseTag::Unknown(0x1234).to_string(), "0x00001234");
+}
+
+#[test]
+fn maximum_junction_target_len() {
+    let data = |len: usize| {
+        let mut substitute_name = wide(r"\??\");
+        substitute_name.resize(substitute_name.len() + len, u16::from(b'a'));
+        ReparseData::MountPoint(MountPointData::new(substitute_name, Vec::new()))
+    };
+    let bytes = data(crate::consts::MAXIMUM_JUNCTION_TARGET_LEN).to_bytes().unwrap();
+    assert_eq!(bytes.len(), MAXIMUM_REPARSE_DATA_BUFFER_SIZE);
+    assert!(data(crate::consts::MAXIMUM_JUNCTION_TARGET_LEN + 1).to_bytes().is_err());
+}
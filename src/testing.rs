@@ -0,0 +1,176 @@
+//! An in-memory file system implementing [`JunctionOps`], for testing code
+//! that manages junction points on any platform.
+//!
+//! [`MemoryFs`] models directories, files and junction points, and fails like
+//! the real file system does in the common error cases: creating a junction
+//! point where an entry already exists or in a missing directory, and querying
+//! or deleting an entry that is not a reparse point, which fails with
+//! `ERROR_NOT_A_REPARSE_POINT` as raw OS error.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::path::Path;
+//! use junction::testing::MemoryFs;
+//! use junction::JunctionOps;
+//!
+//! let fs = MemoryFs::new();
+//! fs.create_dir_all("/app/versions/1.0");
+//! fs.create(Path::new("/app/versions/1.0"), Path::new("/app/current")).unwrap();
+//! assert!(fs.exists(Path::new("/app/current")).unwrap());
+//! assert!(fs.exists(Path::new("/app/versions")).is_err());
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use crate::consts::ERROR_NOT_A_REPARSE_POINT;
+use crate::JunctionOps;
+
+/// An entry of a [`MemoryFs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Entry {
+    /// A plain directory.
+    Dir,
+    /// A regular file.
+    File,
+    /// A junction point, with its target.
+    Junction(PathBuf),
+}
+
+/// An in-memory file system of directories, files and junction points.
+///
+/// Paths are compared component by component, and case-sensitively. They are
+/// not resolved through junction points, and root directories such as `C:\`
+/// always exist. Junction points can target missing directories, as on NTFS,
+/// but their targets must start at a root, such as `C:\` or `/`, since there
+/// is no current directory.
+#[derive(Debug, Default)]
+pub struct MemoryFs {
+    entries: Mutex<BTreeMap<PathBuf, Entry>>,
+}
+
+impl MemoryFs {
+    /// Creates an empty file system.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates the directory `path` and its missing parents, like [`std::fs::create_dir_all`].
+    pub fn create_dir_all<P: AsRef<Path>>(&self, path: P) {
+        let mut entries = self.lock();
+        let mut dir = PathBuf::new();
+        for component in path.as_ref().components() {
+            dir.push(component);
+            if !is_root(&dir) {
+                entries.entry(dir.clone()).or_insert(Entry::Dir);
+            }
+        }
+    }
+
+    /// Creates the file `path`, and its missing parent directories.
+    pub fn create_file<P: AsRef<Path>>(&self, path: P) {
+        let path = key(path.as_ref());
+        if let Some(parent) = path.parent() {
+            self.create_dir_all(parent);
+        }
+        self.lock().insert(path, Entry::File);
+    }
+
+    /// Returns the entry at `path`, or `None` if it does not exist.
+    pub fn entry<P: AsRef<Path>>(&self, path: P) -> Option<Entry> {
+        self.lock().get(&key(path.as_ref())).cloned()
+    }
+
+    /// Returns all entries, sorted by path.
+    pub fn entries(&self) -> Vec<(PathBuf, Entry)> {
+        self.lock()
+            .iter()
+            .map(|(path, entry)| (path.clone(), entry.clone()))
+            .collect()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<PathBuf, Entry>> {
+        // A panic while holding the lock cannot leave the map half updated.
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl JunctionOps for MemoryFs {
+    fn create(&self, target: &Path, junction: &Path) -> io::Result<()> {
+        if !target.has_root() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "`target` must start at a root directory",
+            ));
+        }
+        let junction = key(junction);
+        let mut entries = self.lock();
+        if is_root(&junction) || entries.contains_key(&junction) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "`junction` already exists",
+            ));
+        }
+        match junction.parent() {
+            Some(parent) if is_root(parent) || entries.get(parent) == Some(&Entry::Dir) => {}
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "the parent directory of `junction` does not exist",
+                ))
+            }
+        }
+        entries.insert(junction, Entry::Junction(key(target)));
+        Ok(())
+    }
+
+    fn delete(&self, junction: &Path) -> io::Result<()> {
+        let mut entries = self.lock();
+        match entries.get_mut(&key(junction)) {
+            // The directory of the junction point is left behind, as on NTFS.
+            Some(entry @ Entry::Junction(_)) => {
+                *entry = Entry::Dir;
+                Ok(())
+            }
+            Some(_) => Err(io::Error::from_raw_os_error(ERROR_NOT_A_REPARSE_POINT)),
+            None => Err(not_found()),
+        }
+    }
+
+    fn get_target(&self, junction: &Path) -> io::Result<PathBuf> {
+        match self.lock().get(&key(junction)) {
+            Some(Entry::Junction(target)) => Ok(target.clone()),
+            Some(_) => Err(io::Error::from_raw_os_error(ERROR_NOT_A_REPARSE_POINT)),
+            None => Err(not_found()),
+        }
+    }
+
+    fn exists(&self, junction: &Path) -> io::Result<bool> {
+        match self.lock().get(&key(junction)) {
+            Some(Entry::Junction(_)) => Ok(true),
+            Some(_) => Err(io::Error::from_raw_os_error(ERROR_NOT_A_REPARSE_POINT)),
+            None => Ok(false),
+        }
+    }
+}
+
+// Normalizes `path` into the key of its entry, so that `C:\a\` and `C:\a` are the same.
+fn key(path: &Path) -> PathBuf {
+    path.components().collect()
+}
+
+// Whether `path` has no name, like `C:\` or `/`.
+fn is_root(path: &Path) -> bool {
+    !path.components().any(|c| matches!(c, Component::Normal(_)))
+}
+
+fn not_found() -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, "`junction` does not exist")
+}
@@ -0,0 +1,63 @@
+use super::*;
+
+const NOT_A_REPARSE_POINT: Option<i32> = Some(ERROR_NOT_A_REPARSE_POINT);
+
+#[test]
+fn create_query_and_delete() {
+    let fs = MemoryFs::new();
+    let target = Path::new("/data/target");
+    let junction = Path::new("/data/junction");
+    fs.create_dir_all("/data");
+
+    assert!(!fs.exists(junction).unwrap());
+    fs.create(target, junction).unwrap();
+    assert!(fs.exists(junction).unwrap());
+    assert_eq!(fs.get_target(junction).unwrap(), target);
+    assert_eq!(fs.entry("/data/junction/"), Some(Entry::Junction(target.to_path_buf())));
+
+    fs.delete(junction).unwrap();
+    assert_eq!(fs.entry(junction), Some(Entry::Dir));
+    assert_eq!(fs.exists(junction).unwrap_err().raw_os_error(), NOT_A_REPARSE_POINT);
+}
+
+#[test]
+fn errors_match_the_file_system() {
+    let fs = MemoryFs::new();
+    fs.create_dir_all("/data/dir");
+    fs.create_file("/data/file");
+    let target = Path::new("/target");
+
+    let kind = |result: io::Result<()>| result.unwrap_err().kind();
+    assert_eq!(
+        kind(fs.create(target, Path::new("/data/dir"))),
+        io::ErrorKind::AlreadyExists
+    );
+    assert_eq!(kind(fs.create(target, Path::new("/"))), io::ErrorKind::AlreadyExists);
+    assert_eq!(
+        kind(fs.create(target, Path::new("/missing/junction"))),
+        io::ErrorKind::NotFound
+    );
+    assert_eq!(
+        kind(fs.create(target, Path::new("/data/file/junction"))),
+        io::ErrorKind::NotFound
+    );
+    assert_eq!(
+        kind(fs.create(Path::new("relative"), Path::new("/data/junction"))),
+        io::ErrorKind::InvalidInput
+    );
+
+    for path in ["/data/dir", "/data/file"] {
+        let path = Path::new(path);
+        assert_eq!(fs.get_target(path).unwrap_err().raw_os_error(), NOT_A_REPARSE_POINT);
+        assert_eq!(fs.delete(path).unwrap_err().raw_os_error(), NOT_A_REPARSE_POINT);
+    }
+    assert_eq!(kind(fs.delete(Path::new("/data/missing"))), io::ErrorKind::NotFound);
+    assert_eq!(
+        fs.entries(),
+        [
+            (PathBuf::from("/data"), Entry::Dir),
+            (PathBuf::from("/data/dir"), Entry::Dir),
+            (PathBuf::from("/data/file"), Entry::File),
+        ]
+    );
+}
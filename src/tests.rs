@@ -1,9 +1,13 @@
+use std::ffi::OsString;
 use std::fs::{self, File};
 use std::io::{self, Write};
-use std::os::windows::fs::symlink_file;
-#[cfg(miri)]
+use std::os::windows::ffi::OsStringExt;
+use std::os::windows::fs::{symlink_dir, symlink_file, MetadataExt, OpenOptionsExt};
+use std::os::windows::io::{FromRawHandle, IntoRawHandle};
 use std::path::{Path, PathBuf};
 
+use crate::consts::ERROR_NOT_A_REPARSE_POINT;
+
 #[cfg(not(miri))]
 use tempfile::TempDir;
 
@@ -20,7 +24,6 @@ impl TempDir {
 }
 
 // https://docs.microsoft.com/en-us/windows/desktop/debug/system-error-codes
-const ERROR_NOT_A_REPARSE_POINT: i32 = 0x1126;
 const ERROR_ALREADY_EXISTS: i32 = 0xb7;
 
 #[cfg(not(miri))]
@@ -245,3 +248,2063 @@ fn get_target_user_dirs() {
         other => panic!("target path is not a junction point: {:?}", other),
     }
 }
+
+#[test]
+fn mount_volume_invalid_volume() {
+    let tmpdir = create_tempdir();
+    let mount_point = tmpdir.path().join("mount_point");
+    fs::create_dir_all(&mount_point).unwrap();
+
+    let volume = r"\\?\Volume{00000000-0000-0000-0000-000000000000}\";
+    assert!(super::mount_volume(volume, &mount_point).is_err());
+    assert!(super::unmount_volume(&mount_point).is_err());
+}
+
+#[test]
+fn list_volume_mount_points_not_a_volume() {
+    let tmpdir = create_tempdir();
+    let not_a_volume = tmpdir.path().join("not_a_volume");
+    assert!(super::list_volume_mount_points(not_a_volume).is_err());
+}
+
+#[test]
+fn get_kind_directory_junction() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+
+    super::create(&target, &junction).unwrap();
+    assert_eq!(super::get_kind(&junction).unwrap(), super::JunctionKind::Directory);
+
+    match super::get_kind(&target) {
+        Err(ref e) if e.raw_os_error() == Some(ERROR_NOT_A_REPARSE_POINT) => {}
+        other => panic!("target path is not a junction point: {:?}", other),
+    }
+}
+
+#[test]
+fn can_create_junctions_in_tempdir() {
+    let tmpdir = create_tempdir();
+    let capabilities = super::can_create_junctions(tmpdir.path()).unwrap();
+    assert!(capabilities.is_supported(), "{:?}", capabilities.error());
+    assert_eq!(fs::read_dir(tmpdir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn filesystem_capabilities_of_tempdir() {
+    let tmpdir = create_tempdir();
+    let capabilities = super::filesystem_capabilities(tmpdir.path()).unwrap();
+    assert!(capabilities.supports_reparse_points());
+    assert!(capabilities.case_preserved_names());
+    assert!(capabilities.cluster_size() >= u64::from(capabilities.bytes_per_sector()));
+    assert!(capabilities.bytes_per_sector() > 0);
+    assert!(capabilities.is_case_sensitive_dir() != Some(true));
+
+    let file = tmpdir.path().join("file");
+    fs::write(&file, b"").unwrap();
+    let file_capabilities = super::filesystem_capabilities(&file).unwrap();
+    assert_eq!(file_capabilities.volume(), capabilities.volume());
+}
+
+#[test]
+fn options_without_privilege_adjustment() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+
+    let mut options = super::Options::new();
+    options.privileges(super::PrivilegePolicy::Never);
+    options.create(&target, &junction).unwrap();
+    assert_eq!(options.get_target(&junction).unwrap(), target);
+    options.delete(&junction).unwrap();
+    match options.exists(&junction) {
+        Err(ref e) if e.raw_os_error() == Some(ERROR_NOT_A_REPARSE_POINT) => {}
+        other => panic!("junction had been deleted: {:?}", other),
+    }
+}
+
+#[test]
+fn options_restore_privileges() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+
+    let mut options = super::Options::new();
+    options.restore_privileges(true);
+    options.create(&target, &junction).unwrap();
+    assert_eq!(options.get_target(&junction).unwrap(), target);
+    options.delete(&junction).unwrap();
+}
+
+#[test]
+fn options_restore_privileges_concurrently() {
+    // No other test enables it, and administrators hold it.
+    const PRIVILEGE: &str = "SeTakeOwnershipPrivilege";
+    let is_enabled = || {
+        let diagnostics = super::explain(".");
+        let privileges = diagnostics.privileges().unwrap();
+        privileges
+            .iter()
+            .find(|p| p.name() == PRIVILEGE)
+            .map(|p| p.is_enabled())
+    };
+    if is_enabled() != Some(false) {
+        // Not held, or already enabled by the test runner.
+        return;
+    }
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+    super::create(&target, &junction).unwrap();
+
+    let mut options = super::Options::new();
+    options
+        .privileges(super::PrivilegePolicy::Always(&[PRIVILEGE]))
+        .restore_privileges(true);
+    // Operations completing on other threads leave the privilege enabled while this one holds it.
+    let file = options.open_file(&junction, false).unwrap();
+    assert_eq!(is_enabled(), Some(true));
+    let threads: Vec<_> = (0..8)
+        .map(|_| {
+            let options = options.clone();
+            let (target, junction) = (target.clone(), junction.clone());
+            std::thread::spawn(move || {
+                for _ in 0..20 {
+                    assert_eq!(options.get_target(&junction).unwrap(), target);
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert_eq!(is_enabled(), Some(true));
+    drop(file);
+    assert_eq!(is_enabled(), Some(false));
+}
+
+#[test]
+fn options_share_mode() {
+    const FILE_SHARE_READ: u32 = 0x1;
+    const FILE_SHARE_WRITE: u32 = 0x2;
+    const FILE_SHARE_DELETE: u32 = 0x4;
+    const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+    const FILE_FLAG_OPEN_REPARSE_POINT: u32 = 0x0020_0000;
+
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+    super::create(&target, &junction).unwrap();
+
+    let mut options = super::Options::new();
+    options.share_mode(FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE);
+    // Keep the junction open in another handle that shares everything.
+    let _file = fs::OpenOptions::new()
+        .read(true)
+        .share_mode(FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE)
+        .custom_flags(FILE_FLAG_OPEN_REPARSE_POINT | FILE_FLAG_BACKUP_SEMANTICS)
+        .open(&junction)
+        .unwrap();
+    assert_eq!(options.get_target(&junction).unwrap(), target);
+}
+
+#[test]
+fn options_retry_sharing_violation() {
+    const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+    const FILE_FLAG_OPEN_REPARSE_POINT: u32 = 0x0020_0000;
+
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+    super::create(&target, &junction).unwrap();
+
+    // Hold the junction open without sharing it, then release it shortly after.
+    let file = fs::OpenOptions::new()
+        .read(true)
+        .share_mode(0)
+        .custom_flags(FILE_FLAG_OPEN_REPARSE_POINT | FILE_FLAG_BACKUP_SEMANTICS)
+        .open(&junction)
+        .unwrap();
+    let holder = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        drop(file);
+    });
+    let target_read = super::Options::new()
+        .retry(20, std::time::Duration::from_millis(10))
+        .get_target(&junction);
+    holder.join().unwrap();
+    assert_eq!(target_read.unwrap(), target);
+}
+
+#[test]
+fn options_retry_skips_permission_errors() {
+    use std::time::{Duration, Instant};
+
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+    // The DACL of these junctions denies everyone to list them.
+    super::Options::new()
+        .os_compatibility(true)
+        .create(&target, &junction)
+        .unwrap();
+
+    let mut options = super::Options::new();
+    options
+        .privileges(super::PrivilegePolicy::Never)
+        .retry(3, Duration::from_secs(1));
+    let start = Instant::now();
+    let err = options.get_target(&junction).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[test]
+fn options_adopt_existing() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+    fs::create_dir_all(&junction).unwrap();
+
+    match super::create(&target, &junction) {
+        Err(ref e) if e.raw_os_error() == Some(ERROR_ALREADY_EXISTS) => {}
+        other => panic!("junction path already exists: {:?}", other),
+    }
+    let mut options = super::Options::new();
+    options.adopt_existing(true);
+    options.create(&target, &junction).unwrap();
+    assert_eq!(super::get_target(&junction).unwrap(), target);
+
+    // An existing junction is not an empty directory to adopt.
+    match options.create(&target, &junction) {
+        Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+        other => panic!("junction path is already a junction: {:?}", other),
+    }
+    let not_empty = tmpdir.path().join("not_empty");
+    fs::create_dir_all(not_empty.join("child")).unwrap();
+    match options.create(&target, &not_empty) {
+        Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+        other => panic!("junction path is not empty: {:?}", other),
+    }
+}
+
+#[test]
+fn options_create_parents() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("a").join("b").join("junction");
+    fs::create_dir_all(&target).unwrap();
+
+    match super::create(&target, &junction) {
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => {}
+        other => panic!("parent of the junction does not exist: {:?}", other),
+    }
+    super::Options::new()
+        .create_parents(true)
+        .create(&target, &junction)
+        .unwrap();
+    assert_eq!(super::get_target(&junction).unwrap(), target);
+}
+
+#[test]
+fn create_idempotent_same_target() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let other = tmpdir.path().join("other");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+    fs::create_dir_all(&other).unwrap();
+
+    assert!(super::create_idempotent(&target, &junction).unwrap());
+    assert!(!super::create_idempotent(&target, &junction).unwrap());
+    let upper = PathBuf::from(target.to_str().unwrap().to_uppercase() + "\\");
+    assert!(!super::create_idempotent(&upper, &junction).unwrap());
+    match super::create_idempotent(&other, &junction) {
+        Err(ref e) if e.raw_os_error() == Some(ERROR_ALREADY_EXISTS) => {}
+        other => panic!("junction points to another target: {:?}", other),
+    }
+}
+
+#[test]
+fn verify_targets() {
+    use super::Verification;
+
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let other = tmpdir.path().join("other");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+    fs::create_dir_all(&other).unwrap();
+
+    assert_eq!(super::verify(&junction, &target).unwrap(), Verification::Missing);
+    super::create(&target, &junction).unwrap();
+    assert_eq!(super::verify(&junction, &target).unwrap(), Verification::Match);
+    let upper = PathBuf::from(target.to_str().unwrap().to_uppercase());
+    assert_eq!(super::verify(&junction, &upper).unwrap(), Verification::Match);
+    let verbatim = PathBuf::from(format!(r"\\?\{}", target.display()));
+    assert_eq!(super::verify(&junction, &verbatim).unwrap(), Verification::Match);
+    assert_eq!(
+        super::verify(&junction, &other).unwrap(),
+        Verification::DifferentTarget(target.clone())
+    );
+    assert_eq!(super::verify(&target, &target).unwrap(), Verification::NotAJunction);
+}
+
+#[test]
+fn set_target_and_overwrite() {
+    let tmpdir = create_tempdir();
+    let v1 = tmpdir.path().join("v1");
+    let v2 = tmpdir.path().join("v2");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&v1).unwrap();
+    fs::create_dir_all(&v2).unwrap();
+
+    super::create(&v1, &junction).unwrap();
+    super::set_target(&junction, &v2).unwrap();
+    assert_eq!(super::get_target(&junction).unwrap(), v2);
+
+    super::Options::new().overwrite(true).create(&v1, &junction).unwrap();
+    assert_eq!(super::get_target(&junction).unwrap(), v1);
+
+    // Only junctions are overwritten.
+    match super::Options::new().overwrite(true).create(&v1, &v2) {
+        Err(ref e) if e.raw_os_error() == Some(ERROR_ALREADY_EXISTS) => {}
+        other => panic!("v2 is not a junction: {:?}", other),
+    }
+    match super::set_target(&v2, &v1) {
+        Err(ref e) if e.raw_os_error() == Some(ERROR_NOT_A_REPARSE_POINT) => {}
+        other => panic!("v2 is not a junction: {:?}", other),
+    }
+}
+
+#[test]
+fn same_target_compares_targets() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let other = tmpdir.path().join("other");
+    let a = tmpdir.path().join("a");
+    let b = tmpdir.path().join("b");
+    let c = tmpdir.path().join("c");
+    let via = tmpdir.path().join("via");
+    fs::create_dir_all(&target).unwrap();
+    fs::create_dir_all(&other).unwrap();
+
+    super::create(&target, &a).unwrap();
+    super::create(&target, &b).unwrap();
+    super::create(&other, &c).unwrap();
+    assert!(super::same_target(&a, &b).unwrap());
+    assert!(!super::same_target(&a, &c).unwrap());
+
+    // `via` leads to `target` through `a`, which only file IDs can tell.
+    super::create(&a, &via).unwrap();
+    assert!(!super::same_target(&a, &via).unwrap());
+    let mut opts = super::Options::new();
+    opts.compare_file_ids(true);
+    assert!(opts.same_target(&a, &via).unwrap());
+    assert!(!opts.same_target(&a, &c).unwrap());
+}
+
+#[test]
+fn file_id_of_junction_and_target() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+    super::create(&target, &junction).unwrap();
+
+    let target_id = super::file_id(&target).unwrap();
+    assert_eq!(super::file_id(&target).unwrap(), target_id);
+    assert_ne!(super::file_id(&junction).unwrap(), target_id);
+    assert_eq!(super::target_file_id(&junction).unwrap(), target_id);
+    assert_eq!(
+        super::file_id(&junction).unwrap().volume_serial_number(),
+        target_id.volume_serial_number()
+    );
+
+    fs::remove_dir(&target).unwrap();
+    assert!(super::target_file_id(&junction).is_err());
+}
+
+#[test]
+fn delete_checks_tag_and_target() {
+    const IO_REPARSE_TAG_SYMLINK: u32 = 0xA000_000C;
+
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let other = tmpdir.path().join("other");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+    fs::create_dir_all(&other).unwrap();
+    super::create(&target, &junction).unwrap();
+
+    let err = super::delete_if_target(&junction, &other).unwrap_err();
+    let mismatch = err.get_ref().unwrap().downcast_ref::<super::TargetMismatch>().unwrap();
+    assert_eq!(mismatch.target(), target);
+    assert_eq!(super::get_target(&junction).unwrap(), target);
+    super::delete_if_target(&junction, &target).unwrap();
+    // Only the reparse point is deleted, leaving an empty directory behind.
+    assert!(fs::read_dir(&junction).unwrap().next().is_none());
+
+    let junction = tmpdir.path().join("typed");
+    super::create(&target, &junction).unwrap();
+    assert_eq!(
+        super::delete_if_target_is(&junction, &other).unwrap(),
+        super::DeleteOutcome::DifferentTarget(target.clone())
+    );
+    assert_eq!(super::get_target(&junction).unwrap(), target);
+    assert_eq!(
+        super::delete_if_target_is(&junction, &target).unwrap(),
+        super::DeleteOutcome::Deleted
+    );
+    assert!(!super::exists(&junction).unwrap_or(false));
+
+    // Symbolic links require a privilege or developer mode.
+    let link = tmpdir.path().join("link");
+    if symlink_file(&target, &link).is_err() {
+        return;
+    }
+    let err = super::delete(&link).unwrap_err();
+    let mismatch = err.get_ref().unwrap().downcast_ref::<super::TagMismatch>().unwrap();
+    assert_eq!(mismatch.tag(), IO_REPARSE_TAG_SYMLINK);
+    assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+}
+
+#[test]
+fn delete_and_remove_dir_keeps_target() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    let file = target.join("file");
+    fs::create_dir_all(&target).unwrap();
+    File::create(&file).unwrap().write_all(b"foo").unwrap();
+    super::create(&target, &junction).unwrap();
+
+    super::delete_and_remove_dir(&junction).unwrap();
+    assert!(fs::symlink_metadata(&junction).is_err());
+    assert!(file.exists());
+
+    match super::delete_and_remove_dir(&target) {
+        Err(ref e) if e.raw_os_error() == Some(ERROR_NOT_A_REPARSE_POINT) => {}
+        other => panic!("target is not a junction: {:?}", other),
+    }
+    assert!(file.exists());
+}
+
+#[test]
+fn delete_all_under_tree() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let root = tmpdir.path().join("root");
+    let nested = root.join("a").join("b");
+    let canary = target.join("do_not_delete");
+    fs::create_dir_all(target.join("inner")).unwrap();
+    fs::create_dir_all(&nested).unwrap();
+    File::create(&canary).unwrap().write_all(b"foo").unwrap();
+    // A junction inside the target must not be reached through the junctions below.
+    super::create(&root, target.join("inner").join("back")).unwrap();
+    super::create(&target, root.join("top")).unwrap();
+    super::create(&target, nested.join("deep")).unwrap();
+    super::create(tmpdir.path().join("missing"), nested.join("dangling")).unwrap();
+
+    let results = super::delete_all(&root).unwrap();
+    let deleted: Vec<_> = results.iter().map(|(path, _)| path.clone()).collect();
+    assert_eq!(
+        deleted,
+        vec![nested.join("dangling"), nested.join("deep"), root.join("top")]
+    );
+    assert!(results.iter().all(|(_, result)| result.is_ok()));
+    assert!(root.join("top").is_dir());
+    assert!(canary.exists());
+    assert!(super::exists(target.join("inner").join("back")).unwrap());
+
+    super::create(&target, root.join("top2")).unwrap();
+    let results = super::delete_all_and_remove_dirs(&root).unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(fs::symlink_metadata(root.join("top2")).is_err());
+    assert!(root.join("top").is_dir());
+    assert!(canary.exists());
+}
+
+#[test]
+fn options_relative_to_junction() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let other = tmpdir.path().join("other");
+    let junction = tmpdir.path().join("links").join("junction");
+    fs::create_dir_all(&target).unwrap();
+    fs::create_dir_all(&other).unwrap();
+    fs::create_dir_all(junction.parent().unwrap()).unwrap();
+
+    let mut opts = super::Options::new();
+    opts.relative_to_junction(true);
+    opts.create(r"..\target", &junction).unwrap();
+    assert_eq!(super::get_target(&junction).unwrap(), target);
+    assert_eq!(
+        opts.verify(&junction, r"..\target").unwrap(),
+        super::Verification::Match
+    );
+    opts.set_target(&junction, r"..\other").unwrap();
+    assert_eq!(super::get_target(&junction).unwrap(), other);
+
+    // Absolute targets are used as is.
+    opts.set_target(&junction, &target).unwrap();
+    assert_eq!(super::get_target(&junction).unwrap(), target);
+}
+
+#[test]
+fn create_with_base_resolves_lexically() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    fs::create_dir_all(&target).unwrap();
+
+    super::create_with_base(tmpdir.path(), r".\a\..\target", "junction").unwrap();
+    assert_eq!(super::get_target(tmpdir.path().join("junction")).unwrap(), target);
+    super::create_with_base(tmpdir.path(), "target/", tmpdir.path().join("slashes")).unwrap();
+    assert_eq!(super::get_target(tmpdir.path().join("slashes")).unwrap(), target);
+
+    match super::create_with_base("base", "target", "junction") {
+        Err(ref e) if e.kind() == io::ErrorKind::InvalidInput => {}
+        other => panic!("base is not absolute: {:?}", other),
+    }
+}
+
+#[test]
+fn create_rejects_unc_targets() {
+    let tmpdir = create_tempdir();
+    let junction = tmpdir.path().join("junction");
+
+    for target in [r"\\server\share\dir", r"\\?\UNC\server\share\dir"] {
+        let err = super::create(target, &junction).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(err.get_ref().unwrap().is::<super::UnsupportedTarget>());
+        assert!(fs::symlink_metadata(&junction).is_err());
+    }
+
+    super::Options::new()
+        .allow_unc_targets(true)
+        .create(r"\\server\share\dir", &junction)
+        .unwrap();
+    assert!(super::exists(&junction).unwrap());
+}
+
+#[test]
+fn device_path_targets() {
+    let tmpdir = create_tempdir();
+    let junction = tmpdir.path().join("junction");
+    let target = Path::new(r"\\?\GLOBALROOT\Device\HarddiskVolume1\dir");
+
+    match super::parse_reparse_data(&super::mount_point_data(target).unwrap()).unwrap() {
+        super::ReparseData::MountPoint(data) => {
+            assert_eq!(
+                String::from_utf16_lossy(data.substitute_name()),
+                r"\Device\HarddiskVolume1\dir"
+            );
+        }
+        other => panic!("not a mount point: {:?}", other),
+    }
+
+    super::create(target, &junction).unwrap();
+    assert_eq!(super::get_target(&junction).unwrap(), target);
+    assert_eq!(super::get_kind(&junction).unwrap(), super::JunctionKind::DevicePath);
+    assert_eq!(
+        super::verify(&junction, r"\\?\globalroot\Device\HarddiskVolume1\dir\").unwrap(),
+        super::Verification::Match
+    );
+}
+
+#[test]
+fn read_junction_with_missing_target() {
+    // Junctions in Volume Shadow Copy snapshots usually point to directories
+    // of the live volume that no longer exist.
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    super::create(&target, &junction).unwrap();
+
+    assert!(super::exists(&junction).unwrap());
+    assert_eq!(super::get_target(&junction).unwrap(), target);
+    assert_eq!(super::get_kind(&junction).unwrap(), super::JunctionKind::Directory);
+
+    let shadow = r"\\.\GLOBALROOT\Device\HarddiskVolumeShadowCopy1\dir";
+    super::set_target(&junction, shadow).unwrap();
+    assert_eq!(
+        super::get_target(&junction).unwrap(),
+        Path::new(r"\\?\GLOBALROOT\Device\HarddiskVolumeShadowCopy1\dir")
+    );
+}
+
+#[test]
+fn create_rejects_trailing_dots_and_spaces() {
+    let tmpdir = create_tempdir();
+    let junction = tmpdir.path().join("junction");
+    let mut verbatim = std::ffi::OsString::from(r"\\?\");
+    verbatim.push(tmpdir.path());
+
+    for name in [r"dir.", r"dir ", r"dir.\sub"] {
+        let target = PathBuf::from(&verbatim).join(name);
+        let err = super::create(&target, &junction).unwrap_err();
+        let unsupported = err
+            .get_ref()
+            .unwrap()
+            .downcast_ref::<super::UnsupportedTarget>()
+            .unwrap();
+        assert_eq!(unsupported.reason(), super::UnsupportedTargetReason::TrailingDotOrSpace);
+    }
+
+    let target = PathBuf::from(&verbatim).join("dir.");
+    super::Options::new()
+        .allow_trailing_dots_and_spaces(true)
+        .create(&target, &junction)
+        .unwrap();
+    assert!(super::exists(&junction).unwrap());
+}
+
+#[test]
+fn options_normalization_separators_only() {
+    let tmpdir = create_tempdir();
+    let junction = tmpdir.path().join("junction");
+    let mut opts = super::Options::new();
+    opts.normalization(super::TargetNormalization::SeparatorsOnly);
+
+    opts.create("C:/base/../target", &junction).unwrap();
+    assert_eq!(super::get_target(&junction).unwrap(), Path::new(r"C:\base\..\target"));
+    assert_eq!(
+        opts.verify(&junction, "C:/base/../target").unwrap(),
+        super::Verification::Match
+    );
+    // Fully normalized, the target is another path.
+    assert_ne!(
+        super::verify(&junction, "C:/base/../target").unwrap(),
+        super::Verification::Match
+    );
+
+    match opts.set_target(&junction, "relative/target") {
+        Err(ref e) if e.kind() == io::ErrorKind::InvalidInput => {}
+        other => panic!("relative targets cannot be kept as is: {:?}", other),
+    }
+}
+
+#[test]
+fn options_normalization_verbatim() {
+    let tmpdir = create_tempdir();
+    let junction = tmpdir.path().join("junction");
+    let mut opts = super::Options::new();
+    opts.normalization(super::TargetNormalization::Verbatim);
+
+    opts.overwrite(true);
+    for (target, expected) in [
+        (r"C:\dir.\..\x", r"C:\dir.\..\x"),
+        (r"\??\C:\dir", r"C:\dir"),
+        ("relative/dir", "relative/dir"),
+    ] {
+        opts.create(target, &junction).unwrap();
+        assert_eq!(super::get_target(&junction).unwrap(), Path::new(expected));
+    }
+}
+
+#[test]
+fn options_nt_paths_beyond_max_path() {
+    let tmpdir = create_tempdir();
+    let mut deep = tmpdir.path().to_path_buf();
+    while deep.as_os_str().len() <= 300 {
+        deep.push("a".repeat(50));
+    }
+    let target = deep.join("target");
+    let junction = deep.join("junction");
+    fs::create_dir_all(&target).unwrap();
+    let mut opts = super::Options::new();
+    opts.nt_paths(true);
+
+    opts.create(&target, &junction).unwrap();
+    assert!(opts.exists(&junction).unwrap());
+    assert_eq!(opts.get_target(&junction).unwrap(), target);
+    opts.delete(&junction).unwrap();
+}
+
+#[test]
+fn get_target_into_reuses_buffer() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    let device = tmpdir.path().join("device");
+    fs::create_dir_all(&target).unwrap();
+    super::create(&target, &junction).unwrap();
+    super::create(r"\\?\GLOBALROOT\Device\HarddiskVolume1\dir", &device).unwrap();
+
+    let mut buf = Vec::new();
+    for path in [&junction, &device, &junction] {
+        let wide = super::get_target_into(path, &mut buf).unwrap();
+        assert_eq!(
+            PathBuf::from(OsString::from_wide(wide)),
+            super::get_target(path).unwrap()
+        );
+    }
+    match super::get_target_into(tmpdir.path().join("missing"), &mut buf) {
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => {}
+        other => panic!("junction does not exist: {:?}", other),
+    }
+}
+
+#[test]
+fn read_targets_larger_than_stack_buffer() {
+    let tmpdir = create_tempdir();
+    let junction = tmpdir.path().join("junction");
+    let mut opts = super::Options::new();
+    opts.normalization(super::TargetNormalization::Verbatim).overwrite(true);
+
+    for len in [10, 1000, 4000] {
+        let target = PathBuf::from(format!(r"C:\{}", "a".repeat(len)));
+        opts.create(&target, &junction).unwrap();
+        assert!(super::exists(&junction).unwrap());
+        assert_eq!(super::get_target(&junction).unwrap(), target);
+    }
+}
+
+#[test]
+fn mount_point_data_matches_to_bytes() {
+    for target in [r"C:\dir", r"\\?\GLOBALROOT\Device\HarddiskVolume1\dir"] {
+        let bytes = super::mount_point_data(target).unwrap();
+        let data = super::parse_reparse_data(&bytes).unwrap();
+        assert_eq!(data.to_bytes().unwrap(), bytes);
+    }
+    let target = format!(r"C:\{}", "a".repeat(8 * 1024));
+    match super::mount_point_data(target) {
+        Err(ref e) if e.kind() == io::ErrorKind::InvalidInput => {}
+        other => panic!("target is too long: {:?}", other),
+    }
+}
+
+#[test]
+fn junction_file_reuses_handle() {
+    const FILE_SHARE_READ: u32 = 0x1;
+    const FILE_SHARE_WRITE: u32 = 0x2;
+
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+    super::create(&target, &junction).unwrap();
+
+    let mut opts = super::Options::new();
+    opts.share_mode(FILE_SHARE_READ | FILE_SHARE_WRITE);
+    let file = opts.open_file(&junction, false).unwrap();
+    assert_eq!(file.tag().unwrap(), 0xA000_0003);
+    assert_eq!(file.target().unwrap(), target);
+    assert_eq!(file.print_name().unwrap(), Path::new(""));
+    // The data is cached until refreshed.
+    opts.set_target(&junction, tmpdir.path()).unwrap();
+    assert_eq!(file.target().unwrap(), target);
+    file.refresh();
+    assert_eq!(file.target().unwrap(), tmpdir.path());
+    assert!(file.delete().is_err());
+
+    super::JunctionFile::open_writable(&junction).unwrap().delete().unwrap();
+    match super::exists(&junction) {
+        Err(ref e) if e.raw_os_error() == Some(ERROR_NOT_A_REPARSE_POINT) => {}
+        other => panic!("junction had been deleted: {:?}", other),
+    }
+}
+
+#[test]
+fn metadata_at_once() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+
+    let missing = super::metadata(&junction).unwrap();
+    assert!(!missing.exists() && !missing.is_junction());
+    let dir = super::metadata(&target).unwrap();
+    assert!(dir.exists() && !dir.is_junction());
+    assert_eq!(dir.tag(), None);
+    assert_eq!(dir.target_drive_type(), None);
+
+    super::create(&target, &junction).unwrap();
+    let metadata = super::metadata(&junction).unwrap();
+    assert!(metadata.exists() && metadata.is_junction());
+    assert_eq!(metadata.tag(), Some(0xA000_0003));
+    assert_eq!(metadata.target(), Some(target.as_path()));
+    assert_eq!(metadata.kind(), Some(super::JunctionKind::Directory));
+    // The temporary directory is on the same local drive as the tests.
+    assert!(matches!(
+        metadata.target_drive_type(),
+        Some(super::DriveType::Fixed | super::DriveType::Removable | super::DriveType::RamDisk)
+    ));
+}
+
+#[test]
+fn fs_module_behaves_like_symlinks() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let link = tmpdir.path().join("app").join("data");
+    fs::create_dir_all(link.parent().unwrap()).unwrap();
+    fs::create_dir_all(&target).unwrap();
+
+    // Relative targets are relative to the link, as with symbolic links.
+    super::fs::symlink_dir(Path::new("..").join("target"), &link).unwrap();
+    assert_eq!(super::fs::read_link(&link).unwrap(), target);
+    assert!(super::fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+    let err = super::fs::symlink_dir(&target, &link).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+
+    let err = super::fs::read_link(&target).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    let err = super::fs::read_link(tmpdir.path().join("missing")).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+}
+
+#[test]
+fn reparse_tag_from_directory_entry() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+    super::create(&target, &junction).unwrap();
+
+    assert_eq!(super::reparse_tag(&junction).unwrap(), Some(0xA000_0003));
+    assert_eq!(super::reparse_tag(&target).unwrap(), None);
+    assert_eq!(super::reparse_tag(r"C:\").unwrap(), None);
+    match super::reparse_tag(tmpdir.path().join("missing")) {
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => {}
+        other => panic!("path does not exist: {:?}", other),
+    }
+}
+
+#[test]
+fn walk_junctions() {
+    let tmpdir = create_tempdir();
+    let root = tmpdir.path().join("root");
+    let target = tmpdir.path().join("target");
+    fs::create_dir_all(root.join("dir")).unwrap();
+    fs::create_dir_all(target.join("inner")).unwrap();
+    super::create(&target, root.join("junction")).unwrap();
+    // A cycle back to the root.
+    super::create(&root, root.join("dir").join("loop")).unwrap();
+
+    let names = |follow: bool| -> Vec<PathBuf> {
+        super::walk(&root)
+            .follow_junctions(follow)
+            .map(|entry| entry.unwrap().path().strip_prefix(&root).unwrap().to_path_buf())
+            .collect()
+    };
+    assert_eq!(
+        names(false),
+        [
+            PathBuf::from("dir"),
+            Path::new("dir").join("loop"),
+            PathBuf::from("junction")
+        ]
+    );
+    assert_eq!(
+        names(true),
+        [
+            PathBuf::from("dir"),
+            Path::new("dir").join("loop"),
+            PathBuf::from("junction"),
+            Path::new("junction").join("inner"),
+        ]
+    );
+    let kinds: Vec<_> = super::walk(&root).map(|entry| entry.unwrap().kind().clone()).collect();
+    assert_eq!(kinds[0], super::EntryKind::Dir);
+    assert_eq!(kinds[2], super::EntryKind::Junction { target });
+}
+
+#[test]
+fn read_dir_no_follow_annotates_entries() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    fs::create_dir_all(target.join("inner")).unwrap();
+    File::create(tmpdir.path().join("file")).unwrap();
+    super::create(&target, tmpdir.path().join("junction")).unwrap();
+
+    let mut entries = super::read_dir_no_follow(tmpdir.path()).unwrap();
+    entries.sort_by(|a, b| a.file_name().cmp(b.file_name()));
+    let summary: Vec<_> = entries
+        .iter()
+        .map(|entry| (entry.file_name().to_str().unwrap(), entry.is_dir(), entry.is_junction()))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("file", false, false),
+            ("junction", false, true),
+            ("target", true, false)
+        ]
+    );
+    assert_eq!(entries[1].path(), tmpdir.path().join("junction"));
+    assert_eq!(entries[1].reparse_tag(), Some(0xA000_0003));
+    assert!(super::read_dir_no_follow(target.join("inner")).unwrap().is_empty());
+}
+
+#[test]
+fn junction_boundary_finds_first_junction() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(target.join("dir")).unwrap();
+    super::create(&target, &junction).unwrap();
+    super::create(&target, target.join("nested")).unwrap();
+
+    let index_of = |path: &Path| path.components().count() - 1;
+    let path = junction.join("nested").join("dir");
+    assert_eq!(super::junction_boundary(&path).unwrap(), Some(index_of(&junction)));
+    let path = target.join("nested").join("dir");
+    assert_eq!(
+        super::junction_boundary(&path).unwrap(),
+        Some(index_of(&target.join("nested")))
+    );
+    assert_eq!(super::junction_boundary(target.join("dir")).unwrap(), None);
+    assert_eq!(
+        super::junction_boundary(target.join("missing").join("dir")).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn split_at_junction_rebases_rest() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(target.join("dir")).unwrap();
+    super::create(&target, &junction).unwrap();
+
+    let (prefix, rest) = super::split_at_junction(junction.join("dir").join("file.txt"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(prefix, junction);
+    assert_eq!(rest, Path::new("dir").join("file.txt"));
+
+    let (prefix, rest) = super::split_at_junction(&junction).unwrap().unwrap();
+    assert_eq!(prefix, junction);
+    assert_eq!(rest, Path::new(""));
+
+    assert!(super::split_at_junction(target.join("dir")).unwrap().is_none());
+}
+
+#[test]
+fn convert_to_symlink_keeps_entry() {
+    const IO_REPARSE_TAG_SYMLINK: u32 = 0xA000_000C;
+
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+    fs::write(target.join("file.txt"), b"content").unwrap();
+    super::create(&target, &junction).unwrap();
+    let before = fs::symlink_metadata(&junction).unwrap();
+
+    // Symbolic links require a privilege, without which the junction is restored.
+    if let Err(e) = super::convert_to_symlink(&junction) {
+        assert_eq!(e.raw_os_error(), Some(1314), "{}", e); // ERROR_PRIVILEGE_NOT_HELD
+        assert_eq!(super::get_target(&junction).unwrap(), target);
+        return;
+    }
+    assert_eq!(super::reparse_tag(&junction).unwrap(), Some(IO_REPARSE_TAG_SYMLINK));
+    assert_eq!(fs::read_link(&junction).unwrap(), target);
+    assert_eq!(fs::read(junction.join("file.txt")).unwrap(), b"content");
+    let after = fs::symlink_metadata(&junction).unwrap();
+    assert_eq!(after.file_attributes(), before.file_attributes());
+    assert_eq!(after.created().unwrap(), before.created().unwrap());
+    assert!(super::exists(&junction).is_err());
+}
+
+#[test]
+fn convert_to_junction_resolves_relative_target() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let link = tmpdir.path().join("link");
+    fs::create_dir_all(&target).unwrap();
+    fs::write(target.join("file.txt"), b"content").unwrap();
+
+    // Symbolic links require a privilege or developer mode.
+    if symlink_dir("target", &link).is_err() {
+        return;
+    }
+    super::convert_to_junction(&link).unwrap();
+    assert_eq!(super::get_target(&link).unwrap(), target);
+    assert_eq!(fs::read(link.join("file.txt")).unwrap(), b"content");
+
+    let err = super::convert_to_junction(&link).unwrap_err();
+    assert!(err.get_ref().unwrap().is::<super::TagMismatch>());
+
+    let file_link = tmpdir.path().join("file_link");
+    symlink_file(target.join("file.txt"), &file_link).unwrap();
+    let err = super::convert_to_junction(&file_link).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert!(fs::symlink_metadata(&file_link).unwrap().file_type().is_symlink());
+}
+
+#[test]
+fn copy_junction_keeps_names() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    let copy = tmpdir.path().join("copy");
+    fs::create_dir_all(&target).unwrap();
+    fs::create_dir(&junction).unwrap();
+    let substitute_name = format!(r"\??\{}", target.display()).encode_utf16().collect();
+    let print_name = "shown".encode_utf16().collect();
+    let data = super::ReparseData::MountPoint(super::MountPointData::new(substitute_name, print_name));
+    // Skip the header, which `write_reparse_raw` writes.
+    super::write_reparse_raw(&junction, data.tag(), None, &data.to_bytes().unwrap()[8..]).unwrap();
+
+    super::copy_junction(&junction, &copy).unwrap();
+    assert_eq!(
+        super::read_reparse_raw(&copy).unwrap(),
+        super::read_reparse_raw(&junction).unwrap()
+    );
+    let err = super::copy_junction(&junction, &copy).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+    let err = super::copy_junction(&target, tmpdir.path().join("other")).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(ERROR_NOT_A_REPARSE_POINT));
+    assert!(!tmpdir.path().join("other").exists());
+}
+
+#[test]
+fn move_junction_within_volume() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    let moved = tmpdir.path().join("moved");
+    fs::create_dir_all(&target).unwrap();
+    fs::write(target.join("file.txt"), b"content").unwrap();
+    super::create(&target, &junction).unwrap();
+
+    super::move_junction(&junction, &moved).unwrap();
+    assert_eq!(super::get_target(&moved).unwrap(), target);
+    assert!(fs::symlink_metadata(&junction).is_err());
+    assert_eq!(fs::read(target.join("file.txt")).unwrap(), b"content");
+
+    let err = super::move_junction(&target, &junction).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(ERROR_NOT_A_REPARSE_POINT));
+    assert!(target.join("file.txt").exists());
+}
+
+#[test]
+fn move_junction_across_volumes() {
+    const FILE_SHARE_READ: u32 = 0x1;
+    const FILE_SHARE_WRITE: u32 = 0x2;
+    const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+    const FILE_FLAG_OPEN_REPARSE_POINT: u32 = 0x0020_0000;
+
+    let tmpdir = create_tempdir();
+    // The system temporary directory is on another volume than the workspace on CI.
+    let other = tempfile::Builder::new().prefix("junction-test-").tempdir().unwrap();
+    let prefix = |path: &Path| {
+        fs::canonicalize(path)
+            .unwrap()
+            .components()
+            .next()
+            .map(|c| c.as_os_str().to_owned())
+    };
+    if prefix(tmpdir.path()) == prefix(other.path()) {
+        return;
+    }
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    let moved = other.path().join("moved");
+    fs::create_dir_all(&target).unwrap();
+    fs::write(target.join("file.txt"), b"content").unwrap();
+    super::create(&target, &junction).unwrap();
+
+    super::move_junction(&junction, &moved).unwrap();
+    assert_eq!(super::get_target(&moved).unwrap(), target);
+    assert!(fs::symlink_metadata(&junction).is_err());
+    assert_eq!(fs::read(target.join("file.txt")).unwrap(), b"content");
+
+    // Another handle without delete sharing keeps `src` from being removed,
+    // after which the copy is removed and `src` is a junction point again.
+    super::create(&target, &junction).unwrap();
+    let mut options = super::Options::new();
+    options.share_mode(FILE_SHARE_READ | FILE_SHARE_WRITE);
+    let file = fs::OpenOptions::new()
+        .read(true)
+        .share_mode(FILE_SHARE_READ | FILE_SHARE_WRITE)
+        .custom_flags(FILE_FLAG_OPEN_REPARSE_POINT | FILE_FLAG_BACKUP_SEMANTICS)
+        .open(&junction)
+        .unwrap();
+    let again = other.path().join("again");
+    options.move_junction(&junction, &again).unwrap_err();
+    drop(file);
+    assert_eq!(super::get_target(&junction).unwrap(), target);
+    assert!(fs::symlink_metadata(&again).is_err());
+}
+
+#[test]
+fn options_preserve_timestamps() {
+    let tmpdir = create_tempdir();
+    let v1 = tmpdir.path().join("v1");
+    let v2 = tmpdir.path().join("v2");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&v1).unwrap();
+    fs::create_dir_all(&v2).unwrap();
+    super::create(&v1, &junction).unwrap();
+    let before = fs::symlink_metadata(&junction).unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    let mut options = super::Options::new();
+    options.preserve_timestamps(true);
+    options.set_target(&junction, &v2).unwrap();
+    assert_eq!(super::get_target(&junction).unwrap(), v2);
+    let after = fs::symlink_metadata(&junction).unwrap();
+    assert_eq!(after.last_write_time(), before.last_write_time());
+    assert_eq!(after.creation_time(), before.creation_time());
+
+    options.overwrite(true).create(&v1, &junction).unwrap();
+    assert_eq!(super::get_target(&junction).unwrap(), v1);
+    let after = fs::symlink_metadata(&junction).unwrap();
+    assert_eq!(after.last_write_time(), before.last_write_time());
+}
+
+#[test]
+fn options_preserve_security() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    let copy = tmpdir.path().join("copy");
+    fs::create_dir_all(&target).unwrap();
+    super::create(&target, &junction).unwrap();
+    // Protect the DACL from inheritance, so that it differs from the one of a new directory.
+    let status = std::process::Command::new("icacls")
+        .arg(&junction)
+        .args(["/inheritance:d", "/L"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    // The ACEs as listed by `icacls`, after the path that starts its output.
+    let acl = |path: &Path| {
+        let output = std::process::Command::new("icacls")
+            .arg(path)
+            .arg("/L")
+            .output()
+            .unwrap();
+        let output = String::from_utf8_lossy(&output.stdout).into_owned();
+        output[path.as_os_str().len()..].to_owned()
+    };
+
+    let mut options = super::Options::new();
+    options.preserve_security(true).copy_junction(&junction, &copy).unwrap();
+    assert_eq!(super::get_target(&copy).unwrap(), target);
+    assert_eq!(acl(&copy), acl(&junction));
+}
+
+#[test]
+fn options_security_descriptor() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+
+    let mut options = super::Options::new();
+    options.security_descriptor("D:P(A;;FA;;;SY)(A;;FA;;;BA)(A;;FA;;;OW)");
+    options.create(&target, &junction).unwrap();
+    assert_eq!(super::get_target(&junction).unwrap(), target);
+    let output = std::process::Command::new("icacls")
+        .arg(&junction)
+        .arg("/L")
+        .output()
+        .unwrap();
+    let acl = String::from_utf8_lossy(&output.stdout);
+    assert!(acl.contains("NT AUTHORITY\\SYSTEM:(F)"), "{}", acl);
+    assert!(!acl.contains("(I)"), "{}", acl);
+
+    let err = super::Options::new()
+        .security_descriptor("not SDDL")
+        .create(&target, tmpdir.path().join("invalid"))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert!(!tmpdir.path().join("invalid").exists());
+}
+
+#[test]
+fn options_not_content_indexed() {
+    const FILE_ATTRIBUTE_NOT_CONTENT_INDEXED: u32 = 0x2000;
+
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+
+    super::Options::new()
+        .not_content_indexed(true)
+        .create(&target, &junction)
+        .unwrap();
+    assert_eq!(super::get_target(&junction).unwrap(), target);
+    let attributes = fs::symlink_metadata(&junction).unwrap().file_attributes();
+    assert_ne!(attributes & FILE_ATTRIBUTE_NOT_CONTENT_INDEXED, 0);
+    assert_eq!(
+        fs::metadata(&target).unwrap().file_attributes() & FILE_ATTRIBUTE_NOT_CONTENT_INDEXED,
+        0
+    );
+
+    // Setting the attribute needs no `WRITE_OWNER`, which this DACL does not
+    // grant, and which the owner does not have implicitly.
+    let restricted = tmpdir.path().join("restricted");
+    super::Options::new()
+        .privileges(super::PrivilegePolicy::Never)
+        .security_descriptor("D:P(A;;0x1201bf;;;WD)")
+        .not_content_indexed(true)
+        .create(&target, &restricted)
+        .unwrap();
+    let attributes = fs::symlink_metadata(&restricted).unwrap().file_attributes();
+    assert_ne!(attributes & FILE_ATTRIBUTE_NOT_CONTENT_INDEXED, 0);
+}
+
+#[test]
+fn options_os_compatibility() {
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+    const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
+
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+    fs::write(target.join("file.txt"), b"content").unwrap();
+
+    super::Options::new()
+        .os_compatibility(true)
+        .create(&target, &junction)
+        .unwrap();
+    let attributes = fs::symlink_metadata(&junction).unwrap().file_attributes();
+    assert_eq!(
+        attributes & (FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM),
+        FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM
+    );
+    // The junction redirects, but cannot be listed.
+    assert_eq!(fs::read(junction.join("file.txt")).unwrap(), b"content");
+    assert_eq!(
+        fs::read_dir(&junction).unwrap_err().kind(),
+        io::ErrorKind::PermissionDenied
+    );
+}
+
+#[test]
+fn explain_reports_junction() {
+    const IO_REPARSE_TAG_MOUNT_POINT: u32 = 0xA000_0003;
+    const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
+
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+    super::create(&target, &junction).unwrap();
+
+    let diagnostics = super::explain(&junction);
+    assert_eq!(diagnostics.path(), junction);
+    assert_eq!(diagnostics.full_path().unwrap(), junction);
+    assert_eq!(diagnostics.normalized().unwrap(), junction);
+    assert!(diagnostics.nt_path().unwrap().to_string_lossy().starts_with(r"\??\"));
+    assert!(diagnostics.supports_reparse_points().unwrap());
+    assert_ne!(diagnostics.attributes().unwrap() & FILE_ATTRIBUTE_REPARSE_POINT, 0);
+    assert_eq!(diagnostics.reparse_tag().unwrap(), Some(IO_REPARSE_TAG_MOUNT_POINT));
+    assert_eq!(diagnostics.target().unwrap().unwrap(), target);
+    assert!(!diagnostics.privileges().unwrap().is_empty());
+    let report = diagnostics.to_string();
+    assert!(report.contains("reparse tag: 0xa0000003"), "{}", report);
+
+    let missing = super::explain(tmpdir.path().join("missing"));
+    assert!(missing.attributes().is_err());
+    assert!(missing.target().is_none());
+    assert!(missing.full_path().is_ok());
+}
+
+#[test]
+fn debug_dump_reads_junction() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+    super::create(&target, &junction).unwrap();
+
+    let dump = super::debug_dump(&junction).unwrap();
+    assert_eq!(&dump.bytes()[..4], &0xA000_0003u32.to_le_bytes());
+    let report = dump.to_string();
+    assert!(report.starts_with("tag: 0xa0000003 (mount point)\n"), "{}", report);
+    assert!(report.contains("substitute name: offset 0"), "{}", report);
+
+    let dir = tmpdir.path().join("dir");
+    fs::create_dir(&dir).unwrap();
+    assert_eq!(
+        super::debug_dump(&dir).unwrap_err().raw_os_error(),
+        Some(ERROR_NOT_A_REPARSE_POINT)
+    );
+}
+
+#[test]
+fn junction_file_from_opened_handle() {
+    const FILE_FLAG_OPEN_REPARSE_POINT: u32 = 0x0020_0000;
+    const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+    super::create(&target, &junction).unwrap();
+
+    let file = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(FILE_FLAG_OPEN_REPARSE_POINT | FILE_FLAG_BACKUP_SEMANTICS)
+        .open(&junction)
+        .unwrap();
+    let file = super::JunctionFile::from(file);
+    assert_eq!(file.target().unwrap(), target);
+    file.delete().unwrap();
+    assert!(junction.exists());
+    assert!(!super::exists(&junction).unwrap_or(false));
+
+    super::create(&target, &junction).unwrap();
+    let file = fs::OpenOptions::new()
+        .read(true)
+        .custom_flags(FILE_FLAG_OPEN_REPARSE_POINT | FILE_FLAG_BACKUP_SEMANTICS)
+        .open(&junction)
+        .unwrap();
+    let file = unsafe { <super::JunctionFile as FromRawHandle>::from_raw_handle(file.into_raw_handle()) };
+    assert_eq!(file.tag().unwrap(), 0xA000_0003);
+}
+
+#[test]
+fn options_lenient_exists() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    let file = tmpdir.path().join("file");
+    fs::create_dir_all(&target).unwrap();
+    fs::write(&file, b"content").unwrap();
+    super::create(&target, &junction).unwrap();
+
+    match super::exists(&target) {
+        Err(ref e) if e.raw_os_error() == Some(ERROR_NOT_A_REPARSE_POINT) => {}
+        other => panic!("strict by default: {:?}", other),
+    }
+    let mut opts = super::Options::new();
+    opts.lenient_exists(true);
+    assert!(opts.exists(&junction).unwrap());
+    assert!(!opts.exists(&target).unwrap());
+    assert!(!opts.exists(&file).unwrap());
+    assert!(!opts.exists(tmpdir.path().join("missing")).unwrap());
+}
+
+#[test]
+fn kind_classifies_entries() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let file = tmpdir.path().join("file");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+    fs::write(&file, b"content").unwrap();
+    super::create(&target, &junction).unwrap();
+
+    assert_eq!(super::kind(&target).unwrap(), None);
+    assert_eq!(super::kind(&file).unwrap(), None);
+    assert_eq!(
+        super::kind(&junction).unwrap(),
+        Some(super::ReparseKind::Junction(super::JunctionKind::Directory))
+    );
+    assert!(super::kind(tmpdir.path().join("missing")).is_err());
+
+    let dir_link = tmpdir.path().join("dir_link");
+    let file_link = tmpdir.path().join("file_link");
+    match symlink_dir(&target, &dir_link) {
+        // Creating symbolic links needs developer mode or the privilege.
+        Err(ref e) if e.raw_os_error() == Some(1314) => return,
+        result => result.unwrap(),
+    }
+    symlink_file(&file, &file_link).unwrap();
+    assert_eq!(super::kind(&dir_link).unwrap(), Some(super::ReparseKind::DirSymlink));
+    assert_eq!(super::kind(&file_link).unwrap(), Some(super::ReparseKind::FileSymlink));
+}
+
+#[test]
+fn create_resolved_reports_written_names() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(target.join("sub")).unwrap();
+
+    let spelled = format!("{}/sub/..", target.display());
+    let resolved = super::create_resolved(&spelled, &junction).unwrap();
+    assert_eq!(resolved.junction(), junction);
+    assert_eq!(resolved.target(), target);
+    assert_eq!(resolved.target(), super::get_target(&junction).unwrap());
+    let mut expected = OsString::from(r"\??\");
+    expected.push(&target);
+    assert_eq!(resolved.substitute_name(), expected);
+}
+
+#[test]
+fn delete_at_follows_renamed_directory() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let parent = tmpdir.path().join("parent");
+    let renamed = tmpdir.path().join("renamed");
+    fs::create_dir_all(&target).unwrap();
+    fs::create_dir_all(&parent).unwrap();
+    super::create(&target, parent.join("junction")).unwrap();
+
+    let dir = File::open(&parent).unwrap();
+    // The handle still refers to the directory after it is renamed.
+    fs::rename(&parent, &renamed).unwrap();
+    fs::create_dir_all(&parent).unwrap();
+    super::create(&target, parent.join("junction")).unwrap();
+    super::delete_at(&dir, "junction").unwrap();
+    assert!(!super::exists(renamed.join("junction")).unwrap_or(false));
+    assert!(super::exists(parent.join("junction")).unwrap());
+
+    for name in [r"..\parent\junction", "", r"renamed\junction"] {
+        match super::delete_at(&dir, name) {
+            Err(ref e) if e.kind() == io::ErrorKind::InvalidInput => {}
+            other => panic!("{:?} is not a file name: {:?}", name, other),
+        }
+    }
+}
+
+#[test]
+fn junction_ops_with_options() {
+    use super::JunctionOps;
+
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+
+    let ops: &dyn JunctionOps = &super::Options::new();
+    assert!(!ops.exists(&junction).unwrap());
+    ops.create(&target, &junction).unwrap();
+    assert!(ops.exists(&junction).unwrap());
+    assert_eq!(ops.get_target(&junction).unwrap(), target);
+    ops.delete(&junction).unwrap();
+    assert!(!super::exists(&junction).unwrap_or(false));
+}
+
+#[test]
+fn batch_applies_all() {
+    let tmpdir = create_tempdir();
+    let v1 = tmpdir.path().join("v1");
+    let v2 = tmpdir.path().join("v2");
+    let current = tmpdir.path().join("current");
+    let legacy = tmpdir.path().join("legacy");
+    let data = tmpdir.path().join("data");
+    fs::create_dir_all(&v1).unwrap();
+    fs::create_dir_all(&v2).unwrap();
+    super::create(&v1, &current).unwrap();
+    super::create(&v1, &legacy).unwrap();
+
+    let mut batch = super::Batch::new();
+    batch
+        .set_target(&current, &v2)
+        .create(&v2, &data)
+        .delete(&legacy)
+        .delete(tmpdir.path().join("missing"));
+    assert_eq!(batch.len(), 4);
+    super::Options::new().apply_batch(&batch).unwrap();
+    assert_eq!(super::get_target(&current).unwrap(), v2);
+    assert_eq!(super::get_target(&data).unwrap(), v2);
+    assert!(!super::exists(&legacy).unwrap_or(false));
+}
+
+#[test]
+fn batch_rolls_back_on_failure() {
+    let tmpdir = create_tempdir();
+    let v1 = tmpdir.path().join("v1");
+    let v2 = tmpdir.path().join("v2");
+    let current = tmpdir.path().join("current");
+    let legacy = tmpdir.path().join("legacy");
+    let data = tmpdir.path().join("data");
+    let taken = tmpdir.path().join("taken");
+    fs::create_dir_all(&v1).unwrap();
+    fs::create_dir_all(&v2).unwrap();
+    fs::create_dir_all(taken.join("content")).unwrap();
+    super::create(&v1, &current).unwrap();
+    super::create(&v1, &legacy).unwrap();
+
+    let mut batch = super::Batch::new();
+    batch
+        .set_target(&current, &v2)
+        .create(&v2, &data)
+        .delete(&legacy)
+        .create(&v2, &taken);
+    let err = super::Options::new().apply_batch(&batch).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+    assert!(err.get_ref().is_none());
+    assert_eq!(super::get_target(&current).unwrap(), v1);
+    assert!(!data.exists());
+    assert_eq!(super::get_target(&legacy).unwrap(), v1);
+    assert!(taken.join("content").exists());
+}
+
+#[test]
+fn audit_hook_reports_changes() {
+    use std::sync::{Arc, Mutex};
+
+    use super::{OperationKind, OperationRecord};
+
+    let tmpdir = create_tempdir();
+    let v1 = tmpdir.path().join("v1");
+    let v2 = tmpdir.path().join("v2");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&v1).unwrap();
+    fs::create_dir_all(&v2).unwrap();
+
+    let records = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&records);
+    let mut opts = super::Options::new();
+    opts.audit_hook(move |record: &OperationRecord<'_>| {
+        assert_eq!(record.process_id(), std::process::id());
+        sink.lock().unwrap().push((
+            record.kind(),
+            record.junction().to_path_buf(),
+            record.source().map(Path::to_path_buf),
+            record.target().map(Path::to_path_buf),
+            record.result().is_ok(),
+        ));
+    });
+    opts.create(&v1, &junction).unwrap();
+    assert!(!opts.create_idempotent(&v1, &junction).unwrap());
+    opts.set_target(&junction, &v2).unwrap();
+    opts.create(&v1, &junction).unwrap_err();
+    opts.delete(&junction).unwrap();
+    // The target is reported resolved against the base.
+    opts.create_with_base(tmpdir.path(), r"sub\..\v1", &junction).unwrap();
+    opts.delete(&junction).unwrap();
+
+    let copy = tmpdir.path().join("copy");
+    let moved = tmpdir.path().join("moved");
+    super::create(&v1, &junction).unwrap();
+    opts.copy_junction(&junction, &copy).unwrap();
+    opts.move_junction(&junction, &moved).unwrap();
+    // Symbolic links require a privilege, without which the conversion fails.
+    let converted = opts.convert_to_symlink(&moved).is_ok();
+    if converted {
+        opts.convert_to_junction(&moved).unwrap();
+    }
+    opts.delete_at(&File::open(tmpdir.path()).unwrap(), "copy").unwrap();
+
+    let mut records = records.lock().unwrap();
+    // `delete_at` reports the path of the directory it was given.
+    let (kind, deleted, source, target, ok) = records.pop().unwrap();
+    assert_eq!((kind, source, target, ok), (OperationKind::Delete, None, None, true));
+    assert_eq!(deleted.file_name(), copy.file_name());
+    assert_eq!(deleted.parent().unwrap().file_name(), tmpdir.path().file_name());
+
+    let mut expected = vec![
+        (OperationKind::Create, junction.clone(), None, Some(v1.clone()), true),
+        (OperationKind::Retarget, junction.clone(), None, Some(v2), true),
+        (OperationKind::Create, junction.clone(), None, Some(v1.clone()), false),
+        (OperationKind::Delete, junction.clone(), None, None, true),
+        (OperationKind::Create, junction.clone(), None, Some(v1.clone()), true),
+        (OperationKind::Delete, junction.clone(), None, None, true),
+        (
+            OperationKind::Create,
+            copy,
+            Some(junction.clone()),
+            Some(v1.clone()),
+            true,
+        ),
+        (
+            OperationKind::Move,
+            moved.clone(),
+            Some(junction),
+            Some(v1.clone()),
+            true,
+        ),
+        (
+            OperationKind::ConvertToSymlink,
+            moved.clone(),
+            None,
+            converted.then(|| v1.clone()),
+            converted,
+        ),
+    ];
+    if converted {
+        expected.push((OperationKind::ConvertToJunction, moved, None, Some(v1), true));
+    }
+    assert_eq!(*records, expected);
+}
+
+#[test]
+fn create_checked_validates_target() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let file = tmpdir.path().join("file");
+    let junction = tmpdir.path().join("junction");
+    fs::write(&file, b"").unwrap();
+
+    let err = super::create_checked(&target, &junction).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    let err = super::create_checked(&file, &junction).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert!(!junction.exists());
+
+    // A dangling junction point is a directory, and is not followed.
+    let dangling = tmpdir.path().join("dangling");
+    super::create(&target, &dangling).unwrap();
+    super::create_checked(&dangling, &junction).unwrap();
+    assert_eq!(super::get_target(&junction).unwrap(), dangling);
+}
+
+#[test]
+fn remote_filesystems_policy_passes_local_paths() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::Policy;
+
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+
+    let warnings = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&warnings);
+    let mut opts = super::Options::new();
+    opts.remote_filesystems(Policy::Warn).on_policy_warning(move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+    opts.create(&target, &junction).unwrap();
+    opts.set_target(&junction, &target).unwrap();
+    assert_eq!(warnings.load(Ordering::SeqCst), 0);
+
+    opts.remote_filesystems(Policy::Deny);
+    opts.set_target(&junction, &target).unwrap();
+}
+
+#[test]
+fn cross_volume_policy_passes_same_volume() {
+    use super::Policy;
+
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+
+    let mut opts = super::Options::new();
+    opts.cross_volume(Policy::Deny)
+        .on_policy_warning(|violation| panic!("{}", violation));
+    opts.create(&target, &junction).unwrap();
+    opts.set_target(&junction, &target).unwrap();
+    // Missing targets cannot be compared, and pass.
+    opts.set_target(&junction, tmpdir.path().join("missing")).unwrap();
+}
+
+#[test]
+fn mklink_compatible_writes_print_name() {
+    use std::os::windows::ffi::OsStrExt;
+
+    use super::{MountPointData, ReparseData};
+
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+
+    let mut opts = super::Options::new();
+    opts.mklink_compatible(true).create(&target, &junction).unwrap();
+    let print_name: Vec<u16> = target.as_os_str().encode_wide().collect();
+    let substitute_name = r"\??\".encode_utf16().chain(print_name.iter().copied()).collect();
+    let expected = ReparseData::MountPoint(MountPointData::new(substitute_name, print_name));
+    let dump = super::debug_dump(&junction).unwrap();
+    assert_eq!(dump.bytes(), expected.to_bytes().unwrap());
+}
+
+#[test]
+fn manifest_snapshot_and_restore() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let root = tmpdir.path().join("root");
+    fs::create_dir_all(&target).unwrap();
+    fs::create_dir_all(root.join("nested")).unwrap();
+    super::create(&target, root.join("nested").join("junction")).unwrap();
+
+    let manifest = super::manifest::Manifest::snapshot(&root).unwrap();
+    assert_eq!(manifest.entries().len(), 1);
+    let entry = &manifest.entries()[0];
+    assert_eq!(entry.path(), Path::new(r"nested\junction"));
+    assert_eq!(entry.target(), target);
+    assert_eq!(entry.kind(), super::JunctionKind::Directory);
+
+    let restored = tmpdir.path().join("restored");
+    manifest.restore(&restored).unwrap();
+    assert_eq!(super::get_target(restored.join(entry.path())).unwrap(), target);
+}
+
+#[test]
+fn manifest_plan() {
+    use super::manifest::{Manifest, ManifestEntry, Operation};
+
+    let tmpdir = create_tempdir();
+    let old = tmpdir.path().join("old");
+    let new = tmpdir.path().join("new");
+    let root = tmpdir.path().join("root");
+    fs::create_dir_all(&old).unwrap();
+    fs::create_dir_all(&new).unwrap();
+    fs::create_dir_all(&root).unwrap();
+    super::create(&old, root.join("moved")).unwrap();
+    super::create(&old, root.join("stale")).unwrap();
+
+    let mut manifest = Manifest::new();
+    manifest.push(ManifestEntry::new("moved", &new, super::JunctionKind::Directory));
+    manifest.push(ManifestEntry::new("added", &new, super::JunctionKind::Directory));
+    let operations = manifest.plan(&root).unwrap();
+    assert_eq!(
+        operations,
+        [
+            Operation::Retarget {
+                entry: manifest.entries()[0].clone(),
+                from: old.clone(),
+            },
+            Operation::Create(manifest.entries()[1].clone()),
+            Operation::Delete(ManifestEntry::new("stale", &old, super::JunctionKind::Directory)),
+        ]
+    );
+    // Nothing was changed.
+    assert_eq!(super::get_target(root.join("moved")).unwrap(), old);
+    assert!(!root.join("added").exists());
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn manifest_snapshot_parallel() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let root = tmpdir.path().join("root");
+    fs::create_dir_all(&target).unwrap();
+    for dir in ["a", r"a\b", "c"] {
+        fs::create_dir_all(root.join(dir)).unwrap();
+        super::create(&target, root.join(dir).join("junction")).unwrap();
+    }
+
+    let manifest = super::manifest::Manifest::snapshot_parallel(&root, 4).unwrap();
+    assert_eq!(manifest.entries().len(), 3);
+    assert_eq!(manifest, super::manifest::Manifest::snapshot(&root).unwrap());
+}
+
+#[test]
+fn create_to_volume_guid() {
+    let tmpdir = create_tempdir();
+    let junction = tmpdir.path().join("junction");
+
+    match super::create_to_volume("not-a-guid", &junction) {
+        Err(ref e) if e.kind() == io::ErrorKind::InvalidInput => {}
+        other => panic!("volume is not a volume GUID: {:?}", other),
+    }
+    assert!(
+        !junction.exists(),
+        "junction should not be created for an invalid volume"
+    );
+
+    let guid = "{00000000-0000-0000-0000-000000000000}";
+    super::create_to_volume(guid, &junction).unwrap();
+    assert_eq!(
+        super::get_kind(&junction).unwrap(),
+        super::JunctionKind::VolumeMountPoint
+    );
+    assert_eq!(
+        super::get_target(&junction).unwrap().to_str(),
+        Some(r"\\?\Volume{00000000-0000-0000-0000-000000000000}\"),
+    );
+}
+
+#[test]
+fn get_app_exec_link_not_an_alias() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+    super::create(&target, &junction).unwrap();
+
+    match super::get_app_exec_link(&junction) {
+        Err(ref e) if e.kind() == io::ErrorKind::Other => {}
+        other => panic!("junction is not an app execution alias: {:?}", other),
+    }
+
+    let file = tmpdir.path().join("foo-file");
+    File::create(&file).unwrap().write_all(b"foo").unwrap();
+    match super::get_app_exec_link(&file) {
+        Err(ref e) if e.raw_os_error() == Some(ERROR_NOT_A_REPARSE_POINT) => {}
+        other => panic!("file is not a reparse point: {:?}", other),
+    }
+}
+
+#[test]
+fn get_lx_symlink_not_an_lx_symlink() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+    super::create(&target, &junction).unwrap();
+
+    match super::get_lx_symlink(&junction) {
+        Err(ref e) if e.kind() == io::ErrorKind::Other => {}
+        other => panic!("junction is not an LX symlink: {:?}", other),
+    }
+}
+
+#[test]
+fn reparse_raw_round_trip() {
+    let tmpdir = create_tempdir();
+    let file = tmpdir.path().join("foo-file");
+    File::create(&file).unwrap().write_all(b"foo").unwrap();
+
+    // A non-Microsoft tag must come with a GUID and vice versa.
+    const TAG: u32 = 0x0000_1234;
+    let guid = super::Guid::from_u128(0x01234567_89ab_cdef_0123_456789abcdef);
+    match super::write_reparse_raw(&file, TAG, None, b"payload") {
+        Err(ref e) if e.kind() == io::ErrorKind::InvalidInput => {}
+        other => panic!("non-Microsoft reparse tag requires a GUID: {:?}", other),
+    }
+
+    super::write_reparse_raw(&file, TAG, Some(guid), b"payload").unwrap();
+    let (tag, read_guid, data) = super::read_reparse_raw(&file).unwrap();
+    assert_eq!(tag, TAG);
+    assert_eq!(read_guid, Some(guid));
+    assert_eq!(data, b"payload");
+    assert_eq!(guid.to_string(), "{01234567-89AB-CDEF-0123-456789ABCDEF}");
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn tokio_create_and_delete() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+
+    let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    rt.block_on(async {
+        super::tokio::create(&target, &junction).await.unwrap();
+        assert!(super::tokio::exists(&junction).await.unwrap());
+        assert_eq!(super::tokio::get_target(&junction).await.unwrap(), target);
+        super::tokio::delete(&junction).await.unwrap();
+    });
+}
+
+#[cfg(feature = "camino")]
+#[test]
+fn camino_get_target() {
+    let tmpdir = create_tempdir();
+    let target = camino::Utf8PathBuf::from_path_buf(tmpdir.path().join("target")).unwrap();
+    let junction = target.with_file_name("junction");
+    fs::create_dir_all(&target).unwrap();
+
+    super::camino::create(&target, &junction).unwrap();
+    assert_eq!(super::camino::get_target(&junction).unwrap(), target);
+    assert_eq!(
+        super::camino::get_kind(&junction).unwrap(),
+        super::JunctionKind::Directory
+    );
+}
+
+#[cfg(feature = "backup")]
+#[test]
+fn backup_read_and_restore() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    let restored = tmpdir.path().join("restored");
+    fs::create_dir_all(&target).unwrap();
+    super::create(&target, &junction).unwrap();
+
+    let data = super::backup::read(&junction).unwrap();
+    let (tag, _, raw) = super::read_reparse_raw(&junction).unwrap();
+    assert_eq!(super::parse_reparse_data(&data).unwrap().tag(), tag);
+    assert_eq!(&data[8..], &raw[..]);
+    super::backup::restore(&restored, &data).unwrap();
+    assert_eq!(super::get_target(&restored).unwrap(), target);
+
+    match super::backup::read(&target) {
+        Err(ref e) if e.raw_os_error() == Some(ERROR_NOT_A_REPARSE_POINT) => {}
+        other => panic!("target is not a junction: {:?}", other),
+    }
+    let other = tmpdir.path().join("other");
+    assert!(super::backup::restore(&other, &[0; 8]).is_err());
+    assert!(!other.exists());
+}
+
+#[cfg(feature = "raw")]
+#[test]
+fn raw_round_trip() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+    super::create(&target, &junction).unwrap();
+
+    let file = super::raw::open_reparse_point(&junction, true).unwrap();
+    let data = super::raw::get_reparse_data_point(&file).unwrap();
+    super::raw::delete_reparse_point(&file).unwrap();
+    match super::raw::get_reparse_data_point(&file) {
+        Err(ref e) if e.raw_os_error() == Some(ERROR_NOT_A_REPARSE_POINT) => {}
+        other => panic!("reparse point was deleted: {:?}", other),
+    }
+    assert!(super::raw::set_reparse_point(&file, &data[..data.len() - 2]).is_err());
+    super::raw::set_reparse_point(&file, &data).unwrap();
+    drop(file);
+    assert_eq!(super::get_target(&junction).unwrap(), target);
+}
+
+#[cfg(feature = "etw")]
+#[test]
+fn etw_reports_without_session() {
+    assert_eq!(
+        super::etw::PROVIDER_ID.to_string(),
+        "{DFD6C6BA-4341-49DF-B580-FD7A83C07868}"
+    );
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+    super::create(&target, &junction).unwrap();
+    super::set_target(&junction, &target).unwrap();
+    super::delete(&junction).unwrap();
+    super::delete(&junction).unwrap_err();
+
+    // The other changes are reported too, also through a handle.
+    let junction = tmpdir.path().join("other");
+    let moved = tmpdir.path().join("moved");
+    super::create(&target, &junction).unwrap();
+    super::move_junction(&junction, &moved).unwrap();
+    if super::convert_to_symlink(&moved).is_ok() {
+        super::convert_to_junction(&moved).unwrap();
+    }
+    super::Options::new().open_file(&moved, true).unwrap().delete().unwrap();
+    let dir = File::open(tmpdir.path()).unwrap();
+    super::delete_at(&dir, "moved").unwrap_err();
+}
+
+#[cfg(feature = "usn")]
+#[test]
+fn usn_watcher_reports_junction_changes() {
+    use super::usn::{UsnEventKind, UsnWatcher};
+
+    let tmpdir = create_tempdir();
+    let mut watcher = match UsnWatcher::open(tmpdir.path()) {
+        Ok(watcher) => watcher,
+        // Reading the journal needs administrator rights, and an active journal.
+        Err(e) if e.raw_os_error() == Some(5) || e.raw_os_error() == Some(1179) => return,
+        Err(e) => panic!("{}", e),
+    };
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("usn-junction");
+    fs::create_dir_all(&target).unwrap();
+    super::create(&target, &junction).unwrap();
+    super::set_target(&junction, tmpdir.path()).unwrap();
+    super::delete(&junction).unwrap();
+
+    let mut kinds = Vec::new();
+    loop {
+        let start = watcher.next_usn();
+        let events = watcher.poll().unwrap();
+        if watcher.next_usn() == start {
+            break;
+        }
+        kinds.extend(
+            events
+                .iter()
+                .filter(|event| event.name() == "usn-junction")
+                .map(|event| event.kind()),
+        );
+    }
+    assert_eq!(
+        kinds,
+        [UsnEventKind::Created, UsnEventKind::Retargeted, UsnEventKind::Removed]
+    );
+}
+
+#[cfg(feature = "watch")]
+#[test]
+fn dir_watcher_reports_junction_changes() {
+    use super::watch::{DirEventKind, DirWatcher};
+
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let links = tmpdir.path().join("links");
+    let junction = links.join("junction");
+    fs::create_dir_all(&target).unwrap();
+    fs::create_dir_all(links.join("plain")).unwrap();
+    let mut watcher = DirWatcher::open(&links).unwrap();
+
+    super::create(&target, &junction).unwrap();
+    let events = watcher.wait().unwrap();
+    assert_eq!(events[0].kind(), DirEventKind::Appeared);
+    assert_eq!(events[0].path(), junction);
+    assert_eq!(events[0].metadata().target(), Some(target.as_path()));
+
+    super::delete_and_remove_dir(&junction).unwrap();
+    let mut kinds = Vec::new();
+    while !kinds.contains(&DirEventKind::Disappeared) {
+        kinds.extend(watcher.wait().unwrap().iter().map(|event| event.kind()));
+    }
+}
+
+#[cfg(feature = "cap-std")]
+#[test]
+fn cap_std_create_and_delete() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    fs::create_dir_all(&target).unwrap();
+    let dir = cap_std::fs::Dir::open_ambient_dir(tmpdir.path(), cap_std::ambient_authority()).unwrap();
+
+    super::cap_std::create(&dir, &target, "junction").unwrap();
+    assert_eq!(super::cap_std::get_target(&dir, "junction").unwrap(), target);
+    match super::cap_std::create(&dir, "target", "relative") {
+        Err(ref e) if e.kind() == io::ErrorKind::InvalidInput => {}
+        other => panic!("target is not absolute: {:?}", other),
+    }
+    assert!(super::cap_std::get_target(&dir, r"..\junction").is_err());
+    super::cap_std::delete(&dir, "junction").unwrap();
+    match super::exists(tmpdir.path().join("junction")) {
+        Err(ref e) if e.raw_os_error() == Some(ERROR_NOT_A_REPARSE_POINT) => {}
+        other => panic!("junction had been deleted: {:?}", other),
+    }
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn nonblocking_create_and_delete() {
+    let tmpdir = create_tempdir();
+    let target = tmpdir.path().join("target");
+    let junction = tmpdir.path().join("junction");
+    fs::create_dir_all(&target).unwrap();
+
+    block_on(async {
+        super::nonblocking::create(&target, &junction).await.unwrap();
+        assert!(super::nonblocking::exists(&junction).await.unwrap());
+        assert_eq!(super::nonblocking::get_target(&junction).await.unwrap(), target);
+        super::nonblocking::delete(&junction).await.unwrap();
+    });
+}
+
+// Runs `future` on the current thread, parking it until the future is woken.
+#[cfg(feature = "async")]
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake};
+    use std::thread::{self, Thread};
+
+    struct Unparker(Thread);
+
+    impl Wake for Unparker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Arc::new(Unparker(thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
@@ -0,0 +1,33 @@
+//! Asynchronous versions of the junction operations for the [tokio] runtime.
+//!
+//! Each function runs its blocking counterpart on tokio's blocking thread pool
+//! with [`spawn_blocking`](::tokio::task::spawn_blocking), so it must be called
+//! from within a tokio runtime.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::io;
+//! # async fn run() -> io::Result<()> {
+//! junction::tokio::create(r"C:\target", r"C:\junction").await?;
+//! assert!(junction::tokio::exists(r"C:\junction").await?);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [tokio]: https://tokio.rs
+
+use std::io;
+
+async fn asyncify<F, T>(f: F) -> io::Result<T>
+where
+    F: FnOnce() -> io::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    match ::tokio::task::spawn_blocking(f).await {
+        Ok(res) => res,
+        Err(_) => Err(io::Error::new(io::ErrorKind::Other, "background task failed")),
+    }
+}
+
+async_wrappers!();
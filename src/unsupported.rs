@@ -0,0 +1,317 @@
+//! Stand-ins for `internals` on platforms without NTFS junctions.
+
+#[cfg(test)]
+mod tests;
+
+use std::ffi::OsStr;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    AppExecLink, DeleteOutcome, Diagnostics, DirEntry, FileId, FilesystemCapabilities, Guid, JunctionKind,
+    JunctionMetadata, Options, ReparseDump, ReparseKind, ResolvedJunction, Verification,
+};
+
+fn unsupported() -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, "junctions are only supported on Windows")
+}
+
+pub fn create(_target: &Path, _junction: &Path, _opts: &Options) -> io::Result<()> {
+    Err(unsupported())
+}
+
+pub fn create_checked(_target: &Path, _junction: &Path, _opts: &Options) -> io::Result<()> {
+    Err(unsupported())
+}
+
+pub fn create_resolved(_target: &Path, _junction: &Path, _opts: &Options) -> io::Result<ResolvedJunction> {
+    Err(unsupported())
+}
+
+pub fn create_with_base(_base: &Path, _target: &Path, _junction: &Path, _opts: &Options) -> io::Result<()> {
+    Err(unsupported())
+}
+
+pub fn join_lexically(_base: &Path, _path: &Path) -> io::Result<PathBuf> {
+    Err(unsupported())
+}
+
+pub fn create_to_volume(_volume: &OsStr, _junction: &Path, _opts: &Options) -> io::Result<()> {
+    Err(unsupported())
+}
+
+pub fn mount_point_data(_target: &Path) -> io::Result<Vec<u8>> {
+    Err(unsupported())
+}
+
+pub fn delete(_junction: &Path, _opts: &Options) -> io::Result<()> {
+    Err(unsupported())
+}
+
+pub fn dir_path(_dir: &std::fs::File) -> io::Result<PathBuf> {
+    Err(unsupported())
+}
+
+pub fn delete_in(_dir: &std::fs::File, _name: &Path, _opts: &Options) -> io::Result<()> {
+    Err(unsupported())
+}
+
+pub fn delete_if_target(_junction: &Path, _expected: &Path, _opts: &Options) -> io::Result<()> {
+    Err(unsupported())
+}
+
+pub fn delete_if_target_is(_junction: &Path, _expected: &Path, _opts: &Options) -> io::Result<DeleteOutcome> {
+    Err(unsupported())
+}
+
+#[cfg(feature = "raw")]
+pub fn raw_open(_path: &Path, _write: bool, _opts: &Options) -> io::Result<std::fs::File> {
+    Err(unsupported())
+}
+
+#[cfg(feature = "raw")]
+pub fn raw_get(_file: &std::fs::File) -> io::Result<Vec<u8>> {
+    Err(unsupported())
+}
+
+#[cfg(feature = "raw")]
+pub fn raw_set(_file: &std::fs::File, _data: &[u8]) -> io::Result<()> {
+    Err(unsupported())
+}
+
+#[cfg(feature = "raw")]
+pub fn raw_delete(_file: &std::fs::File) -> io::Result<()> {
+    Err(unsupported())
+}
+
+#[cfg(feature = "usn")]
+pub fn usn_open(_path: &Path) -> io::Result<(std::fs::File, u64, i64)> {
+    Err(unsupported())
+}
+
+#[cfg(feature = "usn")]
+pub fn usn_read(
+    _volume: &std::fs::File,
+    _journal_id: u64,
+    _start: i64,
+    _reasons: u32,
+    _wait: bool,
+    _buffer: &mut [u8],
+) -> io::Result<usize> {
+    Err(unsupported())
+}
+
+#[cfg(feature = "usn")]
+pub fn usn_reparse_tag(_volume: &std::fs::File, _file_id: u64) -> Option<u32> {
+    None
+}
+
+#[cfg(feature = "watch")]
+#[derive(Debug)]
+pub struct DirWatch(());
+
+#[cfg(feature = "watch")]
+impl DirWatch {
+    pub fn open(_path: &Path) -> io::Result<Self> {
+        Err(unsupported())
+    }
+
+    pub fn read(&mut self) -> io::Result<Vec<u8>> {
+        Err(unsupported())
+    }
+}
+
+#[cfg(feature = "backup")]
+pub fn backup_read(_junction: &Path, _opts: &Options) -> io::Result<Vec<u8>> {
+    Err(unsupported())
+}
+
+#[cfg(feature = "backup")]
+pub fn backup_restore(_junction: &Path, _data: &[u8], _opts: &Options) -> io::Result<()> {
+    Err(unsupported())
+}
+
+#[cfg(feature = "cap-std")]
+pub fn create_at(_dir: &::cap_std::fs::Dir, _target: &Path, _junction: &Path) -> io::Result<()> {
+    Err(unsupported())
+}
+
+#[cfg(feature = "cap-std")]
+pub fn delete_at(_dir: &::cap_std::fs::Dir, _junction: &Path) -> io::Result<()> {
+    Err(unsupported())
+}
+
+#[cfg(feature = "cap-std")]
+pub fn get_target_at(_dir: &::cap_std::fs::Dir, _junction: &Path) -> io::Result<PathBuf> {
+    Err(unsupported())
+}
+
+pub fn verify(_junction: &Path, _expected: &Path, _opts: &Options) -> io::Result<Verification> {
+    Err(unsupported())
+}
+
+pub fn create_idempotent(_target: &Path, _junction: &Path, _opts: &Options) -> io::Result<bool> {
+    Err(unsupported())
+}
+
+pub fn same_target(_a: &Path, _b: &Path, _opts: &Options) -> io::Result<bool> {
+    Err(unsupported())
+}
+
+pub fn file_id(_path: &Path, _follow: bool) -> io::Result<FileId> {
+    Err(unsupported())
+}
+
+pub fn set_target(_junction: &Path, _target: &Path, _opts: &Options) -> io::Result<()> {
+    Err(unsupported())
+}
+
+pub fn convert_to_symlink(_junction: &Path, _opts: &Options) -> io::Result<PathBuf> {
+    Err(unsupported())
+}
+
+pub fn convert_to_junction(_symlink: &Path, _opts: &Options) -> io::Result<PathBuf> {
+    Err(unsupported())
+}
+
+pub fn copy_junction(_src: &Path, _dst: &Path, _opts: &Options) -> io::Result<PathBuf> {
+    Err(unsupported())
+}
+
+pub fn move_junction(_src: &Path, _dst: &Path, _opts: &Options) -> io::Result<PathBuf> {
+    Err(unsupported())
+}
+
+pub fn debug_dump(_path: &Path, _opts: &Options) -> io::Result<ReparseDump> {
+    Err(unsupported())
+}
+
+pub fn explain(path: &Path) -> Diagnostics {
+    Diagnostics {
+        path: path.to_path_buf(),
+        full_path: Err(unsupported()),
+        nt_path: Err(unsupported()),
+        normalized: Err(unsupported()),
+        volume: Err(unsupported()),
+        attributes: Err(unsupported()),
+        reparse_tag: Err(unsupported()),
+        target: None,
+        privileges: Err(unsupported()),
+    }
+}
+
+pub fn filesystem_capabilities(_path: &Path) -> io::Result<FilesystemCapabilities> {
+    Err(unsupported())
+}
+
+pub fn symlink_metadata(_path: &Path) -> io::Result<std::fs::Metadata> {
+    Err(unsupported())
+}
+
+pub fn exists(_junction: &Path, _opts: &Options) -> io::Result<bool> {
+    Err(unsupported())
+}
+
+pub fn get_target(_junction: &Path, _opts: &Options) -> io::Result<PathBuf> {
+    Err(unsupported())
+}
+
+pub fn reparse_tag(_path: &Path) -> io::Result<Option<u32>> {
+    Err(unsupported())
+}
+
+pub fn read_dir_no_follow(_path: &Path) -> io::Result<Vec<DirEntry>> {
+    Err(unsupported())
+}
+
+pub fn metadata(_path: &Path, _opts: &Options) -> io::Result<JunctionMetadata> {
+    Err(unsupported())
+}
+
+pub fn get_target_into<'a>(_junction: &Path, _buf: &'a mut Vec<u16>, _opts: &Options) -> io::Result<&'a [u16]> {
+    Err(unsupported())
+}
+
+pub fn get_kind(_junction: &Path, _opts: &Options) -> io::Result<JunctionKind> {
+    Err(unsupported())
+}
+
+pub fn kind(_path: &Path, _opts: &Options) -> io::Result<Option<ReparseKind>> {
+    Err(unsupported())
+}
+
+pub fn get_app_exec_link(_path: &Path, _opts: &Options) -> io::Result<AppExecLink> {
+    Err(unsupported())
+}
+
+pub fn get_lx_symlink(_path: &Path, _opts: &Options) -> io::Result<String> {
+    Err(unsupported())
+}
+
+pub fn read_reparse_raw(_path: &Path, _opts: &Options) -> io::Result<(u32, Option<Guid>, Vec<u8>)> {
+    Err(unsupported())
+}
+
+pub fn write_reparse_raw(
+    _path: &Path,
+    _tag: u32,
+    _guid: Option<Guid>,
+    _data: &[u8],
+    _opts: &Options,
+) -> io::Result<()> {
+    Err(unsupported())
+}
+
+pub fn clear_privilege_cache() {}
+
+pub fn mount_volume(_volume: &Path, _mount_point: &Path) -> io::Result<()> {
+    Err(unsupported())
+}
+
+pub fn unmount_volume(_mount_point: &Path) -> io::Result<()> {
+    Err(unsupported())
+}
+
+/// Never constructed, since volumes cannot be listed.
+pub enum VolumeMountPoints {}
+
+pub fn list_volume_mount_points(_volume_root: &Path) -> io::Result<VolumeMountPoints> {
+    Err(unsupported())
+}
+
+impl Iterator for VolumeMountPoints {
+    type Item = io::Result<(PathBuf, PathBuf)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match *self {}
+    }
+}
+
+/// Never constructed, since reparse points cannot be opened.
+pub enum JunctionFile {}
+
+impl JunctionFile {
+    pub fn open(_path: &Path, _write: bool, _opts: &Options) -> io::Result<Self> {
+        Err(unsupported())
+    }
+
+    pub fn refresh(&self) {
+        match *self {}
+    }
+
+    pub fn tag(&self) -> io::Result<u32> {
+        match *self {}
+    }
+
+    pub fn target(&self) -> io::Result<PathBuf> {
+        match *self {}
+    }
+
+    pub fn print_name(&self) -> io::Result<PathBuf> {
+        match *self {}
+    }
+
+    pub fn delete(self) -> io::Result<()> {
+        match self {}
+    }
+}
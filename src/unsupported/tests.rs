@@ -0,0 +1,70 @@
+use std::io;
+
+fn assert_unsupported<T: std::fmt::Debug>(result: io::Result<T>) {
+    match result {
+        Err(ref e) if e.kind() == io::ErrorKind::Unsupported => {}
+        other => panic!("junctions are only supported on Windows: {:?}", other),
+    }
+}
+
+#[test]
+fn everything_is_unsupported() {
+    assert_unsupported(crate::create("target", "junction"));
+    assert_unsupported(crate::create_checked("target", "junction"));
+    assert_unsupported(crate::create_resolved("target", "junction"));
+    assert_unsupported(crate::create_with_base("/base", "target", "junction"));
+    assert_unsupported(crate::delete("junction"));
+    let dir = std::fs::File::open(".").unwrap();
+    assert_unsupported(crate::delete_at(&dir, "junction"));
+    assert_unsupported(crate::delete_if_target("junction", "target"));
+    assert_unsupported(crate::delete_if_target_is("junction", "target"));
+    assert_unsupported(crate::delete_and_remove_dir("junction"));
+    assert_unsupported(crate::exists("junction"));
+    assert_unsupported(crate::get_target("junction"));
+    assert_unsupported(crate::get_target_into("junction", &mut Vec::new()));
+    assert_unsupported(crate::metadata("junction"));
+    assert_unsupported(crate::reparse_tag("junction"));
+    assert_unsupported(crate::read_dir_no_follow("dir"));
+    assert_unsupported(crate::filesystem_capabilities("dir"));
+    assert_unsupported(crate::fs::symlink_dir("target", "junction"));
+    assert_unsupported(crate::fs::read_link("junction"));
+    assert_unsupported(crate::fs::symlink_metadata("junction"));
+    assert_unsupported(crate::junction_boundary("dir/junction"));
+    assert_unsupported(crate::split_at_junction("dir/junction"));
+    assert_unsupported(crate::convert_to_symlink("junction"));
+    assert_unsupported(crate::convert_to_junction("symlink"));
+    assert_unsupported(crate::copy_junction("junction", "copy"));
+    assert_unsupported(crate::move_junction("junction", "moved"));
+    assert_unsupported(crate::debug_dump("junction"));
+    assert_unsupported(crate::kind("junction"));
+    #[cfg(feature = "raw")]
+    assert_unsupported(crate::raw::open_reparse_point("junction", false));
+    let diagnostics = crate::explain("junction");
+    assert_eq!(diagnostics.full_path().unwrap_err().kind(), io::ErrorKind::Unsupported);
+    assert_eq!(diagnostics.privileges().unwrap_err().kind(), io::ErrorKind::Unsupported);
+    assert_unsupported(crate::JunctionFile::open("junction").map(|_| ()));
+    assert_unsupported(crate::JunctionFile::open_writable("junction").map(|_| ()));
+    assert_unsupported(crate::set_target("junction", "target"));
+    assert_unsupported(crate::same_target("a", "b"));
+    assert_unsupported(crate::file_id("junction"));
+    assert_unsupported(crate::target_file_id("junction"));
+    assert_unsupported(crate::list_volume_mount_points("/").map(|_| ()));
+}
+
+#[test]
+fn probe_reports_unsupported() {
+    let dir = std::env::temp_dir();
+    let capabilities = crate::can_create_junctions(&dir).unwrap();
+    assert!(!capabilities.is_supported());
+    assert!(!capabilities.can_create());
+    assert_eq!(capabilities.error().map(|e| e.kind()), Some(io::ErrorKind::Unsupported));
+    let leftovers = std::fs::read_dir(&dir)
+        .unwrap()
+        .filter(|entry| {
+            let name = entry.as_ref().unwrap().file_name();
+            name.to_string_lossy()
+                .starts_with(&format!(".junction-probe-{}-", std::process::id()))
+        })
+        .count();
+    assert_eq!(leftovers, 0);
+}
@@ -0,0 +1,344 @@
+//! Watching a whole volume for junction changes through the NTFS change journal.
+//!
+//! A [`UsnWatcher`] reads the update sequence number (USN) change journal of a
+//! volume with `FSCTL_READ_USN_JOURNAL`, asking only for records of created
+//! and deleted files and of reparse point changes, and turns those into
+//! [`UsnEvent`]s. Unlike watching directories, this covers the whole volume
+//! with a single handle, and changes made while nothing was watching can be
+//! read afterwards, as long as the journal still holds them.
+//!
+//! Reading the change journal requires administrator rights, and the journal
+//! must be active, as it is on system volumes. It can be created with
+//! `fsutil usn createjournal`.
+//!
+//! # Limits
+//!
+//! The journal records names and file IDs, not full paths, and no reparse
+//! tags. The tag of a junction point that was created or retargeted is read
+//! from the file when the record is read, and events for other kinds of reparse
+//! points, such as symbolic links, are skipped. A reparse point that is gone by
+//! then, and every removed reparse point, is reported with an unknown tag.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::io;
+//! use junction::usn::UsnWatcher;
+//!
+//! fn main() -> io::Result<()> {
+//!     let mut watcher = UsnWatcher::open(r"C:\")?;
+//!     loop {
+//!         for event in watcher.wait()? {
+//!             println!("{:?} {:?} in directory {:#x}", event.kind(), event.name(), event.parent_id());
+//!         }
+//!     }
+//! }
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::consts::IO_REPARSE_TAG_MOUNT_POINT;
+use crate::internals;
+
+const USN_REASON_FILE_CREATE: u32 = 0x0000_0100;
+const USN_REASON_FILE_DELETE: u32 = 0x0000_0200;
+const USN_REASON_REPARSE_POINT_CHANGE: u32 = 0x0010_0000;
+const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x0000_0400;
+// The size of the buffer records are read into, as used by the Windows samples.
+const BUFFER_SIZE: usize = 64 * 1024;
+// The number of created files remembered until they become reparse points.
+const MAX_PENDING_CREATES: usize = 4096;
+
+/// What happened to a junction point, as reported by an [`UsnEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum UsnEventKind {
+    /// A junction point was created, or a new file became one.
+    Created,
+    /// The reparse data of an existing junction point was replaced, which
+    /// usually changes its target.
+    Retargeted,
+    /// A reparse point was deleted, or a file that was one was deleted.
+    Removed,
+}
+
+/// A change of a junction point read from the change journal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsnEvent {
+    kind: UsnEventKind,
+    usn: i64,
+    file_id: u64,
+    parent_id: u64,
+    name: OsString,
+    time: SystemTime,
+    reparse_tag: Option<u32>,
+}
+
+impl UsnEvent {
+    /// Returns what happened.
+    pub fn kind(&self) -> UsnEventKind {
+        self.kind
+    }
+
+    /// Returns the update sequence number of the journal record.
+    pub fn usn(&self) -> i64 {
+        self.usn
+    }
+
+    /// Returns the file ID of the junction point.
+    pub fn file_id(&self) -> u64 {
+        self.file_id
+    }
+
+    /// Returns the file ID of the directory containing the junction point.
+    pub fn parent_id(&self) -> u64 {
+        self.parent_id
+    }
+
+    /// Returns the name of the junction point in its directory.
+    pub fn name(&self) -> &OsString {
+        &self.name
+    }
+
+    /// Returns when the change was recorded.
+    pub fn time(&self) -> SystemTime {
+        self.time
+    }
+
+    /// Returns the reparse tag read after the change, or `None` if it is unknown.
+    ///
+    /// The tag is only read for [`Created`](UsnEventKind::Created) and
+    /// [`Retargeted`](UsnEventKind::Retargeted) events, and is then
+    /// `IO_REPARSE_TAG_MOUNT_POINT` unless the file was gone already.
+    pub fn reparse_tag(&self) -> Option<u32> {
+        self.reparse_tag
+    }
+}
+
+/// Reads the junction changes of a volume from its change journal.
+///
+/// See the [module documentation](self).
+#[derive(Debug)]
+pub struct UsnWatcher {
+    volume: File,
+    journal_id: u64,
+    next_usn: i64,
+    // Files created recently, whose first reparse point is a creation rather than a retargeting.
+    created: HashSet<u64>,
+    buffer: Vec<u8>,
+}
+
+impl UsnWatcher {
+    /// Opens the change journal of the volume holding `path`, to read the
+    /// changes made from now on.
+    ///
+    /// # Error
+    ///
+    /// This function errors with `ERROR_ACCESS_DENIED` without administrator
+    /// rights, and with `ERROR_JOURNAL_NOT_ACTIVE` if the volume has no journal.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let (volume, journal_id, next_usn) = internals::usn_open(path.as_ref())?;
+        Ok(UsnWatcher {
+            volume,
+            journal_id,
+            next_usn,
+            created: HashSet::new(),
+            buffer: vec![0; BUFFER_SIZE],
+        })
+    }
+
+    /// Sets the update sequence number to read from next, such as one
+    /// returned by [`next_usn`](UsnWatcher::next_usn) in an earlier run.
+    ///
+    /// Reading fails with `ERROR_JOURNAL_ENTRY_DELETED` if the journal no longer holds this record.
+    pub fn seek(&mut self, usn: i64) -> &mut Self {
+        self.next_usn = usn;
+        self.created.clear();
+        self
+    }
+
+    /// Returns the update sequence number that the next read starts at.
+    pub fn next_usn(&self) -> i64 {
+        self.next_usn
+    }
+
+    /// Returns the changes recorded since the last read, without waiting.
+    ///
+    /// Reading stops at the end of a buffer of records, so more changes may
+    /// be left to read, even if none of the records read was about a junction
+    /// point and the returned list is empty. All were read once
+    /// [`next_usn`](UsnWatcher::next_usn) stops changing.
+    pub fn poll(&mut self) -> io::Result<Vec<UsnEvent>> {
+        self.read(false)
+    }
+
+    /// Returns the changes recorded since the last read, waiting for one if there are none.
+    pub fn wait(&mut self) -> io::Result<Vec<UsnEvent>> {
+        loop {
+            let events = self.read(true)?;
+            if !events.is_empty() {
+                return Ok(events);
+            }
+        }
+    }
+
+    fn read(&mut self, wait: bool) -> io::Result<Vec<UsnEvent>> {
+        let reasons = USN_REASON_FILE_CREATE | USN_REASON_FILE_DELETE | USN_REASON_REPARSE_POINT_CHANGE;
+        let len = internals::usn_read(
+            &self.volume,
+            self.journal_id,
+            self.next_usn,
+            reasons,
+            wait,
+            &mut self.buffer,
+        )?;
+        let (next_usn, records) = parse_records(&self.buffer[..len])?;
+        self.next_usn = next_usn;
+        let mut events = Vec::new();
+        for record in records {
+            let kind = match classify(&record, &mut self.created) {
+                Some(kind) => kind,
+                None => continue,
+            };
+            let reparse_tag = match kind {
+                UsnEventKind::Removed => None,
+                _ => internals::usn_reparse_tag(&self.volume, record.file_id),
+            };
+            if reparse_tag.map_or(false, |tag| tag != IO_REPARSE_TAG_MOUNT_POINT) {
+                continue;
+            }
+            events.push(UsnEvent {
+                kind,
+                usn: record.usn,
+                file_id: record.file_id,
+                parent_id: record.parent_id,
+                name: os_string(&record.name),
+                time: system_time(record.timestamp),
+                reparse_tag,
+            });
+        }
+        if self.created.len() > MAX_PENDING_CREATES {
+            self.created.clear();
+        }
+        Ok(events)
+    }
+}
+
+// The fields of a `USN_RECORD_V2` used for events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Record {
+    file_id: u64,
+    parent_id: u64,
+    usn: i64,
+    timestamp: i64,
+    reason: u32,
+    attributes: u32,
+    name: Vec<u16>,
+}
+
+// Parses the output of `FSCTL_READ_USN_JOURNAL`: the USN to read from next,
+// followed by records. Records of versions other than 2 are skipped.
+fn parse_records(bytes: &[u8]) -> io::Result<(i64, Vec<Record>)> {
+    const HEADER_SIZE: usize = 60;
+
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed change journal record");
+    let u16_at = |b: &[u8], i: usize| u16::from_le_bytes([b[i], b[i + 1]]);
+    let u32_at = |b: &[u8], i: usize| u32::from_le_bytes([b[i], b[i + 1], b[i + 2], b[i + 3]]);
+    let u64_at = |b: &[u8], i: usize| u64::from(u32_at(b, i)) | u64::from(u32_at(b, i + 4)) << 32;
+
+    if bytes.len() < 8 {
+        return Err(invalid());
+    }
+    let next_usn = u64_at(bytes, 0) as i64;
+    let mut records = Vec::new();
+    let mut rest = &bytes[8..];
+    while !rest.is_empty() {
+        if rest.len() < 4 {
+            return Err(invalid());
+        }
+        let len = u32_at(rest, 0) as usize;
+        if len < 8 || len > rest.len() {
+            return Err(invalid());
+        }
+        let record = &rest[..len];
+        rest = &rest[len..];
+        if u16_at(record, 4) != 2 {
+            continue;
+        }
+        if len < HEADER_SIZE {
+            return Err(invalid());
+        }
+        let name_len = usize::from(u16_at(record, 56));
+        let name_offset = usize::from(u16_at(record, 58));
+        let name = record.get(name_offset..name_offset + name_len).ok_or_else(invalid)?;
+        records.push(Record {
+            file_id: u64_at(record, 8),
+            parent_id: u64_at(record, 16),
+            usn: u64_at(record, 24) as i64,
+            timestamp: u64_at(record, 32) as i64,
+            reason: u32_at(record, 40),
+            attributes: u32_at(record, 52),
+            name: name.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect(),
+        });
+    }
+    Ok((next_usn, records))
+}
+
+// Returns the event of `record`, if it is about a reparse point. `created`
+// holds the files created since, which are removed once they become reparse points.
+fn classify(record: &Record, created: &mut HashSet<u64>) -> Option<UsnEventKind> {
+    let is_reparse_point = record.attributes & FILE_ATTRIBUTE_REPARSE_POINT != 0;
+    if record.reason & USN_REASON_FILE_DELETE != 0 {
+        created.remove(&record.file_id);
+        return if is_reparse_point {
+            Some(UsnEventKind::Removed)
+        } else {
+            None
+        };
+    }
+    let is_new = record.reason & USN_REASON_FILE_CREATE != 0;
+    if record.reason & USN_REASON_REPARSE_POINT_CHANGE == 0 {
+        if is_new {
+            created.insert(record.file_id);
+        }
+        return None;
+    }
+    if !is_reparse_point {
+        Some(UsnEventKind::Removed)
+    } else if created.remove(&record.file_id) || is_new {
+        Some(UsnEventKind::Created)
+    } else {
+        Some(UsnEventKind::Retargeted)
+    }
+}
+
+// Converts a `FILETIME`, in 100 ns intervals since 1601, to a `SystemTime`.
+fn system_time(filetime: i64) -> SystemTime {
+    const UNIX_EPOCH_FILETIME: i64 = 116_444_736_000_000_000;
+    let intervals = filetime - UNIX_EPOCH_FILETIME;
+    let since_epoch = Duration::from_nanos(intervals.unsigned_abs() * 100);
+    if intervals >= 0 {
+        UNIX_EPOCH + since_epoch
+    } else {
+        UNIX_EPOCH - since_epoch
+    }
+}
+
+#[cfg(windows)]
+fn os_string(wide: &[u16]) -> OsString {
+    use std::os::windows::ffi::OsStringExt;
+    OsString::from_wide(wide)
+}
+
+#[cfg(not(windows))]
+fn os_string(wide: &[u16]) -> OsString {
+    String::from_utf16_lossy(wide).into()
+}
@@ -0,0 +1,102 @@
+use super::*;
+
+fn record_bytes(file_id: u64, reason: u32, attributes: u32, name: &str) -> Vec<u8> {
+    let name: Vec<u8> = name.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    let len = (60 + name.len() + 7) / 8 * 8;
+    let mut bytes = Vec::with_capacity(len);
+    bytes.extend_from_slice(&(len as u32).to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&0u16.to_le_bytes());
+    bytes.extend_from_slice(&file_id.to_le_bytes());
+    bytes.extend_from_slice(&5u64.to_le_bytes());
+    bytes.extend_from_slice(&(file_id as i64 * 10).to_le_bytes());
+    bytes.extend_from_slice(&116_444_736_000_000_000i64.to_le_bytes());
+    bytes.extend_from_slice(&reason.to_le_bytes());
+    bytes.extend_from_slice(&[0; 8]);
+    bytes.extend_from_slice(&attributes.to_le_bytes());
+    bytes.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(&60u16.to_le_bytes());
+    bytes.extend_from_slice(&name);
+    bytes.resize(len, 0);
+    bytes
+}
+
+#[test]
+fn parse_v2_records() {
+    let mut bytes = 1234i64.to_le_bytes().to_vec();
+    bytes.extend(record_bytes(7, USN_REASON_FILE_CREATE, 0x10, "link"));
+    // A version 3 record, which is skipped.
+    let mut v3 = record_bytes(8, 0, 0, "x");
+    v3[4] = 3;
+    bytes.extend(v3);
+    bytes.extend(record_bytes(9, USN_REASON_REPARSE_POINT_CHANGE, 0x410, "other"));
+
+    let (next_usn, records) = parse_records(&bytes).unwrap();
+    assert_eq!(next_usn, 1234);
+    assert_eq!(records.len(), 2);
+    assert_eq!(
+        records[0],
+        Record {
+            file_id: 7,
+            parent_id: 5,
+            usn: 70,
+            timestamp: 116_444_736_000_000_000,
+            reason: USN_REASON_FILE_CREATE,
+            attributes: 0x10,
+            name: "link".encode_utf16().collect(),
+        }
+    );
+    assert_eq!(records[1].file_id, 9);
+    assert_eq!(system_time(records[0].timestamp), UNIX_EPOCH);
+}
+
+#[test]
+fn parse_rejects_truncated_records() {
+    let mut bytes = 0i64.to_le_bytes().to_vec();
+    bytes.extend(record_bytes(7, 0, 0, "link"));
+    bytes.pop();
+    assert_eq!(parse_records(&bytes).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    assert!(parse_records(&[0; 4]).is_err());
+    assert_eq!(parse_records(&[0; 8]).unwrap(), (0, Vec::new()));
+}
+
+#[test]
+fn classify_changes() {
+    let record = |file_id, reason, attributes| Record {
+        file_id,
+        parent_id: 5,
+        usn: 0,
+        timestamp: 0,
+        reason,
+        attributes,
+        name: Vec::new(),
+    };
+    let mut created = HashSet::new();
+    // A directory is created, then made a junction point through another handle.
+    assert_eq!(classify(&record(1, USN_REASON_FILE_CREATE, 0x10), &mut created), None);
+    assert_eq!(
+        classify(&record(1, USN_REASON_REPARSE_POINT_CHANGE, 0x410), &mut created),
+        Some(UsnEventKind::Created)
+    );
+    assert_eq!(
+        classify(&record(1, USN_REASON_REPARSE_POINT_CHANGE, 0x410), &mut created),
+        Some(UsnEventKind::Retargeted)
+    );
+    assert_eq!(
+        classify(&record(1, USN_REASON_REPARSE_POINT_CHANGE, 0x10), &mut created),
+        Some(UsnEventKind::Removed)
+    );
+    assert_eq!(
+        classify(
+            &record(2, USN_REASON_FILE_CREATE | USN_REASON_REPARSE_POINT_CHANGE, 0x410),
+            &mut created
+        ),
+        Some(UsnEventKind::Created)
+    );
+    assert_eq!(
+        classify(&record(2, USN_REASON_FILE_DELETE, 0x410), &mut created),
+        Some(UsnEventKind::Removed)
+    );
+    assert_eq!(classify(&record(3, USN_REASON_FILE_DELETE, 0x10), &mut created), None);
+    assert!(created.is_empty());
+}
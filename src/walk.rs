@@ -0,0 +1,177 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::FileId;
+
+/// What a [`WalkEntry`] is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EntryKind {
+    /// A plain directory.
+    Dir,
+    /// A junction point or volume mount point, with its target as returned by
+    /// [`get_target`](crate::get_target).
+    Junction {
+        /// The target of the junction point.
+        target: PathBuf,
+    },
+    /// A symbolic link, or another name surrogate that is not a junction point.
+    Symlink,
+    /// Anything else, such as a regular file.
+    File,
+}
+
+/// An entry yielded by [`Walk`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalkEntry {
+    path: PathBuf,
+    depth: usize,
+    kind: EntryKind,
+}
+
+impl WalkEntry {
+    /// Returns the path of the entry, which starts with the root of the walk.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the depth of the entry below the root, 1 for its direct children.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns what the entry is.
+    pub fn kind(&self) -> &EntryKind {
+        &self.kind
+    }
+}
+
+/// An iterator over the entries below a directory, created by [`walk`].
+#[derive(Debug)]
+pub struct Walk {
+    root: Option<PathBuf>,
+    follow_junctions: bool,
+    // Entries still to visit, in reverse order.
+    stack: Vec<io::Result<(fs::DirEntry, usize)>>,
+    // Directories entered so far when following junction points, to avoid cycles.
+    visited: HashSet<FileId>,
+}
+
+/// Walks the tree below `root`, classifying every entry.
+///
+/// Entries are yielded depth first, each directory before its content, and
+/// the entries of a directory in the order of their names. The root itself is
+/// not yielded. By default, junction points are yielded but not entered; see
+/// [`Walk::follow_junctions`]. Symbolic links are never followed.
+///
+/// Errors, such as a directory that cannot be read, are yielded in place of the
+/// entries concerned, and the walk continues with the next entry.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::io;
+/// use junction::{walk, EntryKind};
+///
+/// fn main() -> io::Result<()> {
+///     for entry in walk(r"C:\workspace") {
+///         let entry = entry?;
+///         if let EntryKind::Junction { target } = entry.kind() {
+///             println!("{} -> {}", entry.path().display(), target.display());
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn walk<P: AsRef<Path>>(root: P) -> Walk {
+    Walk {
+        root: Some(root.as_ref().to_path_buf()),
+        follow_junctions: false,
+        stack: Vec::new(),
+        visited: HashSet::new(),
+    }
+}
+
+impl Walk {
+    /// Sets whether the walk enters junction points as if they were directories.
+    ///
+    /// The entries found through a junction point have paths below the junction
+    /// point, not below its target. Directories are entered only once, so that a
+    /// junction point to one of its parent directories does not lead to a cycle.
+    pub fn follow_junctions(mut self, follow: bool) -> Self {
+        self.follow_junctions = follow;
+        self
+    }
+
+    // Queues the entries of `dir`, or the error reading it. When following
+    // junction points, directories that were already entered are skipped.
+    fn enter(&mut self, dir: &Path, depth: usize) {
+        if self.follow_junctions {
+            match crate::target_file_id(dir) {
+                Ok(id) if !self.visited.insert(id) => return,
+                Ok(_) => {}
+                Err(e) => {
+                    self.stack.push(Err(e));
+                    return;
+                }
+            }
+        }
+        let children = fs::read_dir(dir).and_then(|entries| entries.collect::<io::Result<Vec<_>>>());
+        match children {
+            Ok(mut children) => {
+                children.sort_by_key(|child| child.file_name());
+                self.stack
+                    .extend(children.into_iter().rev().map(|child| Ok((child, depth + 1))));
+            }
+            Err(e) => self.stack.push(Err(e)),
+        }
+    }
+
+    fn classify(child: &fs::DirEntry) -> io::Result<EntryKind> {
+        let file_type = child.file_type()?;
+        // Junctions are name surrogates, which `std` reports as symlinks.
+        if file_type.is_symlink() {
+            let metadata = crate::metadata(child.path())?;
+            Ok(match metadata.target() {
+                Some(target) => EntryKind::Junction {
+                    target: target.to_path_buf(),
+                },
+                None => EntryKind::Symlink,
+            })
+        } else if file_type.is_dir() {
+            Ok(EntryKind::Dir)
+        } else {
+            Ok(EntryKind::File)
+        }
+    }
+}
+
+impl Iterator for Walk {
+    type Item = io::Result<WalkEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(root) = self.root.take() {
+            self.enter(&root, 0);
+        }
+        let (child, depth) = match self.stack.pop()? {
+            Ok(item) => item,
+            Err(e) => return Some(Err(e)),
+        };
+        let path = child.path();
+        let kind = match Self::classify(&child) {
+            Ok(kind) => kind,
+            Err(e) => return Some(Err(e)),
+        };
+        match kind {
+            EntryKind::Dir => self.enter(&path, depth),
+            EntryKind::Junction { .. } if self.follow_junctions => self.enter(&path, depth),
+            _ => {}
+        }
+        Some(Ok(WalkEntry { path, depth, kind }))
+    }
+}
@@ -0,0 +1,42 @@
+use super::*;
+
+#[test]
+fn walk_classifies_entries_in_order() {
+    let root = std::env::temp_dir().join(format!("junction-walk-{}", std::process::id()));
+    fs::create_dir_all(root.join("b").join("c")).unwrap();
+    fs::create_dir_all(root.join("a")).unwrap();
+    fs::write(root.join("b").join("file"), b"").unwrap();
+
+    let entries = walk(&root).collect::<io::Result<Vec<_>>>();
+    fs::remove_dir_all(&root).unwrap();
+    let entries: Vec<_> = entries
+        .unwrap()
+        .into_iter()
+        .map(|entry| {
+            (
+                entry.path().strip_prefix(&root).unwrap().to_path_buf(),
+                entry.depth(),
+                entry.kind().clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        entries,
+        [
+            (PathBuf::from("a"), 1, EntryKind::Dir),
+            (PathBuf::from("b"), 1, EntryKind::Dir),
+            (Path::new("b").join("c"), 2, EntryKind::Dir),
+            (Path::new("b").join("file"), 2, EntryKind::File),
+        ]
+    );
+}
+
+#[test]
+fn walk_yields_read_errors() {
+    let mut entries = walk(std::env::temp_dir().join("junction-walk-missing"));
+    match entries.next() {
+        Some(Err(ref e)) if e.kind() == io::ErrorKind::NotFound => {}
+        other => panic!("root does not exist: {:?}", other),
+    }
+    assert!(entries.next().is_none());
+}
@@ -0,0 +1,247 @@
+//! Watching a directory of junction points with `ReadDirectoryChangesW`.
+//!
+//! A [`DirWatcher`] watches the direct entries of one directory, such as a
+//! folder of junction points pointing to package versions, and reports
+//! [`DirEvent`]s when a junction point appears, disappears, or has its
+//! attributes or reparse data changed. Each event carries the
+//! [`JunctionMetadata`] read right after the change. Unlike the change journal
+//! read by the `usn` module, this needs no administrator rights, but only
+//! covers a single directory and misses changes made while nothing waits.
+//!
+//! The watcher keeps the names of the junction points in the directory, so
+//! that a plain directory or file turning into a junction point is reported as
+//! appearing, and a junction point whose reparse point is deleted as
+//! disappearing. Other entries are not reported.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::io;
+//! use junction::watch::DirWatcher;
+//!
+//! fn main() -> io::Result<()> {
+//!     let mut watcher = DirWatcher::open(r"C:\app\links")?;
+//!     loop {
+//!         for event in watcher.wait()? {
+//!             println!("{:?} {} {:?}", event.kind(), event.path().display(), event.metadata().target());
+//!         }
+//!     }
+//! }
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeSet;
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{internals, read_dir_no_follow, JunctionMetadata, Options};
+
+const FILE_ACTION_ADDED: u32 = 1;
+const FILE_ACTION_REMOVED: u32 = 2;
+const FILE_ACTION_MODIFIED: u32 = 3;
+const FILE_ACTION_RENAMED_OLD_NAME: u32 = 4;
+const FILE_ACTION_RENAMED_NEW_NAME: u32 = 5;
+
+/// What happened to a junction point, as reported by a [`DirEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DirEventKind {
+    /// A junction point was created or renamed into the directory, or an
+    /// existing entry became one.
+    Appeared,
+    /// A junction point was deleted or renamed out of the directory, or its
+    /// reparse point was deleted.
+    Disappeared,
+    /// The attributes, timestamps or reparse data of a junction point changed.
+    Changed,
+}
+
+/// A change of a junction point in a directory watched by a [`DirWatcher`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEvent {
+    kind: DirEventKind,
+    path: PathBuf,
+    metadata: JunctionMetadata,
+}
+
+impl DirEvent {
+    /// Returns what happened.
+    pub fn kind(&self) -> DirEventKind {
+        self.kind
+    }
+
+    /// Returns the path of the junction point, which is the watched directory joined with its name.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns what [`metadata`](crate::metadata) found at the path after the change.
+    ///
+    /// For [`Disappeared`](DirEventKind::Disappeared) events, it tells whether
+    /// something else is left at the path.
+    pub fn metadata(&self) -> &JunctionMetadata {
+        &self.metadata
+    }
+}
+
+/// Watches the direct entries of a directory for junction point changes.
+///
+/// See the [module documentation](self).
+#[derive(Debug)]
+pub struct DirWatcher {
+    watch: internals::DirWatch,
+    path: PathBuf,
+    // The names of the junction points in the directory.
+    junctions: BTreeSet<OsString>,
+    opts: Options,
+}
+
+impl DirWatcher {
+    /// Starts watching the directory `path`.
+    ///
+    /// Notifications are queued from now on, even while no call to
+    /// [`wait`](DirWatcher::wait) is in progress.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let watch = internals::DirWatch::open(&path)?;
+        let mut watcher = DirWatcher {
+            watch,
+            path,
+            junctions: BTreeSet::new(),
+            opts: Options::new(),
+        };
+        // Notifications are already queued, so no change is missed in between.
+        watcher.junctions = watcher.scan()?;
+        Ok(watcher)
+    }
+
+    /// Waits for changes, and returns those about junction points.
+    ///
+    /// Changes of other entries are skipped, so this waits until at least one
+    /// event is found. If more changes happened than the buffer of
+    /// notifications holds, the directory is listed again and the differences
+    /// are reported.
+    pub fn wait(&mut self) -> io::Result<Vec<DirEvent>> {
+        loop {
+            let bytes = self.watch.read()?;
+            // No notifications are returned when they overflowed the buffer.
+            let events = if bytes.is_empty() {
+                self.rescan()?
+            } else {
+                let notifications = parse_notifications(&bytes)?;
+                let mut events = Vec::new();
+                for (action, name) in notifications {
+                    events.extend(self.event_of(action, name)?);
+                }
+                events
+            };
+            if !events.is_empty() {
+                return Ok(events);
+            }
+        }
+    }
+
+    // Returns the event for the notification `action` about `name`, if it is about a junction point.
+    fn event_of(&mut self, action: u32, name: OsString) -> io::Result<Option<DirEvent>> {
+        let path = self.path.join(&name);
+        let metadata = self.opts.metadata(&path)?;
+        let was_junction = self.junctions.contains(&name);
+        let kind = match action {
+            FILE_ACTION_REMOVED | FILE_ACTION_RENAMED_OLD_NAME if was_junction => DirEventKind::Disappeared,
+            FILE_ACTION_ADDED | FILE_ACTION_RENAMED_NEW_NAME | FILE_ACTION_MODIFIED => {
+                match (was_junction, metadata.is_junction()) {
+                    (false, true) => DirEventKind::Appeared,
+                    (true, false) => DirEventKind::Disappeared,
+                    (true, true) => DirEventKind::Changed,
+                    (false, false) => return Ok(None),
+                }
+            }
+            _ => return Ok(None),
+        };
+        match kind {
+            DirEventKind::Disappeared => self.junctions.remove(&name),
+            _ => self.junctions.insert(name),
+        };
+        Ok(Some(DirEvent { kind, path, metadata }))
+    }
+
+    // Lists the names of the junction points in the directory.
+    fn scan(&self) -> io::Result<BTreeSet<OsString>> {
+        Ok(read_dir_no_follow(&self.path)?
+            .into_iter()
+            .filter(|entry| entry.is_junction())
+            .map(|entry| entry.file_name().to_os_string())
+            .collect())
+    }
+
+    // Lists the directory again after notifications were lost, and returns
+    // the junction points that appeared or disappeared since.
+    fn rescan(&mut self) -> io::Result<Vec<DirEvent>> {
+        let junctions = self.scan()?;
+        let mut events = Vec::new();
+        for name in self.junctions.difference(&junctions) {
+            let path = self.path.join(name);
+            let metadata = self.opts.metadata(&path)?;
+            events.push(DirEvent {
+                kind: DirEventKind::Disappeared,
+                path,
+                metadata,
+            });
+        }
+        for name in junctions.difference(&self.junctions) {
+            let path = self.path.join(name);
+            let metadata = self.opts.metadata(&path)?;
+            events.push(DirEvent {
+                kind: DirEventKind::Appeared,
+                path,
+                metadata,
+            });
+        }
+        self.junctions = junctions;
+        Ok(events)
+    }
+}
+
+// Parses a buffer of `FILE_NOTIFY_INFORMATION` into actions and names.
+fn parse_notifications(bytes: &[u8]) -> io::Result<Vec<(u32, OsString)>> {
+    const HEADER_SIZE: usize = 12;
+
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed directory change notification");
+    let u32_at = |b: &[u8], i: usize| u32::from_le_bytes([b[i], b[i + 1], b[i + 2], b[i + 3]]);
+
+    let mut notifications = Vec::new();
+    let mut rest = bytes;
+    loop {
+        if rest.len() < HEADER_SIZE {
+            return Err(invalid());
+        }
+        let next = u32_at(rest, 0) as usize;
+        let action = u32_at(rest, 4);
+        let name_len = u32_at(rest, 8) as usize;
+        let name = rest
+            .get(HEADER_SIZE..HEADER_SIZE + name_len)
+            .ok_or_else(invalid)?
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect::<Vec<_>>();
+        notifications.push((action, os_string(&name)));
+        if next == 0 {
+            return Ok(notifications);
+        }
+        rest = rest.get(next..).ok_or_else(invalid)?;
+    }
+}
+
+#[cfg(windows)]
+fn os_string(wide: &[u16]) -> OsString {
+    use std::os::windows::ffi::OsStringExt;
+    OsString::from_wide(wide)
+}
+
+#[cfg(not(windows))]
+fn os_string(wide: &[u16]) -> OsString {
+    String::from_utf16_lossy(wide).into()
+}
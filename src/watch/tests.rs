@@ -0,0 +1,37 @@
+use super::*;
+
+fn notification(next: u32, action: u32, name: &str) -> Vec<u8> {
+    let name: Vec<u8> = name.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&next.to_le_bytes());
+    bytes.extend_from_slice(&action.to_le_bytes());
+    bytes.extend_from_slice(&(name.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&name);
+    bytes
+}
+
+#[test]
+fn parse_notification_chain() {
+    let mut bytes = notification(24, FILE_ACTION_ADDED, "link");
+    bytes.resize(24, 0);
+    bytes.extend(notification(0, FILE_ACTION_RENAMED_OLD_NAME, "old"));
+    assert_eq!(
+        parse_notifications(&bytes).unwrap(),
+        [
+            (FILE_ACTION_ADDED, OsString::from("link")),
+            (FILE_ACTION_RENAMED_OLD_NAME, OsString::from("old")),
+        ]
+    );
+}
+
+#[test]
+fn parse_rejects_truncated_notifications() {
+    let mut bytes = notification(0, FILE_ACTION_MODIFIED, "link");
+    bytes.pop();
+    assert_eq!(
+        parse_notifications(&bytes).unwrap_err().kind(),
+        io::ErrorKind::InvalidData
+    );
+    let bytes = notification(64, FILE_ACTION_REMOVED, "link");
+    assert!(parse_notifications(&bytes).is_err());
+}